@@ -0,0 +1,34 @@
+-- Durable queue for video processing; workers claim jobs with FOR UPDATE SKIP LOCKED
+CREATE TABLE IF NOT EXISTS processing_jobs (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    video_id UUID NOT NULL REFERENCES videos(id) ON DELETE CASCADE,
+    filename VARCHAR(255) NOT NULL,
+    benchmark_run_id UUID REFERENCES benchmark_runs(id) ON DELETE SET NULL,
+    status VARCHAR(20) NOT NULL DEFAULT 'queued' CHECK (status IN ('queued', 'running', 'completed', 'failed')),
+    attempts INTEGER NOT NULL DEFAULT 0,
+    max_attempts INTEGER NOT NULL DEFAULT 3,
+    run_after TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    locked_by TEXT,
+    heartbeat_at TIMESTAMPTZ,
+    last_error TEXT,
+    started_at TIMESTAMPTZ,
+    finished_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_processing_jobs_queued
+    ON processing_jobs(run_after) WHERE status = 'queued';
+CREATE INDEX IF NOT EXISTS idx_processing_jobs_running_heartbeat
+    ON processing_jobs(heartbeat_at) WHERE status = 'running';
+
+-- At most one pending job per video
+CREATE UNIQUE INDEX IF NOT EXISTS idx_processing_jobs_active_video
+    ON processing_jobs(video_id) WHERE status IN ('queued', 'running');
+
+CREATE TRIGGER update_processing_jobs_updated_at BEFORE UPDATE ON processing_jobs
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
+
+COMMENT ON COLUMN processing_jobs.filename IS 'Storage filename of the original, resolved through get_video_path';
+COMMENT ON COLUMN processing_jobs.run_after IS 'Earliest time the job may be claimed (retry backoff)';
+COMMENT ON COLUMN processing_jobs.heartbeat_at IS 'Last heartbeat from the worker holding the job';
@@ -0,0 +1,6 @@
+-- Why a video ended up failed, for videos that never finished uploading or processing
+ALTER TABLE videos
+    ADD COLUMN IF NOT EXISTS failure_reason TEXT;
+
+-- Speeds up the recovery sweep looking for videos stuck in uploading/processing
+CREATE INDEX IF NOT EXISTS idx_videos_status_updated_at ON videos(status, updated_at);
@@ -0,0 +1,8 @@
+-- Processing progress reported by ffmpeg while a video is being encoded
+ALTER TABLE videos
+    ADD COLUMN IF NOT EXISTS progress_percent DOUBLE PRECISION,
+    ADD COLUMN IF NOT EXISTS rendition_progress JSONB,
+    ADD COLUMN IF NOT EXISTS progress_updated_at TIMESTAMPTZ;
+
+COMMENT ON COLUMN videos.progress_percent IS 'Overall processing progress, 0-100';
+COMMENT ON COLUMN videos.rendition_progress IS 'Encoding progress per HLS rendition, e.g. {"720p": 42.5}';
@@ -0,0 +1,5 @@
+-- Structured record of the last processing failure: category, failing step, ffmpeg stderr tail and attempts
+ALTER TABLE videos
+    ADD COLUMN IF NOT EXISTS failure_details JSONB;
+
+COMMENT ON COLUMN videos.failure_details IS 'e.g. {"category": "corrupt_input", "step": "generate_hls_streams", "stderr_tail": "...", "attempts": 3}';
@@ -0,0 +1,3 @@
+-- Encoding ladder picked at upload time; NULL means the configured default
+ALTER TABLE videos
+    ADD COLUMN IF NOT EXISTS encoding_ladder VARCHAR(50);
@@ -0,0 +1,3 @@
+-- MPEG-DASH manifest over the CMAF renditions; NULL for videos with MPEG-TS segments
+ALTER TABLE videos
+    ADD COLUMN IF NOT EXISTS dash_manifest_path VARCHAR(500);
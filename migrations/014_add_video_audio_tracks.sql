@@ -0,0 +1,5 @@
+-- Audio-only renditions, one per source audio stream, referenced by the master playlist's audio group
+ALTER TABLE videos
+    ADD COLUMN IF NOT EXISTS audio_tracks JSONB;
+
+COMMENT ON COLUMN videos.audio_tracks IS 'e.g. [{"index": 0, "language": "en", "name": "English", "channels": 2, "default": true, "playlist": "audio_0.m3u8"}]';
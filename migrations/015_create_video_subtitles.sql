@@ -0,0 +1,19 @@
+-- Subtitle tracks, uploaded per language or extracted from the source during processing
+CREATE TABLE IF NOT EXISTS video_subtitles (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    video_id UUID NOT NULL REFERENCES videos(id) ON DELETE CASCADE,
+    language VARCHAR(35),
+    name VARCHAR(100) NOT NULL,
+    source VARCHAR(20) NOT NULL CHECK (source IN ('upload', 'embedded')),
+    storage_path VARCHAR(500) NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_video_subtitles_video_id ON video_subtitles(video_id);
+
+-- Uploading a language again replaces the earlier upload
+CREATE UNIQUE INDEX IF NOT EXISTS idx_video_subtitles_uploaded_language
+    ON video_subtitles(video_id, language) WHERE source = 'upload';
+
+COMMENT ON COLUMN video_subtitles.language IS 'RFC 5646 tag such as en; NULL for untagged embedded streams';
+COMMENT ON COLUMN video_subtitles.storage_path IS 'Folder with subtitles.vtt, the WebVTT segments and playlist.m3u8';
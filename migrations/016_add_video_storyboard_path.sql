@@ -0,0 +1,3 @@
+-- WebVTT storyboard of trick-play sprite sheets for scrubbing previews; NULL when none was generated
+ALTER TABLE videos
+    ADD COLUMN IF NOT EXISTS storyboard_path VARCHAR(500);
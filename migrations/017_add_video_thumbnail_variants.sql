@@ -0,0 +1,9 @@
+-- Thumbnail in several sizes and formats, plus the candidate frames it can be switched to
+ALTER TABLE videos
+    ADD COLUMN IF NOT EXISTS thumbnail_variants JSONB,
+    ADD COLUMN IF NOT EXISTS thumbnail_candidates JSONB,
+    ADD COLUMN IF NOT EXISTS thumbnail_candidate INTEGER;
+
+COMMENT ON COLUMN videos.thumbnail_variants IS 'e.g. [{"size": "small", "format": "jpeg", "width": 320, "height": 180, "path": "<id>/thumbnails/small.jpg"}]';
+COMMENT ON COLUMN videos.thumbnail_candidates IS 'e.g. [{"index": 0, "time_secs": 6.0, "path": "<id>/thumbnails/candidates/candidate_0.jpg"}]';
+COMMENT ON COLUMN videos.thumbnail_candidate IS 'Index of the candidate the thumbnail was made from; NULL for a custom upload';
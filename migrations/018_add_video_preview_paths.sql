@@ -0,0 +1,4 @@
+-- Silent animated hover preview as MP4 and WebP; NULL when none was generated
+ALTER TABLE videos
+    ADD COLUMN IF NOT EXISTS preview_path VARCHAR(500),
+    ADD COLUMN IF NOT EXISTS preview_webp_path VARCHAR(500);
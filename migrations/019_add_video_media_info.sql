@@ -0,0 +1,6 @@
+-- Full FFprobe description of the source, plus the duration without truncation to whole seconds
+ALTER TABLE videos
+    ADD COLUMN IF NOT EXISTS duration_ms BIGINT,
+    ADD COLUMN IF NOT EXISTS media_info JSONB;
+
+COMMENT ON COLUMN videos.media_info IS 'e.g. {"container": "mov,mp4,m4a,3gp,3g2,mj2", "duration_ms": 10000, "video": [{"codec": "h264", "width": 1920, "height": 1080, "rotation": 0, "dynamic_range": "sdr", ...}], "audio": [...], "subtitles": [...]}';
@@ -0,0 +1,6 @@
+-- Short lease a PATCH takes on an upload while it writes, instead of a row lock held across the request
+ALTER TABLE upload_sessions
+    ADD COLUMN IF NOT EXISTS locked_by UUID,
+    ADD COLUMN IF NOT EXISTS locked_until TIMESTAMPTZ;
+
+COMMENT ON COLUMN upload_sessions.locked_by IS 'Random id of the request holding the lease, NULL when free';
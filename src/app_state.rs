@@ -4,17 +4,24 @@ use anyhow::Result;
 use sqlx::PgPool;
 
 use crate::services::{
-    AuthService, AuthServiceTrait, CloudStorageService, GcsService, MetricsService, MetricsServiceTrait, VideoProcessingService,
-    VideoProcessingServiceTrait, VideoService, VideoServiceTrait,
+    AuthService, AuthServiceTrait, CloudStorageService, EncodingLadders, JobQueueService,
+    JobQueueServiceTrait, MetricsService, MetricsServiceTrait, StorageBackend, SubtitleService,
+    SubtitleServiceTrait, UploadService, UploadServiceTrait, VideoEncoders, VideoEventBus,
+    VideoProcessingService, VideoProcessingServiceTrait, VideoService, VideoServiceTrait,
 };
 
 #[derive(Clone)]
 pub struct AppState {
     pub video_service: Arc<dyn VideoServiceTrait>,
     pub storage_service: Arc<dyn CloudStorageService>,
+    pub storage_backend: StorageBackend,
     pub video_processing_service: Arc<dyn VideoProcessingServiceTrait>,
     pub auth_service: Arc<dyn AuthServiceTrait>,
     pub metrics_service: Arc<dyn MetricsServiceTrait>,
+    pub upload_service: Arc<dyn UploadServiceTrait>,
+    pub encoding_ladders: Arc<EncodingLadders>,
+    pub subtitle_service: Arc<dyn SubtitleServiceTrait>,
+    pub video_event_bus: VideoEventBus,
 }
 
 impl AppState {
@@ -22,28 +29,55 @@ impl AppState {
         let jwt_secret =
             std::env::var("JWT_SECRET").map_err(|_| anyhow::anyhow!("JWT_SECRET must be set"))?;
 
-        let video_service: Arc<dyn VideoServiceTrait> = Arc::new(VideoService::new(pool.clone()));
-
         let metrics_service: Arc<dyn MetricsServiceTrait> = MetricsService::new(pool.clone());
 
-        let storage_service: Arc<dyn CloudStorageService> = Arc::new(GcsService::new().await?);
+        let storage_backend = StorageBackend::from_env()?;
+        let storage_service: Arc<dyn CloudStorageService> = storage_backend.build().await?;
+
+        let video_service: Arc<dyn VideoServiceTrait> = Arc::new(VideoService::new(
+            pool.clone(),
+            Arc::clone(&storage_service),
+        ));
 
         let auth_service: Arc<dyn AuthServiceTrait> =
             Arc::new(AuthService::new(pool.clone(), jwt_secret.clone()));
 
+        let encoding_ladders = Arc::new(EncodingLadders::from_env()?);
+
+        let job_queue_service: Arc<dyn JobQueueServiceTrait> =
+            Arc::new(JobQueueService::new(pool.clone()));
+
+        let subtitle_service: Arc<dyn SubtitleServiceTrait> = Arc::new(SubtitleService::new(
+            pool.clone(),
+            Arc::clone(&storage_service),
+        ));
+
         let video_processing_service: Arc<dyn VideoProcessingServiceTrait> =
             Arc::new(VideoProcessingService::new(
                 Arc::clone(&video_service),
                 Arc::clone(&storage_service),
                 Arc::clone(&metrics_service),
+                Arc::clone(&job_queue_service),
+                Arc::clone(&encoding_ladders),
+                // The API only queues jobs; the worker detects the encoders it actually has
+                Arc::new(VideoEncoders::default()),
+                Arc::clone(&subtitle_service),
             ));
 
+        let upload_service: Arc<dyn UploadServiceTrait> =
+            Arc::new(UploadService::new(pool.clone()));
+
         Ok(Self {
             video_service,
             storage_service,
+            storage_backend,
             video_processing_service,
             auth_service,
             metrics_service,
+            upload_service,
+            encoding_ladders,
+            subtitle_service,
+            video_event_bus: VideoEventBus::new(),
         })
     }
 }
@@ -0,0 +1,125 @@
+use dotenv::dotenv;
+use serde_json::json;
+use std::env;
+use std::sync::Arc;
+use std::time::Instant;
+
+use video_stream_be::services::{
+    database, CloudStorageService, EncodingLadders, JobQueueService, JobQueueServiceTrait,
+    MetricsService, MetricsServiceTrait, ProcessingWorker, ProcessingWorkerConfig, StorageBackend,
+    SubtitleService, SubtitleServiceTrait, VideoEncoders, VideoProcessingService,
+    VideoProcessingServiceTrait, VideoRecoveryService, VideoService, VideoServiceTrait,
+};
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    // Start measuring immediately at process start
+    let cold_start_timer = Instant::now();
+
+    dotenv().ok();
+    env_logger::init();
+
+    let database_url = env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+
+    let pool = database::create_pool(&database_url)
+        .await
+        .expect("Failed to create database pool");
+
+    VideoProcessingService::check_ffmpeg_availability()?;
+
+    let metrics_service: Arc<dyn MetricsServiceTrait> = MetricsService::new(pool.clone());
+
+    let storage_backend = StorageBackend::from_env()?;
+    let storage_service: Arc<dyn CloudStorageService> = storage_backend.build().await?;
+
+    let video_service: Arc<dyn VideoServiceTrait> = Arc::new(VideoService::new(
+        pool.clone(),
+        Arc::clone(&storage_service),
+    ));
+
+    let encoding_ladders = Arc::new(EncodingLadders::from_env()?);
+    log::info!(
+        "Encoding ladders: {} (default '{}')",
+        encoding_ladders.names().join(", "),
+        encoding_ladders.default_ladder
+    );
+
+    let video_encoders = Arc::new(VideoEncoders::detect()?);
+    log::info!("Video encoders: {}", video_encoders.describe().join(", "));
+    for codec in encoding_ladders.codecs() {
+        if video_encoders.get(codec).is_none() {
+            log::warn!(
+                "Encoding ladders use {} but FFmpeg has no encoder for it, those renditions will be skipped",
+                codec
+            );
+        }
+    }
+
+    let job_queue_service: Arc<dyn JobQueueServiceTrait> =
+        Arc::new(JobQueueService::new(pool.clone()));
+
+    let subtitle_service: Arc<dyn SubtitleServiceTrait> = Arc::new(SubtitleService::new(
+        pool.clone(),
+        Arc::clone(&storage_service),
+    ));
+
+    let video_processing_service: Arc<dyn VideoProcessingServiceTrait> =
+        Arc::new(VideoProcessingService::new(
+            Arc::clone(&video_service),
+            Arc::clone(&storage_service),
+            Arc::clone(&metrics_service),
+            Arc::clone(&job_queue_service),
+            encoding_ladders,
+            video_encoders,
+            subtitle_service,
+        ));
+
+    let config = ProcessingWorkerConfig::from_env();
+
+    let cold_start_duration_ms = cold_start_timer.elapsed().as_millis() as i64;
+
+    log::info!(
+        "Worker cold start completed in {}ms",
+        cold_start_duration_ms
+    );
+
+    let service_name = env::var("K_SERVICE").unwrap_or_else(|_| "video-stream-worker".to_string());
+    let revision = env::var("K_REVISION").ok();
+
+    if let Err(err) = metrics_service
+        .record_server_startup_metric(
+            None,
+            &service_name,
+            revision.as_deref(),
+            true,
+            cold_start_duration_ms,
+            Some(json!({
+                "concurrency": config.concurrency,
+            })),
+        )
+        .await
+    {
+        log::warn!("Failed to record worker startup metric: {}", err);
+    }
+
+    // Pick up videos a crashed instance left in uploading/processing
+    let recovery_service = VideoRecoveryService::new(
+        Arc::clone(&video_service),
+        Arc::clone(&storage_service),
+        Arc::clone(&video_processing_service),
+        Arc::clone(&metrics_service),
+    );
+    tokio::spawn(recovery_service.run());
+
+    ProcessingWorker::new(
+        config,
+        job_queue_service,
+        video_processing_service,
+        video_service,
+        metrics_service,
+    )
+    .run()
+    .await;
+
+    Ok(())
+}
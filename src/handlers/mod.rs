@@ -1,9 +1,13 @@
 pub mod auth;
 pub mod health;
 pub mod metrics;
+pub mod subtitles;
+pub mod uploads;
 pub mod videos;
 
 pub use auth::*;
 pub use health::*;
 pub use metrics::*;
+pub use subtitles::*;
+pub use uploads::*;
 pub use videos::*;
@@ -0,0 +1,276 @@
+use actix_multipart::Multipart;
+use actix_web::{web, HttpResponse, Result};
+use futures_util::TryStreamExt;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::app_state::AppState;
+use crate::models::{SubtitleSource, SubtitleTrackResponse, Video, VideoStatus};
+use crate::services::{language_tag, parse_subtitles, HlsSegmentFormat, NewSubtitleTrack};
+use crate::utils::response::ApiResponse;
+
+const MAX_SUBTITLE_FILE_SIZE: usize = 5 * 1024 * 1024;
+
+/// The video if it exists and belongs to the caller, otherwise the error response to send.
+async fn owned_video(
+    app_state: &AppState,
+    video_id: &Uuid,
+    user_id: &Uuid,
+) -> std::result::Result<Video, HttpResponse> {
+    match app_state.video_service.get_video_by_id(video_id).await {
+        Ok(Some(video)) if video.user_id == *user_id => Ok(video),
+        Ok(Some(_)) => {
+            Err(HttpResponse::Forbidden().json(ApiResponse::<String>::error("Access denied", None)))
+        }
+        Ok(None) => {
+            Err(HttpResponse::NotFound()
+                .json(ApiResponse::<String>::error("Video not found", None)))
+        }
+        Err(e) => {
+            log::error!("Failed to get video: {}", e);
+            Err(HttpResponse::InternalServerError()
+                .json(ApiResponse::<String>::error("Failed to fetch video", None)))
+        }
+    }
+}
+
+/// Point the master playlist of a processed video at its current subtitle tracks. The track
+/// change itself already succeeded, so a failure here is only logged.
+async fn update_master_playlist(app_state: &AppState, video: &Video) {
+    let Some(master_playlist_path) = &video.hls_playlist_path else {
+        return;
+    };
+    if let Err(e) = app_state
+        .subtitle_service
+        .update_master_playlist(&video.id, master_playlist_path)
+        .await
+    {
+        log::error!(
+            "Failed to update master playlist of video {} with subtitles: {:#}",
+            video.id,
+            e
+        );
+    }
+}
+
+/// List the subtitle tracks of a video
+pub async fn list_subtitles(
+    app_state: web::Data<AppState>,
+    user_id: web::ReqData<Uuid>,
+    path: web::Path<Uuid>,
+) -> Result<HttpResponse> {
+    let storage_service = Arc::clone(&app_state.storage_service);
+    let video_id = path.into_inner();
+
+    if let Err(response) = owned_video(&app_state, &video_id, &user_id.into_inner()).await {
+        return Ok(response);
+    }
+
+    match app_state
+        .subtitle_service
+        .list_subtitle_tracks(&video_id)
+        .await
+    {
+        Ok(tracks) => {
+            let tracks: Vec<SubtitleTrackResponse> = tracks
+                .into_iter()
+                .map(|track| {
+                    SubtitleTrackResponse::from_track_with_storage(track, storage_service.as_ref())
+                })
+                .collect();
+            Ok(HttpResponse::Ok().json(ApiResponse::success(tracks)))
+        }
+        Err(e) => {
+            log::error!("Failed to list subtitle tracks: {}", e);
+            Ok(
+                HttpResponse::InternalServerError().json(ApiResponse::<String>::error(
+                    "Failed to fetch subtitles",
+                    None,
+                )),
+            )
+        }
+    }
+}
+
+/// Upload an SRT or WebVTT subtitle file for one language of a video
+pub async fn upload_subtitle(
+    app_state: web::Data<AppState>,
+    user_id: web::ReqData<Uuid>,
+    path: web::Path<Uuid>,
+    mut payload: Multipart,
+) -> Result<HttpResponse> {
+    let storage_service = Arc::clone(&app_state.storage_service);
+    let video_id = path.into_inner();
+
+    let video = match owned_video(&app_state, &video_id, &user_id.into_inner()).await {
+        Ok(video) => video,
+        Err(response) => return Ok(response),
+    };
+
+    // Segments follow the processed timeline, and processing only rebuilds embedded tracks
+    if video.get_status() != VideoStatus::Ready {
+        return Ok(HttpResponse::Conflict().json(ApiResponse::<String>::error(
+            "Video is still being processed",
+            None,
+        )));
+    }
+
+    let mut language = String::new();
+    let mut name = None;
+    let mut file_data: Option<Vec<u8>> = None;
+
+    while let Some(mut field) = payload.try_next().await? {
+        let field_name = field.name().to_string();
+        let mut data = Vec::new();
+        while let Some(chunk) = field.try_next().await? {
+            data.extend_from_slice(&chunk);
+            if data.len() > MAX_SUBTITLE_FILE_SIZE {
+                return Ok(
+                    HttpResponse::PayloadTooLarge().json(ApiResponse::<String>::error(
+                        &format!(
+                            "Subtitle file too large. Maximum allowed: {} MB",
+                            MAX_SUBTITLE_FILE_SIZE / 1024 / 1024
+                        ),
+                        None,
+                    )),
+                );
+            }
+        }
+
+        match field_name.as_str() {
+            "language" => language = String::from_utf8_lossy(&data).to_string(),
+            "name" => {
+                let value = String::from_utf8_lossy(&data).trim().to_string();
+                if !value.is_empty() {
+                    name = Some(value);
+                }
+            }
+            "file" => file_data = Some(data),
+            _ => {}
+        }
+    }
+
+    let Some(language) = language_tag(&language) else {
+        return Ok(
+            HttpResponse::BadRequest().json(ApiResponse::<String>::error(
+                "A language tag such as 'en' is required",
+                None,
+            )),
+        );
+    };
+    if name.as_ref().is_some_and(|name| name.chars().count() > 100) {
+        return Ok(
+            HttpResponse::BadRequest().json(ApiResponse::<String>::error(
+                "Name must be at most 100 characters",
+                None,
+            )),
+        );
+    }
+    let Some(file_data) = file_data else {
+        return Ok(
+            HttpResponse::BadRequest().json(ApiResponse::<String>::error(
+                "No subtitle file provided",
+                None,
+            )),
+        );
+    };
+    let Ok(content) = String::from_utf8(file_data) else {
+        return Ok(
+            HttpResponse::BadRequest().json(ApiResponse::<String>::error(
+                "Subtitle file must be UTF-8 encoded",
+                None,
+            )),
+        );
+    };
+    let cues = match parse_subtitles(&content) {
+        Ok(cues) => cues,
+        Err(e) => {
+            return Ok(
+                HttpResponse::BadRequest().json(ApiResponse::<String>::error(
+                    &format!("Invalid subtitle file: {:#}", e),
+                    None,
+                )),
+            )
+        }
+    };
+
+    let track = NewSubtitleTrack {
+        name: name.unwrap_or_else(|| language.clone()),
+        language: Some(language),
+        source: SubtitleSource::Upload,
+        cues,
+    };
+    // Videos processed before `duration_ms` was recorded only have whole seconds
+    let duration_secs = match video.duration_ms {
+        Some(duration_ms) => duration_ms as f64 / 1000.0,
+        None => video.duration.unwrap_or_default() as f64,
+    };
+    let stored = match app_state
+        .subtitle_service
+        .add_subtitle_track(
+            &video_id,
+            track,
+            HlsSegmentFormat::of_video(&video),
+            duration_secs,
+        )
+        .await
+    {
+        Ok(stored) => stored,
+        Err(e) => {
+            log::error!("Failed to store subtitle track: {:#}", e);
+            return Ok(
+                HttpResponse::InternalServerError().json(ApiResponse::<String>::error(
+                    "Failed to store subtitles",
+                    None,
+                )),
+            );
+        }
+    };
+
+    update_master_playlist(&app_state, &video).await;
+
+    Ok(HttpResponse::Created().json(ApiResponse::success(
+        SubtitleTrackResponse::from_track_with_storage(stored, storage_service.as_ref()),
+    )))
+}
+
+/// Delete a subtitle track
+pub async fn delete_subtitle(
+    app_state: web::Data<AppState>,
+    user_id: web::ReqData<Uuid>,
+    path: web::Path<(Uuid, Uuid)>,
+) -> Result<HttpResponse> {
+    let (video_id, track_id) = path.into_inner();
+
+    let video = match owned_video(&app_state, &video_id, &user_id.into_inner()).await {
+        Ok(video) => video,
+        Err(response) => return Ok(response),
+    };
+
+    match app_state
+        .subtitle_service
+        .delete_subtitle_track(&video_id, &track_id)
+        .await
+    {
+        Ok(true) => {
+            update_master_playlist(&app_state, &video).await;
+            Ok(
+                HttpResponse::Ok()
+                    .json(ApiResponse::success("Subtitle track deleted successfully")),
+            )
+        }
+        Ok(false) => Ok(HttpResponse::NotFound().json(ApiResponse::<String>::error(
+            "Subtitle track not found",
+            None,
+        ))),
+        Err(e) => {
+            log::error!("Failed to delete subtitle track: {}", e);
+            Ok(
+                HttpResponse::InternalServerError().json(ApiResponse::<String>::error(
+                    "Failed to delete subtitles",
+                    None,
+                )),
+            )
+        }
+    }
+}
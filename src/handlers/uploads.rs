@@ -0,0 +1,761 @@
+use actix_web::http::StatusCode;
+use actix_web::{web, HttpRequest, HttpResponse, HttpResponseBuilder, Result};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use futures_util::StreamExt;
+use serde_json::json;
+use std::collections::HashMap;
+use std::io::SeekFrom;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tempfile::TempPath;
+use tokio::fs::OpenOptions;
+use tokio::io::{AsyncSeekExt, AsyncWriteExt};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::app_state::AppState;
+use crate::handlers::videos::{rejection_status, spawn_upload_pipeline, storage_filename};
+use crate::models::{CreateUploadSessionRequest, CreateVideoRequest, UploadSession, Video};
+use crate::services::{
+    detect_container, read_container_header, validate_upload, UploadLimits, UploadRejection,
+    VideoProcessingService, CONTAINER_HEADER_LEN, UPLOAD_LOCK_SECONDS,
+};
+use crate::utils::response::ApiResponse;
+
+const TUS_VERSION: &str = "1.0.0";
+const TUS_EXTENSIONS: &str = "creation,termination,expiration";
+const TUS_CONTENT_TYPE: &str = "application/offset+octet-stream";
+
+/// Headers browser tus clients need to read from cross-origin responses.
+pub const TUS_EXPOSED_HEADERS: [&str; 7] = [
+    "Location",
+    "Tus-Resumable",
+    "Tus-Version",
+    "Tus-Extension",
+    "Tus-Max-Size",
+    "Upload-Offset",
+    "Upload-Length",
+];
+
+/// tus discovery: advertise the protocol version, extensions and size limit
+pub async fn tus_options() -> Result<HttpResponse> {
+    Ok(tus_response(StatusCode::NO_CONTENT)
+        .insert_header(("Tus-Version", TUS_VERSION))
+        .insert_header(("Tus-Extension", TUS_EXTENSIONS))
+        .insert_header((
+            "Tus-Max-Size",
+            VideoProcessingService::max_file_size().to_string(),
+        ))
+        .finish())
+}
+
+/// tus creation: register a new upload and return its location
+pub async fn create_upload(
+    req: HttpRequest,
+    app_state: web::Data<AppState>,
+    user_id: web::ReqData<Uuid>,
+) -> Result<HttpResponse> {
+    if let Some(response) = check_tus_resumable(&req) {
+        return Ok(response);
+    }
+
+    let upload_service = Arc::clone(&app_state.upload_service);
+    let user_id_value = user_id.into_inner();
+
+    let upload_length = match header_i64(&req, "Upload-Length") {
+        Some(length) if length > 0 => length,
+        Some(_) => {
+            return Ok(tus_error(
+                StatusCode::BAD_REQUEST,
+                "Upload-Length must be greater than zero",
+            ))
+        }
+        None => {
+            return Ok(tus_error(
+                StatusCode::BAD_REQUEST,
+                "Upload-Length header is required",
+            ))
+        }
+    };
+
+    let max_file_size = VideoProcessingService::max_file_size();
+    if upload_length as u64 > max_file_size {
+        return Ok(tus_error(
+            StatusCode::PAYLOAD_TOO_LARGE,
+            &format!(
+                "File size too large. Maximum allowed: {} MB",
+                max_file_size / 1024 / 1024
+            ),
+        ));
+    }
+
+    let metadata = match req
+        .headers()
+        .get("Upload-Metadata")
+        .and_then(|value| value.to_str().ok())
+        .map(parse_upload_metadata)
+        .transpose()
+    {
+        Ok(metadata) => metadata.unwrap_or_default(),
+        Err(e) => return Ok(tus_error(StatusCode::BAD_REQUEST, &e)),
+    };
+
+    let original_filename = match metadata.get("filename").or_else(|| metadata.get("name")) {
+        Some(filename) if !filename.trim().is_empty() => filename.trim().to_string(),
+        _ => {
+            return Ok(tus_error(
+                StatusCode::BAD_REQUEST,
+                "Upload-Metadata must include a filename",
+            ))
+        }
+    };
+
+    if let Err(e) = VideoProcessingService::validate_video_file_size(upload_length as u64) {
+        return Ok(tus_error(StatusCode::BAD_REQUEST, &e.to_string()));
+    }
+
+    // Fall back to the file name without its extension when no title is given
+    let title = metadata
+        .get("title")
+        .map(|title| title.trim().to_string())
+        .filter(|title| !title.is_empty())
+        .unwrap_or_else(|| {
+            Path::new(&original_filename)
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().to_string())
+                .unwrap_or_else(|| original_filename.clone())
+        });
+    let description = metadata
+        .get("description")
+        .map(|description| description.trim().to_string())
+        .filter(|description| !description.is_empty());
+
+    let encoding_ladder = match metadata
+        .get("ladder")
+        .filter(|ladder| !ladder.trim().is_empty())
+        .map(|ladder| app_state.encoding_ladders.validate_name(ladder))
+        .transpose()
+    {
+        Ok(ladder) => ladder,
+        Err(e) => return Ok(tus_error(StatusCode::BAD_REQUEST, &e.to_string())),
+    };
+
+    let video_request = CreateVideoRequest {
+        title: title.clone(),
+        description: description.clone(),
+        encoding_ladder,
+    };
+    if video_request.validate().is_err() {
+        return Ok(tus_error(
+            StatusCode::BAD_REQUEST,
+            "Invalid title or description in Upload-Metadata",
+        ));
+    }
+
+    let create_request = CreateUploadSessionRequest {
+        title,
+        description,
+        original_filename,
+        upload_length,
+        metadata: json!(metadata),
+    };
+
+    match upload_service
+        .create_upload(user_id_value, create_request)
+        .await
+    {
+        Ok(session) => {
+            log::info!(
+                "Created upload {} ({} bytes) for user {}",
+                session.id,
+                session.upload_length,
+                user_id_value
+            );
+            let location = format!("{}/{}", req.path().trim_end_matches('/'), session.id);
+            Ok(tus_response(StatusCode::CREATED)
+                .insert_header(("Location", location))
+                .insert_header(("Upload-Expires", http_date(&session.expires_at)))
+                .finish())
+        }
+        Err(e) => {
+            log::error!("Failed to create upload session: {}", e);
+            Ok(tus_error(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Failed to create upload",
+            ))
+        }
+    }
+}
+
+/// tus core: report how many bytes of an upload the server has received
+pub async fn get_upload_offset(
+    req: HttpRequest,
+    app_state: web::Data<AppState>,
+    user_id: web::ReqData<Uuid>,
+    path: web::Path<Uuid>,
+) -> Result<HttpResponse> {
+    if let Some(response) = check_tus_resumable(&req) {
+        return Ok(response);
+    }
+
+    let session = match load_upload(&app_state, user_id.into_inner(), path.into_inner()).await {
+        Ok(session) => session,
+        Err(response) => return Ok(response),
+    };
+
+    Ok(tus_response(StatusCode::OK)
+        .insert_header(("Upload-Offset", session.upload_offset.to_string()))
+        .insert_header(("Upload-Length", session.upload_length.to_string()))
+        .insert_header(("Upload-Expires", http_date(&session.expires_at)))
+        .insert_header(("Cache-Control", "no-store"))
+        .finish())
+}
+
+/// tus core: append the request body at `Upload-Offset`
+pub async fn append_upload(
+    req: HttpRequest,
+    app_state: web::Data<AppState>,
+    user_id: web::ReqData<Uuid>,
+    path: web::Path<Uuid>,
+    payload: web::Payload,
+) -> Result<HttpResponse> {
+    if let Some(response) = check_tus_resumable(&req) {
+        return Ok(response);
+    }
+
+    let upload_service = Arc::clone(&app_state.upload_service);
+    let user_id_value = user_id.into_inner();
+
+    let session = match load_upload(&app_state, user_id_value, path.into_inner()).await {
+        Ok(session) => session,
+        Err(response) => return Ok(response),
+    };
+
+    let content_type = req
+        .headers()
+        .get("Content-Type")
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or("");
+    if content_type != TUS_CONTENT_TYPE {
+        return Ok(tus_error(
+            StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            "Content-Type must be application/offset+octet-stream",
+        ));
+    }
+
+    let offset = match header_i64(&req, "Upload-Offset") {
+        Some(offset) => offset,
+        None => {
+            return Ok(tus_error(
+                StatusCode::BAD_REQUEST,
+                "Upload-Offset header is required",
+            ))
+        }
+    };
+    // A short lease rather than a row lock, so a slow client does not hold a database connection
+    let lock_id = Uuid::new_v4();
+    let session = match upload_service.lock_upload(&session.id, &lock_id).await {
+        Ok(Some(session)) => session,
+        Ok(None) => {
+            return Ok(tus_error(
+                StatusCode::CONFLICT,
+                "Upload is in use by another request",
+            ))
+        }
+        Err(e) => {
+            log::error!("Failed to lock upload {}: {}", session.id, e);
+            return Ok(tus_error(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Failed to store upload data",
+            ));
+        }
+    };
+
+    let response = write_upload(&req, &app_state, &session, &lock_id, offset, payload).await;
+    if let Err(e) = upload_service.unlock_upload(&session.id, &lock_id).await {
+        log::warn!("Failed to unlock upload {}: {}", session.id, e);
+    }
+
+    Ok(response)
+}
+
+/// Append the request body to an upload leased to `lock_id`.
+async fn write_upload(
+    req: &HttpRequest,
+    app_state: &AppState,
+    session: &UploadSession,
+    lock_id: &Uuid,
+    offset: i64,
+    mut payload: web::Payload,
+) -> HttpResponse {
+    let upload_service = Arc::clone(&app_state.upload_service);
+
+    if offset != session.upload_offset {
+        return tus_error(
+            StatusCode::CONFLICT,
+            "Upload-Offset does not match the current offset",
+        );
+    }
+
+    if session.is_complete() {
+        return offset_response(session, session.upload_offset);
+    }
+
+    let part_path = upload_service.part_path(&session.id);
+    let mut part_file = match OpenOptions::new().write(true).open(&part_path).await {
+        Ok(file) => file,
+        Err(e) => {
+            log::error!("Failed to open {}: {}", part_path.display(), e);
+            return tus_error(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Failed to store upload data",
+            );
+        }
+    };
+    // The lease keeps other requests out, so the file only grows from the stored offset
+    if let Err(e) = part_file.seek(SeekFrom::Start(offset as u64)).await {
+        log::error!("Failed to seek {}: {}", part_path.display(), e);
+        return tus_error(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Failed to store upload data",
+        );
+    }
+
+    // Keep whatever arrived before an error so the client can resume from there
+    let remaining = session.upload_length - offset;
+    let renew_interval = Duration::from_secs(UPLOAD_LOCK_SECONDS as u64 / 3);
+    let mut renewed_at = Instant::now();
+    let mut written: i64 = 0;
+    let mut failure: Option<HttpResponse> = None;
+    while let Some(chunk) = payload.next().await {
+        let chunk = match chunk {
+            Ok(chunk) => chunk,
+            Err(e) => {
+                log::warn!(
+                    "Upload {} interrupted after {} bytes: {}",
+                    session.id,
+                    written,
+                    e
+                );
+                failure = Some(tus_error(StatusCode::BAD_REQUEST, "Upload interrupted"));
+                break;
+            }
+        };
+
+        if written + chunk.len() as i64 > remaining {
+            failure = Some(tus_error(
+                StatusCode::PAYLOAD_TOO_LARGE,
+                "Request body exceeds Upload-Length",
+            ));
+            break;
+        }
+
+        // Never write once the lease has passed to another request
+        if renewed_at.elapsed() >= renew_interval {
+            match upload_service.renew_lock(&session.id, lock_id).await {
+                Ok(true) => renewed_at = Instant::now(),
+                Ok(false) => {
+                    log::warn!("Upload {} lease lost after {} bytes", session.id, written);
+                    return tus_error(
+                        StatusCode::CONFLICT,
+                        "Upload was taken over by another request",
+                    );
+                }
+                Err(e) => {
+                    log::error!("Failed to renew lease on upload {}: {}", session.id, e);
+                    return tus_error(
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        "Failed to store upload data",
+                    );
+                }
+            }
+        }
+
+        if let Err(e) = part_file.write_all(&chunk).await {
+            log::error!("Failed to write to {}: {}", part_path.display(), e);
+            failure = Some(tus_error(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Failed to store upload data",
+            ));
+            break;
+        }
+        written += chunk.len() as i64;
+    }
+
+    if let Err(e) = part_file.flush().await {
+        log::error!("Failed to flush {}: {}", part_path.display(), e);
+        return tus_error(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Failed to store upload data",
+        );
+    }
+
+    let new_offset = offset + written;
+    match upload_service
+        .advance_offset(&session.id, lock_id, offset, new_offset)
+        .await
+    {
+        Ok(true) => {}
+        Ok(false) => {
+            return tus_error(
+                StatusCode::CONFLICT,
+                "Upload was modified by another request",
+            )
+        }
+        Err(e) => {
+            log::error!("Failed to update offset for upload {}: {}", session.id, e);
+            return tus_error(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Failed to store upload data",
+            );
+        }
+    }
+
+    if let Some(response) = failure {
+        return response;
+    }
+
+    // Reject files that are not video as soon as their first bytes are in
+    let header_len = CONTAINER_HEADER_LEN as i64;
+    if offset < header_len && (new_offset >= header_len || new_offset == session.upload_length) {
+        match read_container_header(&part_path.to_string_lossy()).await {
+            Ok(header) if detect_container(&header).is_none() => {
+                let rejection = UploadRejection::UnsupportedFormat;
+                return discard_upload(
+                    app_state,
+                    session,
+                    lock_id,
+                    rejection_status(&rejection),
+                    &rejection.to_string(),
+                )
+                .await;
+            }
+            Ok(_) => {}
+            Err(e) => log::warn!("Failed to read header of upload {}: {:#}", session.id, e),
+        }
+    }
+
+    if new_offset == session.upload_length {
+        // The ladder was checked at creation, but the configuration may have changed since
+        let encoding_ladder = match session
+            .metadata
+            .get("ladder")
+            .and_then(|ladder| ladder.as_str())
+            .filter(|ladder| !ladder.trim().is_empty())
+            .map(|ladder| app_state.encoding_ladders.validate_name(ladder))
+            .transpose()
+        {
+            Ok(ladder) => ladder,
+            Err(e) => {
+                return discard_upload(
+                    app_state,
+                    session,
+                    lock_id,
+                    StatusCode::BAD_REQUEST,
+                    &e.to_string(),
+                )
+                .await
+            }
+        };
+
+        match finish_upload(app_state, session, lock_id, encoding_ladder, &part_path).await {
+            Ok(Some((video, filename))) => {
+                start_processing(req, app_state, session, video, filename, part_path).await;
+            }
+            Ok(None) => {
+                return tus_error(
+                    StatusCode::CONFLICT,
+                    "Upload was modified by another request",
+                )
+            }
+            Err(e) => match e.downcast::<UploadRejection>() {
+                Ok(rejection) => {
+                    return discard_upload(
+                        app_state,
+                        session,
+                        lock_id,
+                        rejection_status(&rejection),
+                        &rejection.to_string(),
+                    )
+                    .await
+                }
+                Err(e) => {
+                    log::error!("Failed to finish upload {}: {}", session.id, e);
+                    return tus_error(
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        "Failed to create video record",
+                    );
+                }
+            },
+        }
+    }
+
+    offset_response(session, new_offset)
+}
+
+/// tus termination: discard an upload and its partial data
+pub async fn terminate_upload(
+    req: HttpRequest,
+    app_state: web::Data<AppState>,
+    user_id: web::ReqData<Uuid>,
+    path: web::Path<Uuid>,
+) -> Result<HttpResponse> {
+    if let Some(response) = check_tus_resumable(&req) {
+        return Ok(response);
+    }
+
+    let upload_service = Arc::clone(&app_state.upload_service);
+    let session = match load_upload(&app_state, user_id.into_inner(), path.into_inner()).await {
+        Ok(session) => session,
+        Err(response) => return Ok(response),
+    };
+
+    // The data of a completed upload is owned by the video pipeline; delete the video instead
+    if session.is_complete() {
+        return Ok(tus_error(
+            StatusCode::CONFLICT,
+            "Upload has already completed",
+        ));
+    }
+
+    match upload_service.delete_upload(&session.id, None).await {
+        Ok(true) => Ok(tus_response(StatusCode::NO_CONTENT).finish()),
+        Ok(false) => Ok(tus_error(
+            StatusCode::CONFLICT,
+            "Upload has already completed or is in use by another request",
+        )),
+        Err(e) => {
+            log::error!("Failed to delete upload {}: {}", session.id, e);
+            Ok(tus_error(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Failed to delete upload",
+            ))
+        }
+    }
+}
+
+/// Validate a fully received upload and create its `videos` row, completing the upload in the
+/// same transaction so a retried final PATCH cannot create a second video. Returns `None` when
+/// the upload completed or changed hands in the meantime.
+async fn finish_upload(
+    app_state: &AppState,
+    session: &UploadSession,
+    lock_id: &Uuid,
+    encoding_ladder: Option<String>,
+    part_path: &Path,
+) -> anyhow::Result<Option<(Video, String)>> {
+    let validated =
+        validate_upload(&part_path.to_string_lossy(), &UploadLimits::from_env()).await?;
+
+    let filename = storage_filename(&session.title, validated.extension);
+    let create_request = CreateVideoRequest {
+        title: session.title.clone(),
+        description: session.description.clone(),
+        encoding_ladder,
+    };
+
+    let video = app_state
+        .upload_service
+        .complete_upload(&session.id, lock_id, create_request, filename.clone())
+        .await?;
+    Ok(video.map(|video| (video, filename)))
+}
+
+/// Hand the part file of a completed upload to the regular upload pipeline.
+async fn start_processing(
+    req: &HttpRequest,
+    app_state: &AppState,
+    session: &UploadSession,
+    video: Video,
+    filename: String,
+    part_path: PathBuf,
+) {
+    let metrics_service = Arc::clone(&app_state.metrics_service);
+    log::info!("Upload {} completed as video {}", session.id, video.id);
+
+    let upload_metadata = json!({
+        "video_id": video.id,
+        "user_id": session.user_id,
+        "upload_id": session.id,
+        "upload_protocol": "tus",
+        "filename": filename,
+        "original_filename": session.original_filename,
+        "file_size": session.upload_length,
+        "route": req.path(),
+        "method": req.method().as_str(),
+    });
+
+    let upload_benchmark_run_id = match metrics_service
+        .create_benchmark_run("video_upload", Some(upload_metadata))
+        .await
+    {
+        Ok(id) => Some(id),
+        Err(err) => {
+            log::warn!(
+                "Failed to create upload benchmark run for {}: {}",
+                video.id,
+                err
+            );
+            None
+        }
+    };
+
+    if let Err(err) = metrics_service
+        .record_video_processing_step(
+            upload_benchmark_run_id,
+            Some(video.id),
+            "create_video_record",
+            None,
+            None,
+            None,
+        )
+        .await
+    {
+        log::warn!(
+            "Failed to record create_video_record metric for {}: {}",
+            video.id,
+            err
+        );
+    }
+
+    // Left in `uploading`, the video is later marked failed by the worker's stale video recovery
+    let source_path = match TempPath::try_from_path(&part_path) {
+        Ok(source_path) => source_path,
+        Err(e) => {
+            log::error!("Failed to take over {}: {}", part_path.display(), e);
+            return;
+        }
+    };
+    spawn_upload_pipeline(
+        app_state,
+        video.id,
+        session.user_id,
+        filename,
+        source_path,
+        upload_benchmark_run_id,
+    );
+}
+
+/// Discard an upload that cannot become a video, so the client cannot resume it.
+async fn discard_upload(
+    app_state: &AppState,
+    session: &UploadSession,
+    lock_id: &Uuid,
+    status: StatusCode,
+    message: &str,
+) -> HttpResponse {
+    log::info!("Rejected upload {}: {}", session.id, message);
+    if let Err(e) = app_state
+        .upload_service
+        .delete_upload(&session.id, Some(lock_id))
+        .await
+    {
+        log::error!("Failed to delete rejected upload {}: {}", session.id, e);
+    }
+    tus_error(status, message)
+}
+
+/// Fetch an upload owned by `user_id`, mapping missing, foreign and expired uploads to responses.
+async fn load_upload(
+    app_state: &AppState,
+    user_id: Uuid,
+    upload_id: Uuid,
+) -> std::result::Result<UploadSession, HttpResponse> {
+    match app_state.upload_service.get_upload(&upload_id).await {
+        Ok(Some(session)) => {
+            if session.user_id != user_id {
+                return Err(tus_error(StatusCode::FORBIDDEN, "Access denied"));
+            }
+            if session.is_expired() && !session.is_complete() {
+                return Err(tus_error(StatusCode::GONE, "Upload has expired"));
+            }
+            Ok(session)
+        }
+        Ok(None) => Err(tus_error(StatusCode::NOT_FOUND, "Upload not found")),
+        Err(e) => {
+            log::error!("Failed to fetch upload {}: {}", upload_id, e);
+            Err(tus_error(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Failed to fetch upload",
+            ))
+        }
+    }
+}
+
+fn check_tus_resumable(req: &HttpRequest) -> Option<HttpResponse> {
+    let version = req
+        .headers()
+        .get("Tus-Resumable")
+        .and_then(|value| value.to_str().ok());
+
+    if version == Some(TUS_VERSION) {
+        return None;
+    }
+
+    Some(
+        tus_response(StatusCode::PRECONDITION_FAILED)
+            .insert_header(("Tus-Version", TUS_VERSION))
+            .json(ApiResponse::<String>::error(
+                "Unsupported or missing Tus-Resumable header",
+                None,
+            )),
+    )
+}
+
+/// Decode `Upload-Metadata`: comma separated `key base64(value)` pairs, the value being optional.
+fn parse_upload_metadata(header: &str) -> std::result::Result<HashMap<String, String>, String> {
+    let mut metadata = HashMap::new();
+
+    for pair in header
+        .split(',')
+        .map(str::trim)
+        .filter(|pair| !pair.is_empty())
+    {
+        let mut parts = pair.splitn(2, ' ');
+        let key = parts.next().unwrap_or_default().to_string();
+        let value = match parts.next().map(str::trim) {
+            Some(encoded) if !encoded.is_empty() => {
+                let decoded = BASE64
+                    .decode(encoded)
+                    .map_err(|_| format!("Invalid base64 value for metadata key {}", key))?;
+                String::from_utf8(decoded)
+                    .map_err(|_| format!("Metadata value for {} is not valid UTF-8", key))?
+            }
+            _ => String::new(),
+        };
+        metadata.insert(key, value);
+    }
+
+    Ok(metadata)
+}
+
+fn header_i64(req: &HttpRequest, name: &str) -> Option<i64> {
+    req.headers()
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().parse().ok())
+}
+
+fn offset_response(session: &UploadSession, offset: i64) -> HttpResponse {
+    tus_response(StatusCode::NO_CONTENT)
+        .insert_header(("Upload-Offset", offset.to_string()))
+        .insert_header(("Upload-Expires", http_date(&session.expires_at)))
+        .finish()
+}
+
+fn tus_response(status: StatusCode) -> HttpResponseBuilder {
+    let mut builder = HttpResponse::build(status);
+    builder.insert_header(("Tus-Resumable", TUS_VERSION));
+    builder
+}
+
+fn tus_error(status: StatusCode, message: &str) -> HttpResponse {
+    tus_response(status).json(ApiResponse::<String>::error(message, None))
+}
+
+fn http_date(date: &DateTime<Utc>) -> String {
+    date.format("%a, %d %b %Y %H:%M:%S GMT").to_string()
+}
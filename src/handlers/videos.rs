@@ -1,22 +1,36 @@
 use actix_multipart::Multipart;
+use actix_web::http::StatusCode;
 use actix_web::{web, HttpRequest, HttpResponse, Result};
-use futures_util::TryStreamExt;
+use bytes::Bytes;
+use futures_util::{stream, TryStreamExt};
 use serde::Deserialize;
 use serde_json::json;
 use std::collections::HashMap;
 use std::sync::Arc;
-use std::time::Instant;
+use std::time::{Duration, Instant};
+use tempfile::TempPath;
+use tokio::fs;
+use tokio::io::AsyncWriteExt;
+use tokio::sync::broadcast;
 use uuid::Uuid;
 use validator::Validate;
 
 use crate::app_state::AppState;
 use crate::models::{
-    CreateVideoRequest, HlsStreamingResponse, UpdateVideoRequest, VideoResponse,
+    CreateVideoRequest, FailureCategory, HlsStreamingResponse, ProcessingFailure, ThumbnailFormat,
+    ThumbnailSize, UpdateVideoRequest, VideoProgressResponse, VideoResponse, VideoStatus,
     VideoUploadResponse,
 };
-use crate::services::VideoProcessingService;
+use crate::services::{
+    default_thumbnail_path, store_thumbnail_variants, validate_upload, FfmpegError, UploadLimits,
+    UploadRejection, VideoProcessingService,
+};
 use crate::utils::response::ApiResponse;
 
+const SSE_KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(15);
+
+const MAX_THUMBNAIL_FILE_SIZE: usize = 10 * 1024 * 1024;
+
 pub async fn upload_video(
     req: HttpRequest,
     app_state: web::Data<AppState>,
@@ -24,15 +38,15 @@ pub async fn upload_video(
     mut payload: Multipart,
 ) -> Result<HttpResponse> {
     let video_service = Arc::clone(&app_state.video_service);
-    let storage_service = Arc::clone(&app_state.storage_service);
-    let processing_service = Arc::clone(&app_state.video_processing_service);
     let metrics_service = Arc::clone(&app_state.metrics_service);
     let handler_timer = Instant::now();
     let user_id_value = user_id.into_inner();
 
     let mut title = String::new();
     let mut description = None;
-    let mut video_file: Option<(String, Vec<u8>)> = None;
+    let mut encoding_ladder = None;
+    let mut video_file: Option<(String, TempPath, u64)> = None;
+    let max_file_size = VideoProcessingService::max_file_size();
 
     // Parse multipart form data
     while let Some(mut field) = payload.try_next().await? {
@@ -54,6 +68,22 @@ pub async fn upload_video(
                     description = Some(desc);
                 }
             }
+            "ladder" => {
+                let mut data = Vec::new();
+                while let Some(chunk) = field.try_next().await? {
+                    data.extend_from_slice(&chunk);
+                }
+                let ladder = String::from_utf8_lossy(&data).to_string();
+                if !ladder.trim().is_empty() {
+                    match app_state.encoding_ladders.validate_name(&ladder) {
+                        Ok(ladder) => encoding_ladder = Some(ladder),
+                        Err(e) => {
+                            return Ok(HttpResponse::BadRequest()
+                                .json(ApiResponse::<String>::error(&e.to_string(), None)))
+                        }
+                    }
+                }
+            }
             "files" => {
                 let filename = field
                     .content_disposition()
@@ -61,15 +91,47 @@ pub async fn upload_video(
                     .unwrap_or("unknown")
                     .to_string();
 
-                let mut file_data = Vec::new();
+                // Spool the upload to disk, enforcing the size limit as the bytes arrive
+                let (spool_file, spool_path) = match create_spool_file().await {
+                    Ok(spool) => spool,
+                    Err(e) => {
+                        log::error!("Failed to create upload spool file: {}", e);
+                        return Ok(HttpResponse::InternalServerError().json(
+                            ApiResponse::<String>::error("Failed to store uploaded file", None),
+                        ));
+                    }
+                };
+                let mut spool_file = fs::File::from_std(spool_file);
+
+                let mut written: u64 = 0;
                 while let Some(chunk) = field.try_next().await? {
-                    file_data.extend_from_slice(&chunk);
+                    written += chunk.len() as u64;
+                    if written > max_file_size {
+                        return Ok(HttpResponse::PayloadTooLarge().json(
+                            ApiResponse::<String>::error(
+                                &format!(
+                                    "File size too large. Maximum allowed: {} MB",
+                                    max_file_size / 1024 / 1024
+                                ),
+                                None,
+                            ),
+                        ));
+                    }
+                    if let Err(e) = spool_file.write_all(&chunk).await {
+                        log::error!("Failed to write upload to {}: {}", spool_path.display(), e);
+                        return Ok(HttpResponse::InternalServerError().json(
+                            ApiResponse::<String>::error("Failed to store uploaded file", None),
+                        ));
+                    }
                 }
-
-                // Validate video file types
-                if is_video_file(&filename) {
-                    video_file = Some((filename, file_data));
+                if let Err(e) = spool_file.flush().await {
+                    log::error!("Failed to flush upload to {}: {}", spool_path.display(), e);
+                    return Ok(HttpResponse::InternalServerError().json(
+                        ApiResponse::<String>::error("Failed to store uploaded file", None),
+                    ));
                 }
+
+                video_file = Some((filename, spool_path, written));
             }
             _ => {}
         }
@@ -86,43 +148,43 @@ pub async fn upload_video(
             .json(ApiResponse::<String>::error("Video file is required", None)));
     }
 
-    let (original_filename, file_data) = video_file.unwrap();
-    let file_size = file_data.len() as u64;
+    let (original_filename, spool_path, file_size) = video_file.unwrap();
 
     // Validate video file
     log::info!("Validating video file: {}", original_filename);
-    if let Err(e) = VideoProcessingService::validate_video_file(&original_filename, file_size) {
+    if let Err(e) = VideoProcessingService::validate_video_file_size(file_size) {
         return Ok(
             HttpResponse::BadRequest().json(ApiResponse::<String>::error(&e.to_string(), None))
         );
     }
-
-    // Check FFmpeg availability
-    log::info!("Checking FFmpeg availability");
-    if let Err(e) = VideoProcessingService::check_ffmpeg_availability() {
-        log::error!("FFmpeg not available: {}", e);
-        return Ok(
-            HttpResponse::InternalServerError().json(ApiResponse::<String>::error(
-                "Video processing service is not available",
-                None,
-            )),
-        );
-    }
+    let validated =
+        match validate_upload(&spool_path.to_string_lossy(), &UploadLimits::from_env()).await {
+            Ok(validated) => validated,
+            Err(e) => {
+                if let Some(rejection) = e.downcast_ref::<UploadRejection>() {
+                    return Ok(HttpResponse::build(rejection_status(rejection))
+                        .json(ApiResponse::<String>::error(&rejection.to_string(), None)));
+                }
+                log::error!("Failed to validate upload {}: {:#}", original_filename, e);
+                return Ok(
+                    HttpResponse::InternalServerError().json(ApiResponse::<String>::error(
+                        "Failed to validate uploaded file",
+                        None,
+                    )),
+                );
+            }
+        };
 
     // Generate unique filename
     log::info!("Generating unique filename");
     // let video_id = Uuid::new_v4();
-    let file_extension = original_filename.split('.').last().unwrap_or("mp4");
-    let filename = format!(
-        "{}.{}",
-        title.to_lowercase().replace(" ", "_").replace(".", ""),
-        file_extension
-    );
+    let filename = storage_filename(&title, validated.extension);
 
     // Create video record in database
     let create_request = CreateVideoRequest {
         title: title.clone(),
         description: description.clone(),
+        encoding_ladder,
     };
 
     log::info!("Creating video record in database");
@@ -195,23 +257,82 @@ pub async fn upload_video(
         );
     }
 
-    let file_data_for_processing = file_data.clone();
-    let job_video_service = Arc::clone(&video_service);
-    let job_storage_service = Arc::clone(&storage_service);
-    let job_processing_service = Arc::clone(&processing_service);
-    let job_metrics_service = Arc::clone(&metrics_service);
-    let job_filename = filename.clone();
-    let job_user_id = user_id_value;
+    spawn_upload_pipeline(
+        &app_state,
+        video_id,
+        user_id_value,
+        filename.clone(),
+        spool_path,
+        upload_benchmark_run_id,
+    );
+
+    let response = VideoUploadResponse {
+        video_id,
+        title,
+        description,
+        status: crate::models::VideoStatus::Uploading,
+        hls_files_count: 0,
+        total_size: file_size as i64,
+        created_at: video.created_at.unwrap_or_default(),
+    };
+
+    let http_response = HttpResponse::Accepted().json(ApiResponse::success(response));
+
+    if let Err(err) = metrics_service
+        .record_video_processing_step(
+            upload_benchmark_run_id,
+            Some(video_id),
+            "upload_handler_complete",
+            Some(handler_timer.elapsed().as_millis() as i64),
+            None,
+            None,
+        )
+        .await
+    {
+        log::warn!(
+            "Failed to record upload_handler_complete metric for {}: {}",
+            video_id,
+            err
+        );
+    }
+
+    Ok(http_response)
+}
+
+// Video list query parameters
+#[derive(Debug, Deserialize)]
+pub struct VideoListQuery {
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+/// Upload a received original to storage and hand it to the processing pipeline in the background.
+pub(crate) fn spawn_upload_pipeline(
+    app_state: &AppState,
+    video_id: Uuid,
+    user_id: Uuid,
+    filename: String,
+    source_path: TempPath,
+    benchmark_run_id: Option<Uuid>,
+) {
+    let job_video_service = Arc::clone(&app_state.video_service);
+    let job_storage_service = Arc::clone(&app_state.storage_service);
+    let job_processing_service = Arc::clone(&app_state.video_processing_service);
+    let job_metrics_service = Arc::clone(&app_state.metrics_service);
+    let job_filename = filename;
+    let job_user_id = user_id;
     let job_video_id = video_id;
-    let job_file_data = file_data;
-    let job_benchmark_run_id = upload_benchmark_run_id;
+    // Dropping the source path removes the spooled file once it is in storage
+    let job_source_path = source_path;
+    let job_benchmark_run_id = benchmark_run_id;
 
     actix_web::rt::spawn(async move {
         let storage_video_path = job_storage_service.get_video_path(&job_video_id, &job_filename);
+        let local_video_path = job_source_path.to_string_lossy().to_string();
 
         let upload_timer = Instant::now();
         if let Err(e) = job_storage_service
-            .upload_file_data(job_file_data, &storage_video_path)
+            .upload_file(&local_video_path, &storage_video_path)
             .await
         {
             log::error!("Failed to upload video file to storage: {}", e);
@@ -255,19 +376,19 @@ pub async fn upload_video(
             );
         }
 
-        log::info!("Starting video processing task for {}", job_video_id);
+        log::info!("Queueing video processing for {}", job_video_id);
         if let Err(e) = job_processing_service
-            .process_video(
-                job_video_id,
-                file_data_for_processing,
-                &job_filename,
-                job_benchmark_run_id,
-            )
+            .process_video(job_video_id, &job_filename, job_benchmark_run_id)
             .await
         {
-            log::error!("Failed to start video processing: {}", e);
+            log::error!("Failed to queue video processing: {}", e);
+            let failure = ProcessingFailure::new(
+                FailureCategory::Internal,
+                format!("Failed to queue processing: {:#}", e),
+                0,
+            );
             if let Err(update_err) = job_video_service
-                .update_video_status(&job_video_id, crate::models::VideoStatus::Failed)
+                .mark_video_failed(&job_video_id, &failure)
                 .await
             {
                 log::error!(
@@ -278,53 +399,35 @@ pub async fn upload_video(
             }
         }
     });
+}
 
-    let response = VideoUploadResponse {
-        video_id,
-        title,
-        description,
-        status: crate::models::VideoStatus::Uploading,
-        hls_files_count: 0,
-        total_size: file_size as i64,
-        created_at: video.created_at.unwrap_or_default(),
-    };
-
-    let http_response = HttpResponse::Accepted().json(ApiResponse::success(response));
+/// Stored name of the original, with the extension of the detected container rather than
+/// whatever the uploaded file was called.
+pub(crate) fn storage_filename(title: &str, extension: &str) -> String {
+    format!(
+        "{}.{}",
+        title.to_lowercase().replace(" ", "_").replace(".", ""),
+        extension
+    )
+}
 
-    if let Err(err) = metrics_service
-        .record_video_processing_step(
-            upload_benchmark_run_id,
-            Some(video_id),
-            "upload_handler_complete",
-            Some(handler_timer.elapsed().as_millis() as i64),
-            None,
-            None,
-        )
-        .await
-    {
-        log::warn!(
-            "Failed to record upload_handler_complete metric for {}: {}",
-            video_id,
-            err
-        );
+/// Status for an upload whose content was rejected.
+pub(crate) fn rejection_status(rejection: &UploadRejection) -> StatusCode {
+    match rejection {
+        UploadRejection::UnsupportedFormat => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+        UploadRejection::InvalidContent(_) => StatusCode::UNPROCESSABLE_ENTITY,
     }
-
-    Ok(http_response)
 }
 
-// Video list query parameters
-#[derive(Debug, Deserialize)]
-pub struct VideoListQuery {
-    pub limit: Option<i64>,
-    pub offset: Option<i64>,
-}
+/// Create a temp file under `UPLOAD_DIR` that is removed again when its path is dropped.
+async fn create_spool_file() -> std::io::Result<(std::fs::File, TempPath)> {
+    let upload_dir = std::env::var("UPLOAD_DIR").unwrap_or_else(|_| "uploads".to_string());
+    fs::create_dir_all(&upload_dir).await?;
 
-fn is_video_file(filename: &str) -> bool {
-    let extension = filename.split('.').last().unwrap_or("").to_lowercase();
-    matches!(
-        extension.as_str(),
-        "mp4" | "mov" | "avi" | "mkv" | "webm" | "flv" | "wmv" | "m4v"
-    )
+    let spool = tempfile::Builder::new()
+        .prefix("upload_")
+        .tempfile_in(&upload_dir)?;
+    Ok(spool.into_parts())
 }
 
 /// List videos with pagination
@@ -399,6 +502,96 @@ pub async fn get_video(
     }
 }
 
+/// Get processing progress for a video
+pub async fn get_video_progress(
+    app_state: web::Data<AppState>,
+    user_id: web::ReqData<Uuid>,
+    path: web::Path<Uuid>,
+) -> Result<HttpResponse> {
+    let video_service = Arc::clone(&app_state.video_service);
+    let user_id_value = user_id.into_inner();
+    let video_id = path.into_inner();
+
+    match video_service.get_video_by_id(&video_id).await {
+        Ok(Some(video)) => {
+            // Check if user owns the video
+            if video.user_id != user_id_value {
+                return Ok(HttpResponse::Forbidden()
+                    .json(ApiResponse::<String>::error("Access denied", None)));
+            }
+
+            Ok(HttpResponse::Ok().json(ApiResponse::success(VideoProgressResponse::from(video))))
+        }
+        Ok(None) => Ok(
+            HttpResponse::NotFound().json(ApiResponse::<String>::error("Video not found", None))
+        ),
+        Err(e) => {
+            log::error!("Failed to get video progress: {}", e);
+            Ok(HttpResponse::InternalServerError()
+                .json(ApiResponse::<String>::error("Failed to fetch video progress", None)))
+        }
+    }
+}
+
+/// Server-Sent Events stream of status, progress and metadata changes for the caller's videos
+pub async fn video_events(
+    app_state: web::Data<AppState>,
+    user_id: web::ReqData<Uuid>,
+) -> Result<HttpResponse> {
+    let user_id_value = user_id.into_inner();
+    let receiver = app_state.video_event_bus.subscribe();
+
+    let mut keep_alive = tokio::time::interval(SSE_KEEP_ALIVE_INTERVAL);
+    keep_alive.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    // The first tick completes immediately, doubling as the initial flush
+    let events = stream::unfold(
+        (receiver, keep_alive),
+        move |(mut receiver, mut keep_alive)| async move {
+            loop {
+                tokio::select! {
+                    _ = keep_alive.tick() => {
+                        let frame = Bytes::from_static(b": keep-alive\n\n");
+                        return Some((
+                            Ok::<_, actix_web::Error>(frame),
+                            (receiver, keep_alive),
+                        ));
+                    }
+                    received = receiver.recv() => match received {
+                        Ok(event) if event.user_id == user_id_value => {
+                            let data = match serde_json::to_string(&event) {
+                                Ok(data) => data,
+                                Err(err) => {
+                                    log::warn!("Failed to encode video event: {}", err);
+                                    continue;
+                                }
+                            };
+                            let frame =
+                                format!("event: {}\ndata: {}\n\n", event.kind.name(), data);
+                            return Some((Ok(Bytes::from(frame)), (receiver, keep_alive)));
+                        }
+                        Ok(_) => continue,
+                        Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                            log::warn!(
+                                "SSE subscriber for user {} skipped {} video events",
+                                user_id_value,
+                                skipped
+                            );
+                            continue;
+                        }
+                        Err(broadcast::error::RecvError::Closed) => return None,
+                    },
+                }
+            }
+        },
+    );
+
+    Ok(HttpResponse::Ok()
+        .content_type("text/event-stream")
+        .insert_header(("Cache-Control", "no-cache"))
+        .insert_header(("X-Accel-Buffering", "no"))
+        .streaming(events))
+}
+
 /// Get video streaming information
 pub async fn stream_video(
     app_state: web::Data<AppState>,
@@ -460,11 +653,19 @@ pub async fn stream_video(
     }
 }
 
+/// Thumbnail query parameters; without either the default thumbnail (small JPEG) is served
+#[derive(Debug, Deserialize)]
+pub struct ThumbnailQuery {
+    pub size: Option<ThumbnailSize>,
+    pub format: Option<ThumbnailFormat>,
+}
+
 /// Get video thumbnail
 pub async fn get_thumbnail(
     app_state: web::Data<AppState>,
     user_id: web::ReqData<Uuid>,
     path: web::Path<Uuid>,
+    query: web::Query<ThumbnailQuery>,
 ) -> Result<HttpResponse> {
     let video_service = Arc::clone(&app_state.video_service);
     let storage_service = Arc::clone(&app_state.storage_service);
@@ -473,19 +674,13 @@ pub async fn get_thumbnail(
     let video_id = path.into_inner();
 
     // Verify video exists and user has access
-    match video_service.get_video_by_id(&video_id).await {
+    let video = match video_service.get_video_by_id(&video_id).await {
         Ok(Some(video)) => {
             if video.user_id != user_id_value {
                 return Ok(HttpResponse::Forbidden()
                     .json(ApiResponse::<String>::error("Access denied", None)));
             }
-
-            if video.thumbnail_path.is_none() {
-                return Ok(HttpResponse::NotFound().json(ApiResponse::<String>::error(
-                    "Thumbnail not available",
-                    None,
-                )));
-            }
+            video
         }
         Ok(None) => {
             return Ok(HttpResponse::NotFound()
@@ -500,33 +695,65 @@ pub async fn get_thumbnail(
                 )),
             );
         }
-    }
+    };
 
-    // Construct GCS path for thumbnail
-    let gcs_path = format!("thumbnails/{}.jpg", video_id);
+    let thumbnail_path = if query.size.is_none() && query.format.is_none() {
+        video.thumbnail_path.clone()
+    } else {
+        let size = query.size.unwrap_or(ThumbnailSize::Small);
+        let format = query.format.unwrap_or(ThumbnailFormat::Jpeg);
+        video
+            .thumbnail_variants()
+            .into_iter()
+            .find(|variant| variant.size == size && variant.format == format)
+            .map(|variant| variant.path)
+    };
+    let Some(thumbnail_path) = thumbnail_path else {
+        return Ok(HttpResponse::NotFound().json(ApiResponse::<String>::error(
+            "Thumbnail not available",
+            None,
+        )));
+    };
+    let content_type = match thumbnail_path.rsplit('.').next() {
+        Some("webp") => "image/webp",
+        Some("png") => "image/png",
+        _ => "image/jpeg",
+    };
 
-    // Download thumbnail from GCS
-    let temp_path = format!("/tmp/thumb_{}.jpg", video_id);
-    if let Err(e) = storage_service.download_file(&gcs_path, &temp_path).await {
-        log::error!("Failed to download thumbnail from storage: {}", e);
+    let temp_path = match tempfile::NamedTempFile::new() {
+        Ok(file) => file.into_temp_path(),
+        Err(e) => {
+            log::error!("Failed to create temp file for thumbnail: {}", e);
+            return Ok(
+                HttpResponse::InternalServerError().json(ApiResponse::<String>::error(
+                    "Failed to read thumbnail",
+                    None,
+                )),
+            );
+        }
+    };
+    let local_path = temp_path.to_string_lossy().to_string();
+    if let Err(e) = storage_service
+        .download_file(&thumbnail_path, &local_path)
+        .await
+    {
+        log::error!(
+            "Failed to download thumbnail {} from storage: {}",
+            thumbnail_path,
+            e
+        );
         return Ok(HttpResponse::NotFound()
             .json(ApiResponse::<String>::error("Thumbnail not found", None)));
     }
 
-    // Read thumbnail content
-    match tokio::fs::read(&temp_path).await {
-        Ok(content) => {
-            // Clean up temp file
-            let _ = tokio::fs::remove_file(&temp_path).await;
-
-            Ok(HttpResponse::Ok()
-                .content_type("image/jpeg")
-                .append_header(("Cache-Control", "public, max-age=86400")) // Cache for 24 hours
-                .body(content))
-        }
+    match fs::read(&temp_path).await {
+        // Replacing the thumbnail overwrites the same files, so clients must not reuse old copies
+        Ok(content) => Ok(HttpResponse::Ok()
+            .content_type(content_type)
+            .append_header(("Cache-Control", "private, no-cache"))
+            .body(content)),
         Err(e) => {
             log::error!("Failed to read thumbnail: {}", e);
-            let _ = tokio::fs::remove_file(&temp_path).await;
             Ok(
                 HttpResponse::InternalServerError().json(ApiResponse::<String>::error(
                     "Failed to read thumbnail",
@@ -537,6 +764,225 @@ pub async fn get_thumbnail(
     }
 }
 
+/// JPEG or PNG file extension matching the image's signature
+fn thumbnail_image_extension(data: &[u8]) -> Option<&'static str> {
+    if data.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        Some("jpg")
+    } else if data.starts_with(&[0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1A, b'\n']) {
+        Some("png")
+    } else {
+        None
+    }
+}
+
+/// Replace the video thumbnail with one of its candidates (`candidate` field) or an uploaded
+/// JPEG or PNG image (`file` field)
+pub async fn update_thumbnail(
+    app_state: web::Data<AppState>,
+    user_id: web::ReqData<Uuid>,
+    path: web::Path<Uuid>,
+    mut payload: Multipart,
+) -> Result<HttpResponse> {
+    let video_service = Arc::clone(&app_state.video_service);
+    let storage_service = Arc::clone(&app_state.storage_service);
+
+    let user_id_value = user_id.into_inner();
+    let video_id = path.into_inner();
+
+    let video = match video_service.get_video_by_id(&video_id).await {
+        Ok(Some(video)) => {
+            if video.user_id != user_id_value {
+                return Ok(HttpResponse::Forbidden()
+                    .json(ApiResponse::<String>::error("Access denied", None)));
+            }
+            video
+        }
+        Ok(None) => {
+            return Ok(HttpResponse::NotFound()
+                .json(ApiResponse::<String>::error("Video not found", None)))
+        }
+        Err(e) => {
+            log::error!("Failed to fetch video for thumbnail update: {}", e);
+            return Ok(HttpResponse::InternalServerError()
+                .json(ApiResponse::<String>::error("Failed to fetch video", None)));
+        }
+    };
+
+    // Processing would replace the thumbnail again once it finishes
+    if video.get_status() != VideoStatus::Ready {
+        return Ok(HttpResponse::Conflict().json(ApiResponse::<String>::error(
+            "Video is still being processed",
+            None,
+        )));
+    }
+
+    let mut candidate = None;
+    let mut file_data: Option<Vec<u8>> = None;
+
+    while let Some(mut field) = payload.try_next().await? {
+        let field_name = field.name().to_string();
+        let mut data = Vec::new();
+        while let Some(chunk) = field.try_next().await? {
+            data.extend_from_slice(&chunk);
+            if data.len() > MAX_THUMBNAIL_FILE_SIZE {
+                return Ok(
+                    HttpResponse::PayloadTooLarge().json(ApiResponse::<String>::error(
+                        &format!(
+                            "Thumbnail too large. Maximum allowed: {} MB",
+                            MAX_THUMBNAIL_FILE_SIZE / 1024 / 1024
+                        ),
+                        None,
+                    )),
+                );
+            }
+        }
+
+        match field_name.as_str() {
+            "candidate" => candidate = Some(String::from_utf8_lossy(&data).trim().to_string()),
+            "file" => file_data = Some(data),
+            _ => {}
+        }
+    }
+
+    let temp_dir = match tempfile::tempdir() {
+        Ok(temp_dir) => temp_dir,
+        Err(e) => {
+            log::error!("Failed to create thumbnail directory: {}", e);
+            return Ok(
+                HttpResponse::InternalServerError().json(ApiResponse::<String>::error(
+                    "Failed to update thumbnail",
+                    None,
+                )),
+            );
+        }
+    };
+    let temp_path = temp_dir.path().to_string_lossy().to_string();
+
+    let selected_candidate = match candidate {
+        Some(candidate) => {
+            let index = candidate.parse::<usize>().ok();
+            match video
+                .thumbnail_candidates()
+                .into_iter()
+                .find(|candidate| Some(candidate.index) == index)
+            {
+                Some(candidate) => Some(candidate),
+                None => {
+                    return Ok(
+                        HttpResponse::BadRequest().json(ApiResponse::<String>::error(
+                            "Unknown thumbnail candidate",
+                            None,
+                        )),
+                    );
+                }
+            }
+        }
+        None => None,
+    };
+
+    let source_path = match (&selected_candidate, file_data) {
+        (Some(candidate), None) => {
+            let source_path = format!("{}/candidate.jpg", temp_path);
+            storage_service
+                .download_file(&candidate.path, &source_path)
+                .await
+                .map(|_| source_path)
+        }
+        (None, Some(data)) => {
+            let Some(extension) = thumbnail_image_extension(&data) else {
+                return Ok(
+                    HttpResponse::BadRequest().json(ApiResponse::<String>::error(
+                        "Thumbnail must be a JPEG or PNG image",
+                        None,
+                    )),
+                );
+            };
+
+            let source_path = format!("{}/custom.{}", temp_path, extension);
+            fs::write(&source_path, &data)
+                .await
+                .map(|_| source_path)
+                .map_err(anyhow::Error::from)
+        }
+        _ => {
+            return Ok(
+                HttpResponse::BadRequest().json(ApiResponse::<String>::error(
+                    "Provide either a thumbnail candidate or an image file",
+                    None,
+                )),
+            );
+        }
+    };
+    let source_path = match source_path {
+        Ok(source_path) => source_path,
+        Err(e) => {
+            log::error!("Failed to prepare thumbnail source image: {}", e);
+            return Ok(
+                HttpResponse::InternalServerError().json(ApiResponse::<String>::error(
+                    "Failed to update thumbnail",
+                    None,
+                )),
+            );
+        }
+    };
+    let selected_candidate = selected_candidate.map(|candidate| candidate.index as i32);
+
+    let variants = match store_thumbnail_variants(
+        storage_service.as_ref(),
+        &source_path,
+        &temp_path,
+        &storage_service.get_thumbnails_path(&video_id),
+    )
+    .await
+    {
+        Ok(variants) => variants,
+        Err(e) if selected_candidate.is_none() && e.downcast_ref::<FfmpegError>().is_some() => {
+            log::warn!("Failed to decode uploaded thumbnail: {:#}", e);
+            return Ok(
+                HttpResponse::BadRequest().json(ApiResponse::<String>::error(
+                    "Thumbnail image could not be decoded",
+                    None,
+                )),
+            );
+        }
+        Err(e) => {
+            log::error!("Failed to store thumbnail: {:#}", e);
+            return Ok(
+                HttpResponse::InternalServerError().json(ApiResponse::<String>::error(
+                    "Failed to update thumbnail",
+                    None,
+                )),
+            );
+        }
+    };
+
+    match video_service
+        .update_video_thumbnail(
+            &video_id,
+            default_thumbnail_path(&variants),
+            &variants,
+            selected_candidate,
+            None,
+        )
+        .await
+    {
+        Ok(updated_video) => {
+            let response =
+                VideoResponse::from_video_with_storage(updated_video, storage_service.as_ref());
+            Ok(HttpResponse::Ok().json(ApiResponse::success(response)))
+        }
+        Err(e) => {
+            log::error!("Failed to update video thumbnail: {}", e);
+            Ok(
+                HttpResponse::InternalServerError().json(ApiResponse::<String>::error(
+                    "Failed to update thumbnail",
+                    None,
+                )),
+            )
+        }
+    }
+}
+
 /// Delete video
 pub async fn delete_video(
     app_state: web::Data<AppState>,
@@ -0,0 +1,4 @@
+//! Modules shared by the API server and the transcoding worker binaries.
+
+pub mod models;
+pub mod services;
@@ -1,21 +1,22 @@
 use actix_cors::Cors;
-use actix_web::{middleware::Logger, web, App, HttpServer};
+use actix_files::Files;
+use actix_web::{http::Method, middleware::Logger, web, App, HttpServer};
 use dotenv::dotenv;
 use std::env;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 mod app_state;
 mod handlers;
 mod middleware;
-mod models;
-mod services;
 mod utils;
 
+use video_stream_be::{models, services};
+
 use app_state::AppState;
-use handlers::{auth, metrics, videos};
+use handlers::{auth, metrics, subtitles, uploads, videos};
 use middleware::{auth_middleware, MetricsMiddleware};
 use serde_json::json;
-use services::database;
+use services::{database, LocalFsStorageService, StorageBackend, LOCAL_STORAGE_ROUTE};
 
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
@@ -46,6 +47,10 @@ async fn main() -> std::io::Result<()> {
         .await
         .expect("Failed to initialize application state");
 
+    // Serve stored objects ourselves when running against the local filesystem backend
+    let local_storage_root = (app_state.storage_backend == StorageBackend::Local)
+        .then(LocalFsStorageService::root_dir_from_env);
+
     // Measure cold start before server bind
     let cold_start_duration_ms = cold_start_timer.elapsed().as_millis() as i64;
 
@@ -71,11 +76,29 @@ async fn main() -> std::io::Result<()> {
         log::warn!("Failed to record server startup metric: {}", err);
     }
 
+    // Relay video status changes from every instance (and the worker) to SSE subscribers
+    app_state.video_event_bus.spawn_listener(pool.clone());
+
+    // Drop expired unfinished resumable uploads and their partial data
+    let upload_service = app_state.upload_service.clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(60 * 60));
+        loop {
+            interval.tick().await;
+            match upload_service.delete_expired_uploads().await {
+                Ok(0) => {}
+                Ok(count) => log::info!("Removed {} expired uploads", count),
+                Err(err) => log::warn!("Failed to remove expired uploads: {}", err),
+            }
+        }
+    });
+
     HttpServer::new(move || {
         let cors = allowed_origins.iter().fold(
             Cors::default()
                 .allow_any_method()
                 .allow_any_header()
+                .expose_headers(uploads::TUS_EXPOSED_HEADERS)
                 .supports_credentials()
                 .max_age(3600),
             |cors, origin| cors.allowed_origin(origin),
@@ -103,9 +126,25 @@ async fn main() -> std::io::Result<()> {
                             .wrap(auth_middleware::AuthMiddleware)
                             .route("", web::get().to(videos::list_videos))
                             .route("", web::post().to(videos::upload_video))
+                            .route("/events", web::get().to(videos::video_events))
                             .route("/{id}", web::get().to(videos::get_video))
+                            .route("/{id}/progress", web::get().to(videos::get_video_progress))
                             .route("/{id}", web::put().to(videos::update_video))
-                            .route("/{id}", web::delete().to(videos::delete_video)),
+                            .route("/{id}", web::delete().to(videos::delete_video))
+                            .route("/{id}/thumbnail", web::get().to(videos::get_thumbnail))
+                            .route("/{id}/thumbnail", web::put().to(videos::update_thumbnail))
+                            .route("/{id}/subtitles", web::get().to(subtitles::list_subtitles))
+                            .route("/{id}/subtitles", web::post().to(subtitles::upload_subtitle))
+                            .route("/{id}/subtitles/{track_id}", web::delete().to(subtitles::delete_subtitle)),
+                    )
+                    .service(
+                        web::scope("/uploads")
+                            .wrap(auth_middleware::AuthMiddleware)
+                            .route("", web::method(Method::OPTIONS).to(uploads::tus_options))
+                            .route("", web::post().to(uploads::create_upload))
+                            .route("/{id}", web::head().to(uploads::get_upload_offset))
+                            .route("/{id}", web::patch().to(uploads::append_upload))
+                            .route("/{id}", web::delete().to(uploads::terminate_upload)),
                     )
                     .service(
                         web::scope("/metrics")
@@ -113,6 +152,11 @@ async fn main() -> std::io::Result<()> {
                             .route("/insights", web::get().to(metrics::get_metrics_insights)),
                     ),
             )
+            .configure(|cfg| {
+                if let Some(root) = &local_storage_root {
+                    cfg.service(Files::new(LOCAL_STORAGE_ROUTE, root));
+                }
+            })
     })
     .bind(format!("0.0.0.0:{}", port))?
     .run()
@@ -0,0 +1,18 @@
+use serde::{Deserialize, Serialize};
+
+/// Audio-only HLS rendition made from one audio stream of the source, stored in
+/// `videos.audio_tracks`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AudioTrack {
+    /// Position among the source's audio streams
+    pub index: usize,
+    /// RFC 5646 tag such as `en`, when the source stream is tagged with a language
+    pub language: Option<String>,
+    /// Label for player track menus: the stream title, else its language
+    pub name: String,
+    pub channels: u32,
+    /// Selected when the player has no language preference
+    pub default: bool,
+    /// Media playlist next to `playlist.m3u8`
+    pub playlist: String,
+}
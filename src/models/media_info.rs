@@ -0,0 +1,157 @@
+use serde::{Deserialize, Serialize};
+
+/// Technical description of the uploaded source, probed once before encoding and stored in
+/// `videos.media_info`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct MediaInfo {
+    /// FFmpeg demuxer names, such as `mov,mp4,m4a,3gp,3g2,mj2`
+    pub container: String,
+    pub duration_ms: i64,
+    /// Overall bitrate in bits per second
+    pub bit_rate: Option<u64>,
+    pub size: Option<u64>,
+    pub video: Vec<VideoStreamInfo>,
+    pub audio: Vec<AudioStreamInfo>,
+    pub subtitles: Vec<SubtitleStreamInfo>,
+}
+
+impl MediaInfo {
+    pub fn duration_secs(&self) -> f64 {
+        self.duration_ms as f64 / 1000.0
+    }
+
+    /// Stream the renditions are encoded from, the first one as FFmpeg picks by default.
+    pub fn primary_video(&self) -> Option<&VideoStreamInfo> {
+        self.video.first()
+    }
+}
+
+/// Video stream of the source; cover art attached to audio files is not listed.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct VideoStreamInfo {
+    pub codec: String,
+    pub profile: Option<String>,
+    /// Coded size, before rotation
+    pub width: u32,
+    pub height: u32,
+    pub frame_rate: Option<f64>,
+    pub bit_rate: Option<u64>,
+    pub pixel_format: Option<String>,
+    /// Clockwise rotation players apply on display: 0, 90, 180 or 270
+    pub rotation: u32,
+    pub color_space: Option<String>,
+    pub color_transfer: Option<String>,
+    pub color_primaries: Option<String>,
+    pub color_range: Option<String>,
+    pub dynamic_range: DynamicRange,
+}
+
+impl VideoStreamInfo {
+    /// Size as displayed, which FFmpeg also outputs as it applies the rotation while decoding.
+    pub fn display_dimensions(&self) -> (u32, u32) {
+        if self.rotation % 180 == 90 {
+            (self.height, self.width)
+        } else {
+            (self.width, self.height)
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum DynamicRange {
+    Sdr,
+    /// PQ transfer (SMPTE ST 2084)
+    Hdr10,
+    Hlg,
+    DolbyVision,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AudioStreamInfo {
+    pub codec: String,
+    pub profile: Option<String>,
+    pub channels: u32,
+    pub channel_layout: Option<String>,
+    pub sample_rate: Option<u32>,
+    pub bit_rate: Option<u64>,
+    /// RFC 5646 tag such as `en`, when the stream is tagged with a language
+    pub language: Option<String>,
+    pub title: Option<String>,
+    pub default: bool,
+    /// Measured when the encoding ladder normalizes loudness
+    #[serde(default)]
+    pub loudness: Option<LoudnessInfo>,
+}
+
+/// EBU R128 loudness of an audio stream, as measured by the first `loudnorm` pass.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct LoudnessInfo {
+    /// Integrated loudness in LUFS
+    pub integrated_lufs: f64,
+    /// True peak in dBTP
+    pub true_peak_dbtp: f64,
+    /// Loudness range in LU
+    pub loudness_range: f64,
+    /// Gating threshold in LUFS
+    pub threshold_lufs: f64,
+    /// Integrated loudness the stream is normalized to
+    pub target_lufs: f64,
+    /// Gain `loudnorm` computed for the target, applied in the second pass
+    pub target_offset: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SubtitleStreamInfo {
+    pub codec: String,
+    pub language: Option<String>,
+    pub title: Option<String>,
+    pub default: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn video(width: u32, height: u32, rotation: u32) -> VideoStreamInfo {
+        VideoStreamInfo {
+            codec: "h264".to_string(),
+            profile: Some("High".to_string()),
+            width,
+            height,
+            frame_rate: Some(30.0),
+            bit_rate: None,
+            pixel_format: Some("yuv420p".to_string()),
+            rotation,
+            color_space: None,
+            color_transfer: None,
+            color_primaries: None,
+            color_range: None,
+            dynamic_range: DynamicRange::Sdr,
+        }
+    }
+
+    #[test]
+    fn swaps_dimensions_for_quarter_turns() {
+        assert_eq!(video(1920, 1080, 0).display_dimensions(), (1920, 1080));
+        assert_eq!(video(1920, 1080, 90).display_dimensions(), (1080, 1920));
+        assert_eq!(video(1920, 1080, 180).display_dimensions(), (1920, 1080));
+        assert_eq!(video(1920, 1080, 270).display_dimensions(), (1080, 1920));
+    }
+
+    #[test]
+    fn reports_duration_in_seconds_and_first_video_stream() {
+        let info = MediaInfo {
+            container: "matroska,webm".to_string(),
+            duration_ms: 12_345,
+            bit_rate: None,
+            size: None,
+            video: vec![video(1280, 720, 0), video(640, 360, 0)],
+            audio: Vec::new(),
+            subtitles: Vec::new(),
+        };
+
+        assert_eq!(info.duration_secs(), 12.345);
+        assert_eq!(info.primary_video().map(|video| video.width), Some(1280));
+    }
+}
@@ -1,5 +1,21 @@
+pub mod audio_track;
+pub mod media_info;
+pub mod processing_failure;
+pub mod processing_job;
+pub mod subtitle_track;
+pub mod thumbnail;
+pub mod upload;
 pub mod user;
 pub mod video;
+pub mod video_event;
 
+pub use audio_track::*;
+pub use media_info::*;
+pub use processing_failure::*;
+pub use processing_job::*;
+pub use subtitle_track::*;
+pub use thumbnail::*;
+pub use upload::*;
 pub use user::*;
 pub use video::*;
+pub use video_event::*;
@@ -0,0 +1,73 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// User-facing bucket a processing failure falls into.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum FailureCategory {
+    CorruptInput,
+    UnsupportedCodec,
+    MissingVideoStream,
+    StorageError,
+    Interrupted,
+    Internal,
+}
+
+impl FailureCategory {
+    /// Explanation shown to the uploader.
+    pub fn description(&self) -> &'static str {
+        match self {
+            FailureCategory::CorruptInput => "The uploaded file is corrupt or truncated",
+            FailureCategory::UnsupportedCodec => "The video uses a codec we can't decode",
+            FailureCategory::MissingVideoStream => {
+                "The uploaded file does not contain a video stream"
+            }
+            FailureCategory::StorageError => {
+                "The video could not be read from or written to storage"
+            }
+            FailureCategory::Interrupted => "Processing was interrupted too many times",
+            FailureCategory::Internal => "Processing failed because of an internal error",
+        }
+    }
+}
+
+impl std::fmt::Display for FailureCategory {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FailureCategory::CorruptInput => write!(f, "corrupt_input"),
+            FailureCategory::UnsupportedCodec => write!(f, "unsupported_codec"),
+            FailureCategory::MissingVideoStream => write!(f, "missing_video_stream"),
+            FailureCategory::StorageError => write!(f, "storage_error"),
+            FailureCategory::Interrupted => write!(f, "interrupted"),
+            FailureCategory::Internal => write!(f, "internal"),
+        }
+    }
+}
+
+/// Why processing of a video gave up, stored in `videos.failure_details`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProcessingFailure {
+    pub category: FailureCategory,
+    pub description: String,
+    /// Full error chain, also kept in `videos.failure_reason`
+    pub message: String,
+    /// Step name as recorded by `record_video_processing_step`
+    pub step: Option<String>,
+    pub stderr_tail: Option<String>,
+    pub attempts: i32,
+    pub failed_at: DateTime<Utc>,
+}
+
+impl ProcessingFailure {
+    pub fn new(category: FailureCategory, message: impl Into<String>, attempts: i32) -> Self {
+        Self {
+            category,
+            description: category.description().to_string(),
+            message: message.into(),
+            step: None,
+            stderr_tail: None,
+            attempts,
+            failed_at: Utc::now(),
+        }
+    }
+}
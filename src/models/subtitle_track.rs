@@ -0,0 +1,98 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use std::str::FromStr;
+use uuid::Uuid;
+
+use crate::services::CloudStorageService;
+
+/// File name of the complete WebVTT file in a track's storage folder.
+pub const SUBTITLE_WEBVTT_FILENAME: &str = "subtitles.vtt";
+
+/// File name of the track's HLS media playlist in its storage folder.
+pub const SUBTITLE_PLAYLIST_FILENAME: &str = "playlist.m3u8";
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct SubtitleTrack {
+    pub id: Uuid,
+    pub video_id: Uuid,
+    pub language: Option<String>,
+    pub name: String,
+    pub source: String,
+    /// Storage folder of the track, ending in `/`
+    pub storage_path: String,
+    pub created_at: DateTime<Utc>,
+}
+
+impl SubtitleTrack {
+    pub fn get_source(&self) -> SubtitleSource {
+        SubtitleSource::from_str(&self.source).unwrap_or(SubtitleSource::Upload)
+    }
+}
+
+/// Where a subtitle track came from.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum SubtitleSource {
+    /// Uploaded through the API as SRT or WebVTT
+    Upload,
+    /// Extracted from a text subtitle stream of the source during processing
+    Embedded,
+}
+
+impl FromStr for SubtitleSource {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "upload" => Ok(SubtitleSource::Upload),
+            "embedded" => Ok(SubtitleSource::Embedded),
+            _ => Err(format!("Invalid subtitle source: {}", s)),
+        }
+    }
+}
+
+impl std::fmt::Display for SubtitleSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SubtitleSource::Upload => write!(f, "upload"),
+            SubtitleSource::Embedded => write!(f, "embedded"),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct SubtitleTrackResponse {
+    pub id: Uuid,
+    pub language: Option<String>,
+    pub name: String,
+    pub source: SubtitleSource,
+    /// The complete track, for `<track>` elements and downloads
+    pub webvtt_url: String,
+    /// Segmented track as referenced by the master playlist
+    pub hls_playlist_url: String,
+    pub created_at: DateTime<Utc>,
+}
+
+impl SubtitleTrackResponse {
+    pub fn from_track_with_storage(
+        track: SubtitleTrack,
+        storage_service: &(dyn CloudStorageService + Send + Sync),
+    ) -> Self {
+        SubtitleTrackResponse {
+            id: track.id,
+            source: track.get_source(),
+            webvtt_url: storage_service.get_public_url(&format!(
+                "{}{}",
+                track.storage_path, SUBTITLE_WEBVTT_FILENAME
+            )),
+            hls_playlist_url: storage_service.get_public_url(&format!(
+                "{}{}",
+                track.storage_path, SUBTITLE_PLAYLIST_FILENAME
+            )),
+            language: track.language,
+            name: track.name,
+            created_at: track.created_at,
+        }
+    }
+}
@@ -0,0 +1,122 @@
+use serde::{Deserialize, Serialize};
+
+use crate::services::CloudStorageService;
+
+/// Sizes every thumbnail is rendered in, letterboxed to 16:9.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ThumbnailSize {
+    Small,
+    Medium,
+    Large,
+}
+
+impl ThumbnailSize {
+    pub const ALL: [ThumbnailSize; 3] = [
+        ThumbnailSize::Small,
+        ThumbnailSize::Medium,
+        ThumbnailSize::Large,
+    ];
+
+    pub fn dimensions(&self) -> (u32, u32) {
+        match self {
+            ThumbnailSize::Small => (320, 180),
+            ThumbnailSize::Medium => (640, 360),
+            ThumbnailSize::Large => (1280, 720),
+        }
+    }
+}
+
+impl std::fmt::Display for ThumbnailSize {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ThumbnailSize::Small => write!(f, "small"),
+            ThumbnailSize::Medium => write!(f, "medium"),
+            ThumbnailSize::Large => write!(f, "large"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ThumbnailFormat {
+    Jpeg,
+    Webp,
+}
+
+impl ThumbnailFormat {
+    pub const ALL: [ThumbnailFormat; 2] = [ThumbnailFormat::Jpeg, ThumbnailFormat::Webp];
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ThumbnailFormat::Jpeg => "jpg",
+            ThumbnailFormat::Webp => "webp",
+        }
+    }
+}
+
+/// One rendered size and format of the selected thumbnail, stored in
+/// `videos.thumbnail_variants`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ThumbnailVariant {
+    pub size: ThumbnailSize,
+    pub format: ThumbnailFormat,
+    pub width: u32,
+    pub height: u32,
+    pub path: String,
+}
+
+/// Frame picked from the video as a possible thumbnail, stored in
+/// `videos.thumbnail_candidates`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ThumbnailCandidate {
+    /// What `PUT /videos/{id}/thumbnail` takes as `candidate`
+    pub index: usize,
+    /// Position of the frame in the video
+    pub time_secs: f64,
+    pub path: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThumbnailVariantResponse {
+    pub size: ThumbnailSize,
+    pub format: ThumbnailFormat,
+    pub width: u32,
+    pub height: u32,
+    pub url: String,
+}
+
+impl ThumbnailVariantResponse {
+    pub fn from_variant_with_storage(
+        variant: ThumbnailVariant,
+        storage_service: &(dyn CloudStorageService + Send + Sync),
+    ) -> Self {
+        ThumbnailVariantResponse {
+            url: storage_service.get_public_url(&variant.path),
+            size: variant.size,
+            format: variant.format,
+            width: variant.width,
+            height: variant.height,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ThumbnailCandidateResponse {
+    pub index: usize,
+    pub time_secs: f64,
+    pub url: String,
+}
+
+impl ThumbnailCandidateResponse {
+    pub fn from_candidate_with_storage(
+        candidate: ThumbnailCandidate,
+        storage_service: &(dyn CloudStorageService + Send + Sync),
+    ) -> Self {
+        ThumbnailCandidateResponse {
+            url: storage_service.get_public_url(&candidate.path),
+            index: candidate.index,
+            time_secs: candidate.time_secs,
+        }
+    }
+}
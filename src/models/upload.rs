@@ -0,0 +1,48 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// A resumable tus upload. Bytes up to `upload_offset` are stored in the session's part file.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct UploadSession {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub title: String,
+    pub description: Option<String>,
+    pub original_filename: String,
+    pub upload_length: i64,
+    pub upload_offset: i64,
+    pub metadata: serde_json::Value,
+    pub video_id: Option<Uuid>,
+    pub expires_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    /// Request currently writing to the upload, until `locked_until`
+    pub locked_by: Option<Uuid>,
+    pub locked_until: Option<DateTime<Utc>>,
+}
+
+impl UploadSession {
+    pub fn is_complete(&self) -> bool {
+        self.completed_at.is_some()
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.expires_at <= Utc::now()
+    }
+
+    pub fn is_locked(&self) -> bool {
+        self.locked_until.is_some_and(|until| until > Utc::now())
+    }
+}
+
+#[derive(Debug)]
+pub struct CreateUploadSessionRequest {
+    pub title: String,
+    pub description: Option<String>,
+    pub original_filename: String,
+    pub upload_length: i64,
+    pub metadata: serde_json::Value,
+}
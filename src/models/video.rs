@@ -5,6 +5,10 @@ use std::str::FromStr;
 use uuid::Uuid;
 use validator::Validate;
 
+use crate::models::{
+    AudioTrack, MediaInfo, ProcessingFailure, ThumbnailCandidate, ThumbnailCandidateResponse,
+    ThumbnailVariant, ThumbnailVariantResponse,
+};
 use crate::services::CloudStorageService;
 
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
@@ -16,10 +20,30 @@ pub struct Video {
     pub original_filename: String,
     pub file_size: i64,
     pub duration: Option<i32>, // Duration in seconds
+    pub duration_ms: Option<i64>,
+    pub media_info: Option<serde_json::Value>,
     pub thumbnail_path: Option<String>,
+    pub thumbnail_variants: Option<serde_json::Value>,
+    pub thumbnail_candidates: Option<serde_json::Value>,
+    /// Candidate the thumbnail was made from; `None` for a custom upload
+    pub thumbnail_candidate: Option<i32>,
     pub hls_playlist_path: Option<String>,
+    /// Set when the renditions are CMAF, so DASH clients can play the same segments
+    pub dash_manifest_path: Option<String>,
+    /// WebVTT storyboard mapping time ranges to trick-play sprite regions
+    pub storyboard_path: Option<String>,
+    /// Silent animated hover preview, with `preview_webp_path` as its WebP counterpart
+    pub preview_path: Option<String>,
+    pub preview_webp_path: Option<String>,
     #[sqlx(rename = "status")]
     pub status: Option<String>, // Store as string for SQLx compatibility
+    pub failure_reason: Option<String>,
+    pub failure_details: Option<serde_json::Value>,
+    pub progress_percent: Option<f64>,
+    pub rendition_progress: Option<serde_json::Value>,
+    pub progress_updated_at: Option<DateTime<Utc>>,
+    pub encoding_ladder: Option<String>,
+    pub audio_tracks: Option<serde_json::Value>,
     pub user_id: Uuid,
     pub created_at: Option<DateTime<Utc>>,
     pub updated_at: Option<DateTime<Utc>>,
@@ -36,6 +60,44 @@ impl Video {
     pub fn set_status(&mut self, status: VideoStatus) {
         self.status = Some(status.to_string());
     }
+
+    /// Structured failure record, if processing failed and one was stored.
+    pub fn failure(&self) -> Option<ProcessingFailure> {
+        self.failure_details
+            .clone()
+            .and_then(|details| serde_json::from_value(details).ok())
+    }
+
+    /// Separate audio renditions; empty for videos processed before they existed or
+    /// whose source has no audio.
+    pub fn audio_tracks(&self) -> Vec<AudioTrack> {
+        self.audio_tracks
+            .clone()
+            .and_then(|tracks| serde_json::from_value(tracks).ok())
+            .unwrap_or_default()
+    }
+
+    /// Sizes and formats of the thumbnail; empty for videos processed before they existed.
+    pub fn thumbnail_variants(&self) -> Vec<ThumbnailVariant> {
+        self.thumbnail_variants
+            .clone()
+            .and_then(|variants| serde_json::from_value(variants).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn thumbnail_candidates(&self) -> Vec<ThumbnailCandidate> {
+        self.thumbnail_candidates
+            .clone()
+            .and_then(|candidates| serde_json::from_value(candidates).ok())
+            .unwrap_or_default()
+    }
+
+    /// Probed description of the source; `None` for videos processed before it was stored.
+    pub fn media_info(&self) -> Option<MediaInfo> {
+        self.media_info
+            .clone()
+            .and_then(|info| serde_json::from_value(info).ok())
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -77,6 +139,9 @@ pub struct CreateVideoRequest {
     pub title: String,
     #[validate(length(max = 1000))]
     pub description: Option<String>,
+    /// Encoding ladder name; the configured default when `None`
+    #[validate(length(min = 1, max = 50))]
+    pub encoding_ladder: Option<String>,
 }
 
 #[derive(Debug, Deserialize, Validate)]
@@ -116,16 +181,62 @@ pub struct VideoResponse {
     pub filename: String,
     pub file_size: i64,
     pub duration: Option<i32>,
+    pub duration_ms: Option<i64>,
+    pub media_info: Option<MediaInfo>,
     pub thumbnail_path: Option<String>,
     pub hls_playlist_path: Option<String>,
     pub hls_stream_url: Option<String>,
+    pub dash_manifest_path: Option<String>,
+    pub dash_stream_url: Option<String>,
+    pub storyboard_path: Option<String>,
+    pub storyboard_url: Option<String>,
+    pub preview_url: Option<String>,
+    pub preview_webp_url: Option<String>,
+    pub audio_tracks: Vec<AudioTrack>,
     pub thumbnail_url: Option<String>,
+    pub thumbnails: Vec<ThumbnailVariantResponse>,
+    pub thumbnail_candidates: Vec<ThumbnailCandidateResponse>,
+    pub thumbnail_candidate: Option<i32>,
     pub status: VideoStatus,
+    pub encoding_ladder: Option<String>,
+    pub failure: Option<ProcessingFailure>,
+    pub progress_percent: Option<f64>,
+    pub rendition_progress: Option<serde_json::Value>,
     pub user_id: Uuid,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
+#[derive(Debug, Serialize)]
+pub struct VideoProgressResponse {
+    pub video_id: Uuid,
+    pub status: VideoStatus,
+    pub progress_percent: f64,
+    pub renditions: serde_json::Value,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl From<Video> for VideoProgressResponse {
+    fn from(video: Video) -> Self {
+        let status = video.get_status();
+        // Videos processed before progress tracking existed only know whether they finished
+        let progress_percent = video.progress_percent.unwrap_or(match status {
+            VideoStatus::Ready => 100.0,
+            _ => 0.0,
+        });
+
+        VideoProgressResponse {
+            video_id: video.id,
+            status,
+            progress_percent,
+            renditions: video
+                .rendition_progress
+                .unwrap_or_else(|| serde_json::json!({})),
+            updated_at: video.progress_updated_at,
+        }
+    }
+}
+
 #[derive(Debug, Serialize)]
 pub struct PaginatedResponse<T> {
     pub data: Vec<T>,
@@ -149,17 +260,56 @@ impl VideoResponse {
         storage_service: &(dyn CloudStorageService + Send + Sync),
     ) -> Self {
         let status = video.get_status();
+        let failure = video.failure();
+        let audio_tracks = video.audio_tracks();
+        let media_info = video.media_info();
 
         let hls_stream_url = video
             .hls_playlist_path
             .as_ref()
             .map(|path| storage_service.get_public_url(path));
 
+        let dash_stream_url = video
+            .dash_manifest_path
+            .as_ref()
+            .map(|path| storage_service.get_public_url(path));
+
+        let storyboard_url = video
+            .storyboard_path
+            .as_ref()
+            .map(|path| storage_service.get_public_url(path));
+
+        let preview_url = video
+            .preview_path
+            .as_ref()
+            .map(|path| storage_service.get_public_url(path));
+
+        let preview_webp_url = video
+            .preview_webp_path
+            .as_ref()
+            .map(|path| storage_service.get_public_url(path));
+
         let thumbnail_url = video
             .thumbnail_path
             .as_ref()
             .map(|path| storage_service.get_public_url(path));
 
+        let thumbnails = video
+            .thumbnail_variants()
+            .into_iter()
+            .map(|variant| {
+                ThumbnailVariantResponse::from_variant_with_storage(variant, storage_service)
+            })
+            .collect();
+
+        let thumbnail_candidates = video
+            .thumbnail_candidates()
+            .into_iter()
+            .map(|candidate| {
+                ThumbnailCandidateResponse::from_candidate_with_storage(candidate, storage_service)
+            })
+            .collect();
+
         VideoResponse {
             id: video.id,
             title: video.title,
@@ -167,11 +317,27 @@ impl VideoResponse {
             filename: video.filename,
             file_size: video.file_size,
             duration: video.duration,
+            duration_ms: video.duration_ms,
+            media_info,
             thumbnail_path: video.thumbnail_path,
             hls_playlist_path: video.hls_playlist_path,
             hls_stream_url,
+            dash_manifest_path: video.dash_manifest_path,
+            dash_stream_url,
+            storyboard_path: video.storyboard_path,
+            storyboard_url,
+            preview_url,
+            preview_webp_url,
+            audio_tracks,
             thumbnail_url,
+            thumbnails,
+            thumbnail_candidates,
+            thumbnail_candidate: video.thumbnail_candidate,
             status,
+            encoding_ladder: video.encoding_ladder,
+            failure,
+            progress_percent: video.progress_percent,
+            rendition_progress: video.rendition_progress,
             user_id: video.user_id,
             created_at: video.created_at.unwrap_or_default(),
             updated_at: video.updated_at.unwrap_or_default(),
@@ -183,13 +349,22 @@ impl From<Video> for VideoResponse {
     fn from(video: Video) -> Self {
         let video_id = video.id;
         let status = video.get_status();
+        let failure = video.failure();
+        let audio_tracks = video.audio_tracks();
+        let media_info = video.media_info();
 
         let hls_stream_url = None;
+        let dash_stream_url = None;
+        let storyboard_url = None;
+        let preview_url = None;
+        let preview_webp_url = None;
 
         let thumbnail_url = video
             .thumbnail_path
             .as_ref()
             .map(|_| format!("/api/v1/videos/{}/thumbnail", video_id));
+        let thumbnails = Vec::new();
+        let thumbnail_candidates = Vec::new();
 
         VideoResponse {
             id: video.id,
@@ -198,11 +373,27 @@ impl From<Video> for VideoResponse {
             filename: video.filename,
             file_size: video.file_size,
             duration: video.duration,
+            duration_ms: video.duration_ms,
+            media_info,
             thumbnail_path: video.thumbnail_path,
             hls_playlist_path: video.hls_playlist_path,
             hls_stream_url,
+            dash_manifest_path: video.dash_manifest_path,
+            dash_stream_url,
+            storyboard_path: video.storyboard_path,
+            storyboard_url,
+            preview_url,
+            preview_webp_url,
+            audio_tracks,
             thumbnail_url,
+            thumbnails,
+            thumbnail_candidates,
+            thumbnail_candidate: video.thumbnail_candidate,
             status,
+            encoding_ladder: video.encoding_ladder,
+            failure,
+            progress_percent: video.progress_percent,
+            rendition_progress: video.rendition_progress,
             user_id: video.user_id,
             created_at: video.created_at.unwrap_or_default(),
             updated_at: video.updated_at.unwrap_or_default(),
@@ -0,0 +1,86 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::models::{AudioTrack, ThumbnailVariantResponse, Video, VideoStatus};
+use crate::services::CloudStorageService;
+
+/// Change to a video pushed to its owner over `/api/v1/videos/events`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VideoEvent {
+    pub video_id: Uuid,
+    pub user_id: Uuid,
+    #[serde(flatten)]
+    pub kind: VideoEventKind,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum VideoEventKind {
+    Status {
+        status: VideoStatus,
+        failure_reason: Option<String>,
+    },
+    Progress {
+        progress_percent: f64,
+        renditions: serde_json::Value,
+    },
+    /// Playback details, with the same public URLs as the video details.
+    Metadata {
+        duration: Option<i32>,
+        duration_ms: Option<i64>,
+        thumbnail_url: Option<String>,
+        thumbnails: Vec<ThumbnailVariantResponse>,
+        thumbnail_candidate: Option<i32>,
+        hls_stream_url: Option<String>,
+        dash_stream_url: Option<String>,
+        storyboard_url: Option<String>,
+        preview_url: Option<String>,
+        preview_webp_url: Option<String>,
+        audio_tracks: Vec<AudioTrack>,
+    },
+    /// Stand-in for an event too large for a Postgres notification; `event` names the
+    /// original event and clients fetch the video to see the change.
+    Changed { event: String },
+}
+
+impl VideoEventKind {
+    /// SSE event name
+    pub fn name(&self) -> &'static str {
+        match self {
+            VideoEventKind::Status { .. } => "status",
+            VideoEventKind::Progress { .. } => "progress",
+            VideoEventKind::Metadata { .. } => "metadata",
+            VideoEventKind::Changed { .. } => "changed",
+        }
+    }
+
+    pub fn metadata(
+        video: &Video,
+        storage_service: &(dyn CloudStorageService + Send + Sync),
+    ) -> Self {
+        let public_url = |path: &Option<String>| {
+            path.as_ref()
+                .map(|path| storage_service.get_public_url(path))
+        };
+
+        VideoEventKind::Metadata {
+            duration: video.duration,
+            duration_ms: video.duration_ms,
+            thumbnail_url: public_url(&video.thumbnail_path),
+            thumbnails: video
+                .thumbnail_variants()
+                .into_iter()
+                .map(|variant| {
+                    ThumbnailVariantResponse::from_variant_with_storage(variant, storage_service)
+                })
+                .collect(),
+            thumbnail_candidate: video.thumbnail_candidate,
+            hls_stream_url: public_url(&video.hls_playlist_path),
+            dash_stream_url: public_url(&video.dash_manifest_path),
+            storyboard_url: public_url(&video.storyboard_path),
+            preview_url: public_url(&video.preview_path),
+            preview_webp_url: public_url(&video.preview_webp_path),
+            audio_tracks: video.audio_tracks(),
+        }
+    }
+}
@@ -0,0 +1,377 @@
+use crate::services::MediaSegment;
+
+/// Timescale of `SegmentTimeline` durations, in ticks per second.
+const TIMELINE_TIMESCALE: u64 = 1000;
+
+/// One CMAF rendition, described from its HLS media playlist.
+#[derive(Debug, Clone)]
+pub struct DashRepresentation {
+    pub id: String,
+    pub bandwidth: u64,
+    pub codecs: Vec<String>,
+    pub width: i32,
+    pub height: i32,
+    pub frame_rate: Option<f64>,
+    pub init_uri: String,
+    pub segments: Vec<MediaSegment>,
+}
+
+/// One audio-only CMAF rendition, made from one audio stream of the source.
+#[derive(Debug, Clone)]
+pub struct DashAudioRepresentation {
+    pub id: String,
+    pub bandwidth: u64,
+    pub codecs: Vec<String>,
+    pub language: Option<String>,
+    pub channels: u32,
+    pub default: bool,
+    pub init_uri: String,
+    pub segments: Vec<MediaSegment>,
+}
+
+/// Static MPD referencing the same init sections and `.m4s` segments as the HLS playlists.
+pub fn render_dash_manifest(
+    representations: &[DashRepresentation],
+    audio_representations: &[DashAudioRepresentation],
+    min_buffer_secs: f64,
+) -> String {
+    let duration = representations
+        .iter()
+        .map(|representation| &representation.segments)
+        .chain(
+            audio_representations
+                .iter()
+                .map(|representation| &representation.segments),
+        )
+        .map(|segments| segments.iter().map(|segment| segment.duration).sum::<f64>())
+        .fold(0.0, f64::max);
+
+    let mut manifest = format!(
+        concat!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+            "<MPD xmlns=\"urn:mpeg:dash:schema:mpd:2011\" profiles=\"urn:mpeg:dash:profile:isoff-main:2011\" ",
+            "type=\"static\" mediaPresentationDuration=\"PT{:.3}S\" minBufferTime=\"PT{:.1}S\">\n",
+            "  <Period id=\"0\" start=\"PT0S\">\n",
+        ),
+        duration, min_buffer_secs
+    );
+
+    // Players only switch within an AdaptationSet, so each video codec gets its own
+    let mut codec_families: Vec<&str> = Vec::new();
+    for representation in representations {
+        let family = codec_family(&representation.codecs);
+        if !codec_families.contains(&family) {
+            codec_families.push(family);
+        }
+    }
+
+    let mut adaptation_set_id = 0;
+    for family in codec_families {
+        manifest.push_str(&format!(
+            "    <AdaptationSet id=\"{}\" mimeType=\"video/mp4\" segmentAlignment=\"true\" startWithSAP=\"1\">\n",
+            adaptation_set_id
+        ));
+        for representation in representations
+            .iter()
+            .filter(|representation| codec_family(&representation.codecs) == family)
+        {
+            manifest.push_str(&render_representation(representation));
+        }
+        manifest.push_str("    </AdaptationSet>\n");
+        adaptation_set_id += 1;
+    }
+
+    // Each source audio stream is a separate language or mix, so one AdaptationSet apiece
+    for representation in audio_representations {
+        let language = representation
+            .language
+            .as_ref()
+            .map(|language| format!(" lang=\"{}\"", escape_xml(language)))
+            .unwrap_or_default();
+        manifest.push_str(&format!(
+            "    <AdaptationSet id=\"{}\" mimeType=\"audio/mp4\"{} segmentAlignment=\"true\" startWithSAP=\"1\">\n",
+            adaptation_set_id, language
+        ));
+        let role = if representation.default {
+            "main"
+        } else {
+            "alternate"
+        };
+        manifest.push_str(&format!(
+            "      <Role schemeIdUri=\"urn:mpeg:dash:role:2011\" value=\"{}\"/>\n",
+            role
+        ));
+        manifest.push_str(&render_audio_representation(representation));
+        manifest.push_str("    </AdaptationSet>\n");
+        adaptation_set_id += 1;
+    }
+
+    manifest.push_str("  </Period>\n</MPD>\n");
+    manifest
+}
+
+/// Sample entry of the video codec, such as `avc1` or `av01`; empty when the codecs are unknown.
+fn codec_family(codecs: &[String]) -> &str {
+    codecs
+        .first()
+        .map(|codec| codec.split('.').next().unwrap_or_default())
+        .unwrap_or_default()
+}
+
+fn render_representation(representation: &DashRepresentation) -> String {
+    let mut attributes = format!(
+        "id=\"{}\" bandwidth=\"{}\" width=\"{}\" height=\"{}\"",
+        escape_xml(&representation.id),
+        representation.bandwidth,
+        representation.width,
+        representation.height
+    );
+    if !representation.codecs.is_empty() {
+        attributes.push_str(&format!(
+            " codecs=\"{}\"",
+            escape_xml(&representation.codecs.join(","))
+        ));
+    }
+    if let Some(frame_rate) = representation.frame_rate {
+        attributes.push_str(&format!(" frameRate=\"{}\"", dash_frame_rate(frame_rate)));
+    }
+
+    format!(
+        "      <Representation {}>\n{}      </Representation>\n",
+        attributes,
+        render_segment_list(&representation.init_uri, &representation.segments)
+    )
+}
+
+fn render_audio_representation(representation: &DashAudioRepresentation) -> String {
+    let mut attributes = format!(
+        "id=\"{}\" bandwidth=\"{}\"",
+        escape_xml(&representation.id),
+        representation.bandwidth
+    );
+    if !representation.codecs.is_empty() {
+        attributes.push_str(&format!(
+            " codecs=\"{}\"",
+            escape_xml(&representation.codecs.join(","))
+        ));
+    }
+
+    format!(
+        concat!(
+            "      <Representation {}>\n",
+            "        <AudioChannelConfiguration schemeIdUri=\"urn:mpeg:dash:23003:3:audio_channel_configuration:2011\" value=\"{}\"/>\n",
+            "{}",
+            "      </Representation>\n",
+        ),
+        attributes,
+        representation.channels,
+        render_segment_list(&representation.init_uri, &representation.segments)
+    )
+}
+
+fn render_segment_list(init_uri: &str, segments: &[MediaSegment]) -> String {
+    let mut segment_list = format!(
+        "        <SegmentList timescale=\"{}\">\n          <Initialization sourceURL=\"{}\"/>\n          <SegmentTimeline>\n",
+        TIMELINE_TIMESCALE,
+        escape_xml(init_uri)
+    );
+    for (index, (duration, repeat)) in segment_timeline(segments).into_iter().enumerate() {
+        let start = if index == 0 { " t=\"0\"" } else { "" };
+        let repeat = if repeat > 0 {
+            format!(" r=\"{}\"", repeat)
+        } else {
+            String::new()
+        };
+        segment_list.push_str(&format!(
+            "            <S{} d=\"{}\"{}/>\n",
+            start, duration, repeat
+        ));
+    }
+    segment_list.push_str("          </SegmentTimeline>\n");
+    for segment in segments {
+        segment_list.push_str(&format!(
+            "          <SegmentURL media=\"{}\"/>\n",
+            escape_xml(&segment.uri)
+        ));
+    }
+    segment_list.push_str("        </SegmentList>\n");
+    segment_list
+}
+
+/// Segment durations in timeline ticks as `(duration, repeat)` runs. Durations are taken
+/// between rounded segment start times so rounding never accumulates drift.
+fn segment_timeline(segments: &[MediaSegment]) -> Vec<(u64, u32)> {
+    let mut runs: Vec<(u64, u32)> = Vec::new();
+    let mut elapsed = 0.0;
+    let mut start_ticks = 0;
+
+    for segment in segments {
+        elapsed += segment.duration;
+        let end_ticks = (elapsed * TIMELINE_TIMESCALE as f64).round() as u64;
+        let duration = end_ticks - start_ticks;
+        start_ticks = end_ticks;
+
+        match runs.last_mut() {
+            Some((last, repeat)) if *last == duration => *repeat += 1,
+            _ => runs.push((duration, 0)),
+        }
+    }
+
+    runs
+}
+
+/// `frameRate` as an integer, or as a `/1001` fraction for NTSC rates such as 29.97.
+fn dash_frame_rate(frame_rate: f64) -> String {
+    if (frame_rate - frame_rate.round()).abs() < 0.001 {
+        return format!("{}", frame_rate.round() as u64);
+    }
+    let ntsc = frame_rate * 1.001;
+    if (ntsc - ntsc.round()).abs() < 0.01 {
+        return format!("{}/1001", ntsc.round() as u64 * 1000);
+    }
+    format!("{}/1000", (frame_rate * 1000.0).round() as u64)
+}
+
+fn escape_xml(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn segments(prefix: &str, durations: &[f64]) -> Vec<MediaSegment> {
+        durations
+            .iter()
+            .enumerate()
+            .map(|(index, duration)| MediaSegment {
+                duration: *duration,
+                uri: format!("{}_{:03}.m4s", prefix, index),
+            })
+            .collect()
+    }
+
+    fn video(
+        name: &str,
+        bandwidth: u64,
+        width: i32,
+        height: i32,
+        frame_rate: f64,
+    ) -> DashRepresentation {
+        DashRepresentation {
+            id: name.to_string(),
+            bandwidth,
+            codecs: vec!["avc1.640028".to_string()],
+            width,
+            height,
+            frame_rate: Some(frame_rate),
+            init_uri: format!("{}_init.mp4", name),
+            segments: segments(name, &[6.0, 6.0, 3.5]),
+        }
+    }
+
+    #[test]
+    fn renders_two_rung_video_with_audio() {
+        let representations = [
+            video("1080p", 2_200_000, 1920, 1080, 30.0),
+            video("720p", 1_100_000, 1280, 720, 29.97),
+        ];
+        let audio = [DashAudioRepresentation {
+            id: "audio_0".to_string(),
+            bandwidth: 196_000,
+            codecs: vec!["mp4a.40.2".to_string()],
+            language: Some("en".to_string()),
+            channels: 2,
+            default: true,
+            init_uri: "audio_0_init.mp4".to_string(),
+            segments: segments("audio_0", &[6.0, 6.0, 3.52]),
+        }];
+
+        let manifest = render_dash_manifest(&representations, &audio, 12.0);
+
+        assert_eq!(
+            manifest,
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<MPD xmlns="urn:mpeg:dash:schema:mpd:2011" profiles="urn:mpeg:dash:profile:isoff-main:2011" type="static" mediaPresentationDuration="PT15.520S" minBufferTime="PT12.0S">
+  <Period id="0" start="PT0S">
+    <AdaptationSet id="0" mimeType="video/mp4" segmentAlignment="true" startWithSAP="1">
+      <Representation id="1080p" bandwidth="2200000" width="1920" height="1080" codecs="avc1.640028" frameRate="30">
+        <SegmentList timescale="1000">
+          <Initialization sourceURL="1080p_init.mp4"/>
+          <SegmentTimeline>
+            <S t="0" d="6000" r="1"/>
+            <S d="3500"/>
+          </SegmentTimeline>
+          <SegmentURL media="1080p_000.m4s"/>
+          <SegmentURL media="1080p_001.m4s"/>
+          <SegmentURL media="1080p_002.m4s"/>
+        </SegmentList>
+      </Representation>
+      <Representation id="720p" bandwidth="1100000" width="1280" height="720" codecs="avc1.640028" frameRate="30000/1001">
+        <SegmentList timescale="1000">
+          <Initialization sourceURL="720p_init.mp4"/>
+          <SegmentTimeline>
+            <S t="0" d="6000" r="1"/>
+            <S d="3500"/>
+          </SegmentTimeline>
+          <SegmentURL media="720p_000.m4s"/>
+          <SegmentURL media="720p_001.m4s"/>
+          <SegmentURL media="720p_002.m4s"/>
+        </SegmentList>
+      </Representation>
+    </AdaptationSet>
+    <AdaptationSet id="1" mimeType="audio/mp4" lang="en" segmentAlignment="true" startWithSAP="1">
+      <Role schemeIdUri="urn:mpeg:dash:role:2011" value="main"/>
+      <Representation id="audio_0" bandwidth="196000" codecs="mp4a.40.2">
+        <AudioChannelConfiguration schemeIdUri="urn:mpeg:dash:23003:3:audio_channel_configuration:2011" value="2"/>
+        <SegmentList timescale="1000">
+          <Initialization sourceURL="audio_0_init.mp4"/>
+          <SegmentTimeline>
+            <S t="0" d="6000" r="1"/>
+            <S d="3520"/>
+          </SegmentTimeline>
+          <SegmentURL media="audio_0_000.m4s"/>
+          <SegmentURL media="audio_0_001.m4s"/>
+          <SegmentURL media="audio_0_002.m4s"/>
+        </SegmentList>
+      </Representation>
+    </AdaptationSet>
+  </Period>
+</MPD>
+"#
+        );
+    }
+
+    #[test]
+    fn splits_codecs_into_adaptation_sets() {
+        let mut av1 = video("1080p_av1", 1_500_000, 1920, 1080, 25.0);
+        av1.codecs = vec!["av01.0.08M.08".to_string()];
+        let manifest = render_dash_manifest(
+            &[video("1080p", 2_200_000, 1920, 1080, 25.0), av1],
+            &[],
+            12.0,
+        );
+
+        assert_eq!(manifest.matches("<AdaptationSet ").count(), 2);
+        assert!(manifest.contains(
+            "    <AdaptationSet id=\"1\" mimeType=\"video/mp4\" segmentAlignment=\"true\" startWithSAP=\"1\">\n      <Representation id=\"1080p_av1\""
+        ));
+    }
+
+    #[test]
+    fn keeps_timeline_free_of_rounding_drift() {
+        let segments = segments("720p", &[3.3333, 3.3333, 3.3333, 3.3333]);
+
+        // Boundaries at 0, 3333, 6667, 10000 and 13333 ticks
+        assert_eq!(
+            segment_timeline(&segments),
+            [(3333, 0), (3334, 0), (3333, 1)]
+        );
+        assert_eq!(dash_frame_rate(23.976), "24000/1001");
+        assert_eq!(dash_frame_rate(12.5), "12500/1000");
+    }
+}
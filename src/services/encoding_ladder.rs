@@ -0,0 +1,353 @@
+use anyhow::{anyhow, Context, Result};
+use config::{Config, File, FileFormat};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::env;
+
+use crate::services::{VideoCodec, VideoEncoder, LOUDNESS_TARGET_RANGE};
+
+/// Ladders shipped with the binary; `ENCODING_LADDERS_FILE` is layered on top.
+const BUILTIN_ENCODING_LADDERS: &str = include_str!("../../config/encoding_ladders.toml");
+
+/// One HLS rendition: the box the source is scaled to fit in and its target bitrates.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Rendition {
+    pub name: String,
+    pub width: i32,
+    pub height: i32,
+    pub video_bitrate: String,
+    pub audio_bitrate: String,
+    /// Codecs the rung is encoded with, each as its own variant
+    #[serde(default = "default_codecs")]
+    pub codecs: Vec<VideoCodec>,
+    /// Per-codec overrides of `video_bitrate`, e.g. a lower one for AV1
+    #[serde(default)]
+    pub codec_bitrates: HashMap<VideoCodec, String>,
+    /// Encoder of a single-codec variant, set by `VideoEncoders::expand_renditions`
+    #[serde(skip)]
+    pub encoder: VideoEncoder,
+}
+
+fn default_codecs() -> Vec<VideoCodec> {
+    vec![VideoCodec::H264]
+}
+
+/// Named sets of renditions an upload can be encoded with.
+#[derive(Debug, Clone, Deserialize)]
+pub struct EncodingLadders {
+    pub default_ladder: String,
+    pub ladders: HashMap<String, Vec<Rendition>>,
+    /// Integrated loudness in LUFS the audio of a ladder is normalized to; ladders without a
+    /// target keep the source loudness
+    #[serde(default)]
+    pub loudness_targets: HashMap<String, f64>,
+}
+
+impl EncodingLadders {
+    /// Load the built-in ladders, overridden by `ENCODING_LADDERS_FILE` and `DEFAULT_ENCODING_LADDER`.
+    pub fn from_env() -> Result<Self> {
+        let mut builder = Config::builder()
+            .add_source(File::from_str(BUILTIN_ENCODING_LADDERS, FileFormat::Toml));
+        if let Ok(path) = env::var("ENCODING_LADDERS_FILE") {
+            log::info!("Loading encoding ladders from {}", path);
+            builder = builder.add_source(File::with_name(&path));
+        }
+        let ladders: Self = builder
+            .set_override_option("default_ladder", env::var("DEFAULT_ENCODING_LADDER").ok())?
+            .build()
+            .context("Failed to load encoding ladders")?
+            .try_deserialize()
+            .context("Invalid encoding ladder configuration")?;
+
+        ladders.validate()?;
+        Ok(ladders)
+    }
+
+    fn validate(&self) -> Result<()> {
+        if !self.ladders.contains_key(&self.default_ladder) {
+            return Err(anyhow!(
+                "Default encoding ladder '{}' is not defined",
+                self.default_ladder
+            ));
+        }
+
+        for (name, renditions) in &self.ladders {
+            if renditions.is_empty() {
+                return Err(anyhow!("Encoding ladder '{}' has no renditions", name));
+            }
+            for (index, rendition) in renditions.iter().enumerate() {
+                // Rendition names double as the names of their output files
+                if renditions[..index]
+                    .iter()
+                    .any(|earlier| earlier.name == rendition.name)
+                {
+                    return Err(anyhow!(
+                        "Rendition '{}' appears twice in ladder '{}'",
+                        rendition.name,
+                        name
+                    ));
+                }
+                if rendition.width < 2 || rendition.height < 2 {
+                    return Err(anyhow!(
+                        "Rendition '{}' in ladder '{}' has invalid dimensions {}x{}",
+                        rendition.name,
+                        name,
+                        rendition.width,
+                        rendition.height
+                    ));
+                }
+                if rendition.codecs.is_empty() {
+                    return Err(anyhow!(
+                        "Rendition '{}' in ladder '{}' has no codecs",
+                        rendition.name,
+                        name
+                    ));
+                }
+                if parse_bitrate(&rendition.video_bitrate).is_none()
+                    || parse_bitrate(&rendition.audio_bitrate).is_none()
+                    || rendition
+                        .codec_bitrates
+                        .values()
+                        .any(|bitrate| parse_bitrate(bitrate).is_none())
+                {
+                    return Err(anyhow!(
+                        "Rendition '{}' in ladder '{}' has an invalid bitrate",
+                        rendition.name,
+                        name
+                    ));
+                }
+            }
+        }
+
+        for (name, target) in &self.loudness_targets {
+            if !self.ladders.contains_key(name) {
+                return Err(anyhow!(
+                    "Loudness target set for undefined encoding ladder '{}'",
+                    name
+                ));
+            }
+            if !LOUDNESS_TARGET_RANGE.contains(target) {
+                return Err(anyhow!(
+                    "Loudness target {} LUFS of ladder '{}' is outside {} to {} LUFS",
+                    target,
+                    name,
+                    LOUDNESS_TARGET_RANGE.start(),
+                    LOUDNESS_TARGET_RANGE.end()
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Canonical form of a requested ladder name, or an error listing the configured ladders.
+    pub fn validate_name(&self, name: &str) -> Result<String> {
+        let name = name.trim().to_lowercase();
+        if self.ladders.contains_key(&name) {
+            Ok(name)
+        } else {
+            Err(anyhow!(
+                "Unknown encoding ladder '{}'. Available ladders: {}",
+                name,
+                self.names().join(", ")
+            ))
+        }
+    }
+
+    /// Renditions of the named ladder, or of the default one when `name` is `None` or no longer configured.
+    pub fn resolve(&self, name: Option<&str>) -> (&str, &[Rendition]) {
+        if let Some(name) = name {
+            if let Some((name, renditions)) = self.ladders.get_key_value(&name.to_lowercase()) {
+                return (name, renditions);
+            }
+            log::warn!(
+                "Encoding ladder '{}' is not configured, using '{}'",
+                name,
+                self.default_ladder
+            );
+        }
+
+        (&self.default_ladder, &self.ladders[&self.default_ladder])
+    }
+
+    /// Target integrated loudness of the named ladder as resolved by `resolve`, when its audio
+    /// is normalized.
+    pub fn loudness_target(&self, name: &str) -> Option<f64> {
+        self.loudness_targets.get(name).copied()
+    }
+
+    /// Every codec some ladder asks for, to check against the available encoders.
+    pub fn codecs(&self) -> Vec<VideoCodec> {
+        let mut codecs: Vec<VideoCodec> = self
+            .ladders
+            .values()
+            .flatten()
+            .flat_map(|rendition| rendition.codecs.iter().copied())
+            .collect();
+        codecs.sort_unstable();
+        codecs.dedup();
+        codecs
+    }
+
+    pub fn names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.ladders.keys().map(String::as_str).collect();
+        names.sort_unstable();
+        names
+    }
+}
+
+/// Bits per second for an ffmpeg-style bitrate such as `2000k` or `2M`.
+pub fn parse_bitrate(bitrate: &str) -> Option<u64> {
+    let bitrate = bitrate.trim();
+    let (digits, multiplier) = match bitrate.chars().last()? {
+        'k' | 'K' => (&bitrate[..bitrate.len() - 1], 1_000),
+        'm' | 'M' => (&bitrate[..bitrate.len() - 1], 1_000_000),
+        _ => (bitrate, 1),
+    };
+    digits.parse::<u64>().ok().map(|value| value * multiplier)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(toml: &str) -> Result<EncodingLadders> {
+        let ladders: EncodingLadders = Config::builder()
+            .add_source(File::from_str(toml, FileFormat::Toml))
+            .build()?
+            .try_deserialize()?;
+        ladders.validate()?;
+        Ok(ladders)
+    }
+
+    fn ladder(rungs: &str) -> String {
+        format!("default_ladder = \"standard\"\n{}", rungs)
+    }
+
+    const RUNG_720P: &str = r#"
+[[ladders.standard]]
+name = "720p"
+width = 1280
+height = 720
+video_bitrate = "1000k"
+audio_bitrate = "128k"
+"#;
+
+    #[test]
+    fn parses_shipped_ladders() {
+        let ladders = parse(BUILTIN_ENCODING_LADDERS).unwrap();
+
+        assert_eq!(ladders.default_ladder, "standard");
+        assert_eq!(ladders.names(), ["extended", "mobile", "standard"]);
+        let standard: Vec<&str> = ladders.ladders["standard"]
+            .iter()
+            .map(|rendition| rendition.name.as_str())
+            .collect();
+        assert_eq!(standard, ["1080p", "720p", "480p", "360p"]);
+        assert_eq!(ladders.codecs(), [VideoCodec::H264]);
+    }
+
+    #[test]
+    fn parses_bitrates() {
+        assert_eq!(parse_bitrate("2000k"), Some(2_000_000));
+        assert_eq!(parse_bitrate(" 2M "), Some(2_000_000));
+        assert_eq!(parse_bitrate("128000"), Some(128_000));
+        assert_eq!(parse_bitrate(""), None);
+        assert_eq!(parse_bitrate("k"), None);
+        assert_eq!(parse_bitrate("1.5M"), None);
+        assert_eq!(parse_bitrate("-500k"), None);
+    }
+
+    #[test]
+    fn rejects_malformed_bitrates() {
+        let video = ladder(&RUNG_720P.replace("\"1000k\"", "\"fast\""));
+        let audio = ladder(&RUNG_720P.replace("\"128k\"", "\"128kbps\""));
+        let codec = ladder(&format!(
+            "{}codec_bitrates = {{ hevc = \"\" }}\n",
+            RUNG_720P
+        ));
+
+        for toml in [video, audio, codec] {
+            let err = parse(&toml).unwrap_err();
+            assert_eq!(
+                err.to_string(),
+                "Rendition '720p' in ladder 'standard' has an invalid bitrate"
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_empty_and_duplicate_ladders() {
+        let empty = "default_ladder = \"standard\"\n[ladders]\nstandard = []\n";
+        assert_eq!(
+            parse(empty).unwrap_err().to_string(),
+            "Encoding ladder 'standard' has no renditions"
+        );
+
+        let duplicate = ladder(&format!("{}{}", RUNG_720P, RUNG_720P));
+        assert_eq!(
+            parse(&duplicate).unwrap_err().to_string(),
+            "Rendition '720p' appears twice in ladder 'standard'"
+        );
+
+        let duplicate_table = format!("{}\n[ladders]\nstandard = []\n", ladder(RUNG_720P));
+        assert!(parse(&duplicate_table).is_err());
+
+        let missing_default = RUNG_720P.replace("ladders.standard", "ladders.mobile");
+        assert_eq!(
+            parse(&format!(
+                "default_ladder = \"standard\"\n{}",
+                missing_default
+            ))
+            .unwrap_err()
+            .to_string(),
+            "Default encoding ladder 'standard' is not defined"
+        );
+    }
+
+    #[test]
+    fn validates_loudness_targets() {
+        let ladders = parse(&format!(
+            "{}\n[loudness_targets]\nstandard = -16.0\n",
+            ladder(RUNG_720P)
+        ))
+        .unwrap();
+        assert_eq!(ladders.loudness_target("standard"), Some(-16.0));
+        assert_eq!(ladders.loudness_target("mobile"), None);
+
+        let undefined = format!(
+            "{}\n[loudness_targets]\nmobile = -16.0\n",
+            ladder(RUNG_720P)
+        );
+        assert_eq!(
+            parse(&undefined).unwrap_err().to_string(),
+            "Loudness target set for undefined encoding ladder 'mobile'"
+        );
+
+        let out_of_range = format!(
+            "{}\n[loudness_targets]\nstandard = 3.0\n",
+            ladder(RUNG_720P)
+        );
+        assert!(parse(&out_of_range)
+            .unwrap_err()
+            .to_string()
+            .starts_with("Loudness target 3 LUFS of ladder 'standard' is outside"));
+    }
+
+    #[test]
+    fn validates_and_resolves_names() {
+        let ladders = parse(BUILTIN_ENCODING_LADDERS).unwrap();
+
+        assert_eq!(ladders.validate_name(" Mobile ").unwrap(), "mobile");
+        assert_eq!(
+            ladders.validate_name("4k").unwrap_err().to_string(),
+            "Unknown encoding ladder '4k'. Available ladders: extended, mobile, standard"
+        );
+
+        let (name, renditions) = ladders.resolve(Some("EXTENDED"));
+        assert_eq!(name, "extended");
+        assert_eq!(renditions[0].name, "2160p");
+        assert_eq!(ladders.resolve(Some("removed")).0, "standard");
+        assert_eq!(ladders.resolve(None).0, "standard");
+    }
+}
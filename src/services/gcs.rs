@@ -11,14 +11,24 @@ use uuid::Uuid;
 #[async_trait]
 pub trait CloudStorageService: Send + Sync {
     async fn upload_file_data(&self, file_data: Vec<u8>, remote_path: &str) -> Result<String>;
+    /// Stream a file from local disk into storage without loading it into memory.
+    async fn upload_file(&self, local_path: &str, remote_path: &str) -> Result<String>;
     async fn download_file(&self, remote_path: &str, local_path: &str) -> Result<()>;
+    async fn file_exists(&self, remote_path: &str) -> Result<bool>;
     async fn delete_folder(&self, folder_prefix: &str) -> Result<()>;
     fn get_public_url(&self, remote_path: &str) -> String;
     #[allow(dead_code)]
     fn get_signed_url(&self, remote_path: &str, expiration_hours: u32) -> Result<String>;
     fn get_video_path(&self, video_id: &Uuid, filename: &str) -> String;
-    fn get_thumbnail_path(&self, video_id: &Uuid) -> String;
+    /// Folder holding the thumbnail in every size plus the candidates it was picked from
+    fn get_thumbnails_path(&self, video_id: &Uuid) -> String;
     fn get_hls_path(&self, video_id: &Uuid) -> String;
+    /// Folder holding one subfolder per subtitle track, a sibling of the HLS folder
+    fn get_subtitles_path(&self, video_id: &Uuid) -> String;
+    /// Folder holding the trick-play sprite sheets and their WebVTT storyboard
+    fn get_trickplay_path(&self, video_id: &Uuid) -> String;
+    /// Folder holding the animated hover preview as MP4 and WebP
+    fn get_preview_path(&self, video_id: &Uuid) -> String;
 }
 
 #[derive(Clone)]
@@ -86,6 +96,32 @@ impl CloudStorageService for GcsService {
         Ok(format!("gs://{}/{}", self.bucket_name, remote_path))
     }
 
+    async fn upload_file(&self, local_path: &str, remote_path: &str) -> Result<String> {
+        log::info!(
+            "Uploading file {} to gs://{}/{}",
+            local_path,
+            self.bucket_name,
+            remote_path
+        );
+
+        let file = fs::File::open(local_path)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to open {}: {}", local_path, e))?;
+
+        // The file payload is seekable, so the client can resume interrupted uploads
+        let bucket_path = format!("projects/_/buckets/{}", self.bucket_name);
+        self.storage_client
+            .write_object(&bucket_path, remote_path, file)
+            .set_cache_control(Self::determine_cache_control(remote_path))
+            .set_content_type(Self::get_content_type(remote_path))
+            .send_unbuffered()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to upload file to GCS: {}", e))?;
+
+        log::info!("Successfully uploaded {} to GCS", remote_path);
+        Ok(format!("gs://{}/{}", self.bucket_name, remote_path))
+    }
+
     async fn download_file(&self, remote_path: &str, local_path: &str) -> Result<()> {
         log::info!(
             "Downloading file from gs://{}/{} to {}",
@@ -123,6 +159,24 @@ impl CloudStorageService for GcsService {
         Ok(())
     }
 
+    async fn file_exists(&self, remote_path: &str) -> Result<bool> {
+        // An exact match sorts before every other name sharing the prefix
+        let response = self
+            .control_client
+            .list_objects()
+            .set_parent(format!("projects/_/buckets/{}", self.bucket_name))
+            .set_prefix(remote_path)
+            .set_page_size(1)
+            .send()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to look up {} in GCS: {}", remote_path, e))?;
+
+        Ok(response
+            .objects
+            .first()
+            .is_some_and(|object| object.name == remote_path))
+    }
+
     /// Delete every object whose name starts with the provided `folder_prefix`.
     ///
     /// We treat the prefix like a virtual directory: first try to remove an object
@@ -241,19 +295,30 @@ impl CloudStorageService for GcsService {
         format!("{}/videos/{}", video_id, filename)
     }
 
-    fn get_thumbnail_path(&self, video_id: &Uuid) -> String {
-        format!("{}/thumbnails/thumbnail.jpg", video_id)
+    fn get_thumbnails_path(&self, video_id: &Uuid) -> String {
+        format!("{}/thumbnails/", video_id)
     }
 
     fn get_hls_path(&self, video_id: &Uuid) -> String {
         format!("{}/hls/", video_id)
     }
+
+    fn get_subtitles_path(&self, video_id: &Uuid) -> String {
+        format!("{}/subtitles/", video_id)
+    }
+
+    fn get_trickplay_path(&self, video_id: &Uuid) -> String {
+        format!("{}/trickplay/", video_id)
+    }
+
+    fn get_preview_path(&self, video_id: &Uuid) -> String {
+        format!("{}/preview/", video_id)
+    }
 }
 
 impl GcsService {
     /// Get content type based on file extension
-    #[allow(dead_code)]
-    fn get_content_type(file_path: &str) -> String {
+    pub(crate) fn get_content_type(file_path: &str) -> String {
         let extension = Path::new(file_path)
             .extension()
             .and_then(|ext| ext.to_str())
@@ -272,13 +337,18 @@ impl GcsService {
             "jpg" | "jpeg" => "image/jpeg".to_string(),
             "png" => "image/png".to_string(),
             "gif" => "image/gif".to_string(),
+            "webp" => "image/webp".to_string(),
             "m3u8" => "application/vnd.apple.mpegurl".to_string(),
             "ts" => "video/mp2t".to_string(),
+            "m4s" => "video/iso.segment".to_string(),
+            "mpd" => "application/dash+xml".to_string(),
+            "vtt" => "text/vtt".to_string(),
             _ => "application/octet-stream".to_string(),
         }
     }
 
-    fn determine_cache_control(file_path: &str) -> String {
+    /// Short-lived caching for playlists, long-lived for immutable media segments
+    pub(crate) fn determine_cache_control(file_path: &str) -> String {
         let extension = Path::new(file_path)
             .extension()
             .and_then(|ext| ext.to_str())
@@ -286,8 +356,8 @@ impl GcsService {
             .to_lowercase();
 
         match extension.as_str() {
-            "m3u8" => "public, max-age=1, no-transform".to_string(),
-            "ts" | "mp4" => "public, max-age=86400".to_string(),
+            "m3u8" | "mpd" => "public, max-age=1, no-transform".to_string(),
+            "ts" | "m4s" | "mp4" | "vtt" => "public, max-age=86400".to_string(),
             _ => "no-cache".to_string(),
         }
     }
@@ -0,0 +1,789 @@
+use anyhow::{anyhow, Context, Result};
+
+/// Version written to master playlists; 6 is the first with AVERAGE-BANDWIDTH and FRAME-RATE.
+pub const MASTER_PLAYLIST_VERSION: u32 = 6;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MediaSegment {
+    pub duration: f64,
+    pub uri: String,
+}
+
+/// The parts of a rendition's media playlist needed to measure its bitrate.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MediaPlaylist {
+    pub target_duration: f64,
+    /// `URI` of `#EXT-X-MAP`, when segments need an initialization section
+    pub init_uri: Option<String>,
+    pub segments: Vec<MediaSegment>,
+}
+
+pub fn parse_media_playlist(content: &str) -> Result<MediaPlaylist> {
+    let mut lines = content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty());
+    if lines.next() != Some("#EXTM3U") {
+        return Err(anyhow!("Media playlist does not start with #EXTM3U"));
+    }
+
+    let mut target_duration = None;
+    let mut init_uri = None;
+    let mut pending_duration = None;
+    let mut segments = Vec::new();
+
+    for line in lines {
+        if let Some(value) = line.strip_prefix("#EXT-X-TARGETDURATION:") {
+            target_duration = Some(
+                value
+                    .parse::<f64>()
+                    .context("Invalid #EXT-X-TARGETDURATION")?,
+            );
+        } else if let Some(value) = line.strip_prefix("#EXTINF:") {
+            let duration = value.split(',').next().unwrap_or_default();
+            pending_duration = Some(
+                duration
+                    .parse::<f64>()
+                    .context("Invalid #EXTINF duration")?,
+            );
+        } else if let Some(attributes) = line.strip_prefix("#EXT-X-MAP:") {
+            init_uri = parse_attribute_list(attributes)
+                .into_iter()
+                .find(|(name, _)| name == "URI")
+                .map(|(_, value)| value);
+        } else if !line.starts_with('#') {
+            let duration = pending_duration
+                .take()
+                .ok_or_else(|| anyhow!("Segment {} has no #EXTINF", line))?;
+            segments.push(MediaSegment {
+                duration,
+                uri: line.to_string(),
+            });
+        }
+    }
+
+    Ok(MediaPlaylist {
+        target_duration: target_duration
+            .ok_or_else(|| anyhow!("Media playlist has no #EXT-X-TARGETDURATION"))?,
+        init_uri,
+        segments,
+    })
+}
+
+/// Peak and average bitrate in bits per second, given the size in bytes of every segment.
+///
+/// The peak follows the HLS definition: the highest bitrate of any run of consecutive segments
+/// lasting between 0.5 and 1.5 times the target duration.
+pub fn measure_bitrates(playlist: &MediaPlaylist, segment_sizes: &[u64]) -> (u64, u64) {
+    let segments: Vec<(f64, u64)> = playlist
+        .segments
+        .iter()
+        .zip(segment_sizes)
+        .map(|(segment, &size)| (segment.duration, size))
+        .collect();
+
+    let total_duration: f64 = segments.iter().map(|(duration, _)| duration).sum();
+    let total_bytes: u64 = segments.iter().map(|(_, size)| size).sum();
+    if total_duration <= 0.0 {
+        return (0, 0);
+    }
+    let average = (total_bytes as f64 * 8.0 / total_duration).ceil() as u64;
+
+    let min_window = playlist.target_duration * 0.5;
+    let max_window = playlist.target_duration * 1.5;
+    let mut peak: Option<u64> = None;
+    for start in 0..segments.len() {
+        let mut duration = 0.0;
+        let mut bytes = 0;
+        for &(segment_duration, size) in &segments[start..] {
+            duration += segment_duration;
+            bytes += size;
+            if duration > max_window {
+                break;
+            }
+            if duration >= min_window {
+                let bitrate = (bytes as f64 * 8.0 / duration).ceil() as u64;
+                peak = Some(peak.map_or(bitrate, |peak| peak.max(bitrate)));
+            }
+        }
+    }
+
+    // Content shorter than half a target duration has no qualifying window
+    (peak.unwrap_or(average).max(average), average)
+}
+
+/// RFC 6381 codec string for an H.264 stream, from ffprobe's `profile` and `level`.
+pub fn avc1_codec(profile: &str, level: i64) -> Option<String> {
+    let (profile_idc, constraints) = match profile.to_lowercase().as_str() {
+        "constrained baseline" => (0x42, 0xE0),
+        "baseline" => (0x42, 0x00),
+        "main" => (0x4D, 0x40),
+        "extended" => (0x58, 0x00),
+        "high" => (0x64, 0x00),
+        "high 10" => (0x6E, 0x00),
+        "high 4:2:2" => (0x7A, 0x00),
+        "high 4:4:4 predictive" => (0xF4, 0x00),
+        _ => return None,
+    };
+    if !(1..=0xFF).contains(&level) {
+        return None;
+    }
+    Some(format!(
+        "avc1.{:02X}{:02X}{:02X}",
+        profile_idc, constraints, level
+    ))
+}
+
+/// VP9 levels as (level, max luma picture size, max luma sample rate).
+const VP9_LEVELS: [(u32, u64, u64); 14] = [
+    (10, 36_864, 829_440),
+    (11, 73_728, 2_764_800),
+    (20, 122_880, 4_608_000),
+    (21, 245_760, 9_216_000),
+    (30, 552_960, 20_736_000),
+    (31, 983_040, 36_864_000),
+    (40, 2_228_224, 83_558_400),
+    (41, 2_228_224, 160_432_128),
+    (50, 8_912_896, 311_951_360),
+    (51, 8_912_896, 588_251_136),
+    (52, 8_912_896, 1_176_502_272),
+    (60, 35_651_584, 1_176_502_272),
+    (61, 35_651_584, 2_353_004_544),
+    (62, 35_651_584, 4_706_009_088),
+];
+
+/// AV1 levels as (seq_level_idx, max picture size, max display rate).
+const AV1_LEVELS: [(u32, u64, u64); 13] = [
+    (0, 147_456, 4_423_680),
+    (1, 278_784, 8_363_520),
+    (4, 665_856, 19_975_680),
+    (5, 1_065_024, 31_950_720),
+    (8, 2_359_296, 70_778_880),
+    (9, 2_359_296, 141_557_760),
+    (12, 8_912_896, 267_386_880),
+    (13, 8_912_896, 534_773_760),
+    (14, 8_912_896, 1_069_547_520),
+    (16, 35_651_584, 1_069_547_520),
+    (17, 35_651_584, 2_139_095_040),
+    (18, 35_651_584, 4_278_190_080),
+    (19, 35_651_584, 4_278_190_080),
+];
+
+/// RFC 6381 codec string for a video stream, from ffprobe's `codec_name`, `profile` and `level`.
+/// VP9 and AV1 levels are derived from the resolution and frame rate, as ffprobe rarely reports them.
+pub fn video_codec(
+    codec_name: &str,
+    profile: Option<&str>,
+    level: i64,
+    width: i64,
+    height: i64,
+    frame_rate: Option<f64>,
+) -> Option<String> {
+    let profile = profile.unwrap_or_default();
+    match codec_name {
+        "h264" => avc1_codec(profile, level),
+        "hevc" => hevc_codec(profile, level),
+        "vp9" => {
+            let profile = match profile {
+                "Profile 0" | "" => 0,
+                "Profile 1" => 1,
+                "Profile 2" => 2,
+                "Profile 3" => 3,
+                _ => return None,
+            };
+            let level = lowest_level(&VP9_LEVELS, width, height, frame_rate)?;
+            Some(format!("vp09.{:02}.{:02}.08", profile, level))
+        }
+        "av1" => {
+            let profile = match profile {
+                "Main" | "" => 0,
+                "High" => 1,
+                "Professional" => 2,
+                _ => return None,
+            };
+            let level = lowest_level(&AV1_LEVELS, width, height, frame_rate)?;
+            Some(format!("av01.{}.{:02}M.08", profile, level))
+        }
+        _ => None,
+    }
+}
+
+/// RFC 6381 codec string for an HEVC stream, from ffprobe's `profile` and `level`.
+pub fn hevc_codec(profile: &str, level: i64) -> Option<String> {
+    let (profile_idc, compatibility) = match profile.to_lowercase().as_str() {
+        "main" => (1, 6),
+        "main 10" => (2, 4),
+        _ => return None,
+    };
+    if level <= 0 {
+        return None;
+    }
+    Some(format!(
+        "hvc1.{}.{}.L{}.B0",
+        profile_idc, compatibility, level
+    ))
+}
+
+fn lowest_level(
+    levels: &[(u32, u64, u64)],
+    width: i64,
+    height: i64,
+    frame_rate: Option<f64>,
+) -> Option<u32> {
+    if width <= 0 || height <= 0 {
+        return None;
+    }
+    let picture_size = (width * height) as u64;
+    let sample_rate = (picture_size as f64 * frame_rate.unwrap_or(30.0)).ceil() as u64;
+    levels
+        .iter()
+        .find(|(_, max_picture_size, max_sample_rate)| {
+            picture_size <= *max_picture_size && sample_rate <= *max_sample_rate
+        })
+        .map(|(level, _, _)| *level)
+}
+
+/// RFC 6381 codec string for an audio stream, from ffprobe's `codec_name` and `profile`.
+pub fn audio_codec(codec_name: &str, profile: Option<&str>) -> Option<String> {
+    match codec_name {
+        "aac" => Some(
+            match profile.unwrap_or("LC") {
+                "HE-AAC" => "mp4a.40.5",
+                "HE-AACv2" => "mp4a.40.29",
+                _ => "mp4a.40.2",
+            }
+            .to_string(),
+        ),
+        "mp3" => Some("mp4a.40.34".to_string()),
+        "ac3" => Some("ac-3".to_string()),
+        "eac3" => Some("ec-3".to_string()),
+        "opus" => Some("Opus".to_string()),
+        _ => None,
+    }
+}
+
+/// Frame rate from an ffprobe rational such as `30000/1001`.
+pub fn parse_frame_rate(rate: &str) -> Option<f64> {
+    let (numerator, denominator) = rate.split_once('/').unwrap_or((rate, "1"));
+    let numerator: f64 = numerator.trim().parse().ok()?;
+    let denominator: f64 = denominator.trim().parse().ok()?;
+    (numerator > 0.0 && denominator > 0.0).then(|| numerator / denominator)
+}
+
+/// RFC 5646 tag for an ffprobe `language` tag, which is usually ISO 639-2 such as `eng`.
+/// Two-letter codes are preferred where one exists; `und` and empty tags give `None`.
+pub fn language_tag(language: &str) -> Option<String> {
+    let language = language.trim().to_lowercase();
+    if language.is_empty() || language == "und" {
+        return None;
+    }
+    let two_letter = match language.as_str() {
+        "ara" => "ar",
+        "chi" | "zho" => "zh",
+        "cze" | "ces" => "cs",
+        "dan" => "da",
+        "dut" | "nld" => "nl",
+        "eng" => "en",
+        "fin" => "fi",
+        "fre" | "fra" => "fr",
+        "ger" | "deu" => "de",
+        "gre" | "ell" => "el",
+        "heb" => "he",
+        "hin" => "hi",
+        "hun" => "hu",
+        "ind" => "id",
+        "ita" => "it",
+        "jpn" => "ja",
+        "kor" => "ko",
+        "nor" => "no",
+        "pol" => "pl",
+        "por" => "pt",
+        "rum" | "ron" => "ro",
+        "rus" => "ru",
+        "spa" => "es",
+        "swe" => "sv",
+        "tha" => "th",
+        "tur" => "tr",
+        "ukr" => "uk",
+        "vie" => "vi",
+        other => other,
+    };
+    Some(two_letter.to_string())
+}
+
+/// One `#EXT-X-MEDIA:TYPE=AUDIO` entry of a master playlist.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AudioRendition {
+    pub group_id: String,
+    pub name: String,
+    pub language: Option<String>,
+    pub channels: u32,
+    pub default: bool,
+    pub uri: String,
+}
+
+/// One `#EXT-X-STREAM-INF` entry of a master playlist.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VariantStream {
+    pub uri: String,
+    pub bandwidth: u64,
+    pub average_bandwidth: u64,
+    pub codecs: Vec<String>,
+    pub width: i32,
+    pub height: i32,
+    pub frame_rate: Option<f64>,
+    /// `GROUP-ID` of the audio renditions played alongside this video-only variant
+    pub audio_group: Option<String>,
+}
+
+pub fn render_master_playlist(
+    audio_renditions: &[AudioRendition],
+    variants: &[VariantStream],
+) -> String {
+    let mut playlist = format!(
+        "#EXTM3U\n#EXT-X-VERSION:{}\n#EXT-X-INDEPENDENT-SEGMENTS\n",
+        MASTER_PLAYLIST_VERSION
+    );
+
+    if !audio_renditions.is_empty() {
+        playlist.push('\n');
+    }
+    for rendition in audio_renditions {
+        let mut attributes = vec![
+            "TYPE=AUDIO".to_string(),
+            format!("GROUP-ID=\"{}\"", quoted_string(&rendition.group_id)),
+            format!("NAME=\"{}\"", quoted_string(&rendition.name)),
+        ];
+        if let Some(language) = &rendition.language {
+            attributes.push(format!("LANGUAGE=\"{}\"", quoted_string(language)));
+        }
+        let default = if rendition.default { "YES" } else { "NO" };
+        attributes.push(format!("DEFAULT={}", default));
+        attributes.push("AUTOSELECT=YES".to_string());
+        attributes.push(format!("CHANNELS=\"{}\"", rendition.channels));
+        attributes.push(format!("URI=\"{}\"", quoted_string(&rendition.uri)));
+
+        playlist.push_str(&format!("#EXT-X-MEDIA:{}\n", attributes.join(",")));
+    }
+
+    for variant in variants {
+        let mut attributes = vec![
+            format!("BANDWIDTH={}", variant.bandwidth),
+            format!("AVERAGE-BANDWIDTH={}", variant.average_bandwidth),
+        ];
+        if !variant.codecs.is_empty() {
+            attributes.push(format!("CODECS=\"{}\"", variant.codecs.join(",")));
+        }
+        attributes.push(format!("RESOLUTION={}x{}", variant.width, variant.height));
+        if let Some(frame_rate) = variant.frame_rate {
+            attributes.push(format!("FRAME-RATE={:.3}", frame_rate));
+        }
+        if let Some(audio_group) = &variant.audio_group {
+            attributes.push(format!("AUDIO=\"{}\"", quoted_string(audio_group)));
+        }
+
+        playlist.push_str(&format!(
+            "\n#EXT-X-STREAM-INF:{}\n{}\n",
+            attributes.join(","),
+            variant.uri
+        ));
+    }
+
+    playlist
+}
+
+/// One `#EXT-X-MEDIA:TYPE=SUBTITLES` entry of a master playlist.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SubtitleRendition {
+    pub group_id: String,
+    pub name: String,
+    pub language: Option<String>,
+    pub uri: String,
+}
+
+/// Replace the subtitle renditions of a rendered master playlist, so tracks can be added and
+/// removed after encoding. Variants reference the new group, or no group when there are none.
+pub fn set_subtitle_renditions(master: &str, renditions: &[SubtitleRendition]) -> String {
+    let lines: Vec<&str> = master
+        .lines()
+        .filter(|line| {
+            !line
+                .strip_prefix("#EXT-X-MEDIA:")
+                .is_some_and(|attributes| attributes.contains("TYPE=SUBTITLES"))
+        })
+        .collect();
+    let first_variant = lines
+        .iter()
+        .position(|line| line.starts_with("#EXT-X-STREAM-INF:"))
+        .unwrap_or(lines.len());
+    let head = &lines[..first_variant];
+    let head_end = head
+        .iter()
+        .rposition(|line| !line.trim().is_empty())
+        .map_or(0, |index| index + 1);
+
+    let mut playlist = String::new();
+    for line in &head[..head_end] {
+        playlist.push_str(line);
+        playlist.push('\n');
+    }
+    if !renditions.is_empty() {
+        playlist.push('\n');
+    }
+    for rendition in renditions {
+        let mut attributes = vec![
+            "TYPE=SUBTITLES".to_string(),
+            format!("GROUP-ID=\"{}\"", quoted_string(&rendition.group_id)),
+            format!("NAME=\"{}\"", quoted_string(&rendition.name)),
+        ];
+        if let Some(language) = &rendition.language {
+            attributes.push(format!("LANGUAGE=\"{}\"", quoted_string(language)));
+        }
+        attributes.push("DEFAULT=NO".to_string());
+        attributes.push("AUTOSELECT=YES".to_string());
+        attributes.push(format!("URI=\"{}\"", quoted_string(&rendition.uri)));
+        playlist.push_str(&format!("#EXT-X-MEDIA:{}\n", attributes.join(",")));
+    }
+
+    let group = renditions.first().map(|rendition| &rendition.group_id);
+    for line in &lines[first_variant..] {
+        match line.strip_prefix("#EXT-X-STREAM-INF:") {
+            Some(attributes) => {
+                let mut attributes: Vec<String> = split_attribute_list(attributes)
+                    .into_iter()
+                    .filter(|attribute| !attribute.starts_with("SUBTITLES="))
+                    .map(str::to_string)
+                    .collect();
+                if let Some(group) = group {
+                    attributes.push(format!("SUBTITLES=\"{}\"", quoted_string(group)));
+                }
+                playlist.push_str(&format!("\n#EXT-X-STREAM-INF:{}\n", attributes.join(",")));
+            }
+            None if line.trim().is_empty() => {}
+            None => {
+                playlist.push_str(line);
+                playlist.push('\n');
+            }
+        }
+    }
+
+    playlist
+}
+
+/// Split an attribute list into its `NAME=value` items, leaving commas in quoted strings alone.
+fn split_attribute_list(attributes: &str) -> Vec<&str> {
+    let mut items = Vec::new();
+    let mut in_quotes = false;
+    let mut start = 0;
+    for (index, c) in attributes.char_indices() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            ',' if !in_quotes => {
+                items.push(attributes[start..index].trim());
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    items.push(attributes[start..].trim());
+    items.retain(|item| !item.is_empty());
+    items
+}
+
+/// Attribute value safe inside double quotes, which HLS quoted strings cannot escape.
+fn quoted_string(value: &str) -> String {
+    value
+        .chars()
+        .filter(|c| !matches!(c, '"' | '\r' | '\n'))
+        .collect()
+}
+
+/// Split an HLS attribute list into names and values, with quotes removed from quoted strings.
+pub fn parse_attribute_list(attributes: &str) -> Vec<(String, String)> {
+    let mut parsed = Vec::new();
+    let mut rest = attributes.trim();
+
+    while let Some((name, after_name)) = rest.split_once('=') {
+        let (value, after_value) = if let Some(quoted) = after_name.strip_prefix('"') {
+            let end = quoted.find('"').unwrap_or(quoted.len());
+            let after = quoted.get(end + 1..).unwrap_or_default();
+            (&quoted[..end], after)
+        } else {
+            let end = after_name.find(',').unwrap_or(after_name.len());
+            (&after_name[..end], &after_name[end..])
+        };
+        parsed.push((name.trim().to_string(), value.to_string()));
+        rest = after_value.trim_start_matches(',').trim();
+    }
+
+    parsed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    const MEDIA_PLAYLIST: &str = "#EXTM3U
+#EXT-X-VERSION:3
+#EXT-X-TARGETDURATION:10
+#EXT-X-MEDIA-SEQUENCE:0
+#EXT-X-PLAYLIST-TYPE:VOD
+#EXTINF:10.000000,
+720p_000.ts
+#EXTINF:10.000000,
+720p_001.ts
+#EXTINF:4.500000,
+720p_002.ts
+#EXT-X-ENDLIST
+";
+
+    fn variant(uri: &str, bandwidth: u64, average_bandwidth: u64) -> VariantStream {
+        VariantStream {
+            uri: uri.to_string(),
+            bandwidth,
+            average_bandwidth,
+            codecs: vec!["avc1.64001F".to_string(), "mp4a.40.2".to_string()],
+            width: 1280,
+            height: 720,
+            frame_rate: Some(30000.0 / 1001.0),
+            audio_group: None,
+        }
+    }
+
+    fn audio(index: usize, name: &str, language: Option<&str>, default: bool) -> AudioRendition {
+        AudioRendition {
+            group_id: "audio".to_string(),
+            name: name.to_string(),
+            language: language.map(str::to_string),
+            channels: 2,
+            default,
+            uri: format!("audio_{}.m3u8", index),
+        }
+    }
+
+    /// Variant streams of a master playlist as (attributes, uri) pairs.
+    fn parse_master(playlist: &str) -> Vec<(HashMap<String, String>, String)> {
+        let mut lines = playlist.lines().filter(|line| !line.trim().is_empty());
+        let mut variants = Vec::new();
+        while let Some(line) = lines.next() {
+            if let Some(attributes) = line.strip_prefix("#EXT-X-STREAM-INF:") {
+                let uri = lines.next().expect("STREAM-INF without URI").to_string();
+                assert!(!uri.starts_with('#'), "STREAM-INF followed by a tag");
+                variants.push((parse_attribute_list(attributes).into_iter().collect(), uri));
+            }
+        }
+        variants
+    }
+
+    #[test]
+    fn parses_media_playlist_segments() {
+        let playlist = parse_media_playlist(MEDIA_PLAYLIST).unwrap();
+
+        assert_eq!(playlist.target_duration, 10.0);
+        assert_eq!(playlist.init_uri, None);
+        assert_eq!(playlist.segments.len(), 3);
+        assert_eq!(playlist.segments[2].uri, "720p_002.ts");
+        assert_eq!(playlist.segments[2].duration, 4.5);
+    }
+
+    #[test]
+    fn parses_init_section() {
+        let content = "#EXTM3U\n#EXT-X-TARGETDURATION:6\n#EXT-X-MAP:URI=\"init.mp4\"\n#EXTINF:6.0,\nseg_0.m4s\n";
+        let playlist = parse_media_playlist(content).unwrap();
+
+        assert_eq!(playlist.init_uri.as_deref(), Some("init.mp4"));
+        assert_eq!(playlist.segments.len(), 1);
+    }
+
+    #[test]
+    fn rejects_playlist_without_header() {
+        assert!(parse_media_playlist("#EXTINF:10,\nseg.ts\n").is_err());
+    }
+
+    #[test]
+    fn measures_peak_and_average_bitrate() {
+        let playlist = parse_media_playlist(MEDIA_PLAYLIST).unwrap();
+        // 1 Mbit/s, 2 Mbit/s, then 0.5 Mbit/s
+        let sizes = [1_250_000, 2_500_000, 281_250];
+
+        let (peak, average) = measure_bitrates(&playlist, &sizes);
+
+        assert_eq!(peak, 2_000_000);
+        assert_eq!(average, 1_316_327);
+    }
+
+    #[test]
+    fn short_trailing_segment_does_not_count_as_peak_on_its_own() {
+        let playlist = MediaPlaylist {
+            target_duration: 10.0,
+            init_uri: None,
+            segments: vec![
+                MediaSegment {
+                    duration: 10.0,
+                    uri: "a.ts".to_string(),
+                },
+                MediaSegment {
+                    duration: 1.0,
+                    uri: "b.ts".to_string(),
+                },
+            ],
+        };
+        // The 1 second segment alone would be 8 Mbit/s
+        let (peak, _) = measure_bitrates(&playlist, &[1_250_000, 1_000_000]);
+
+        assert_eq!(peak, 1_636_364);
+    }
+
+    #[test]
+    fn builds_codec_strings() {
+        assert_eq!(avc1_codec("High", 31).as_deref(), Some("avc1.64001F"));
+        assert_eq!(avc1_codec("Main", 40).as_deref(), Some("avc1.4D4028"));
+        assert_eq!(
+            avc1_codec("Constrained Baseline", 30).as_deref(),
+            Some("avc1.42E01E")
+        );
+        assert_eq!(avc1_codec("Unknown", 30), None);
+        assert_eq!(
+            video_codec("hevc", Some("Main"), 120, 1920, 1080, Some(30.0)).as_deref(),
+            Some("hvc1.1.6.L120.B0")
+        );
+        assert_eq!(
+            video_codec("vp9", Some("Profile 0"), -99, 1280, 720, Some(30.0)).as_deref(),
+            Some("vp09.00.31.08")
+        );
+        assert_eq!(
+            video_codec("av1", Some("Main"), -99, 1920, 1080, Some(60.0)).as_deref(),
+            Some("av01.0.09M.08")
+        );
+        assert_eq!(audio_codec("aac", Some("LC")).as_deref(), Some("mp4a.40.2"));
+        assert_eq!(audio_codec("pcm_s16le", None), None);
+    }
+
+    #[test]
+    fn parses_frame_rates() {
+        assert_eq!(parse_frame_rate("25/1"), Some(25.0));
+        assert!((parse_frame_rate("30000/1001").unwrap() - 29.97).abs() < 0.001);
+        assert_eq!(parse_frame_rate("0/0"), None);
+    }
+
+    #[test]
+    fn renders_spec_compliant_master_playlist() {
+        let playlist = render_master_playlist(
+            &[],
+            &[
+                variant("720p.m3u8", 2_100_000, 1_500_000),
+                variant("480p.m3u8", 900_000, 700_000),
+            ],
+        );
+        let mut lines = playlist.lines();
+
+        assert_eq!(lines.next(), Some("#EXTM3U"));
+        assert_eq!(lines.next(), Some("#EXT-X-VERSION:6"));
+        assert!(playlist.contains("#EXT-X-INDEPENDENT-SEGMENTS"));
+
+        let variants = parse_master(&playlist);
+        assert_eq!(variants.len(), 2);
+
+        let (attributes, uri) = &variants[0];
+        assert_eq!(uri, "720p.m3u8");
+        assert_eq!(attributes["BANDWIDTH"], "2100000");
+        assert_eq!(attributes["AVERAGE-BANDWIDTH"], "1500000");
+        assert_eq!(attributes["CODECS"], "avc1.64001F,mp4a.40.2");
+        assert_eq!(attributes["RESOLUTION"], "1280x720");
+        assert_eq!(attributes["FRAME-RATE"], "29.970");
+        assert!(playlist.contains("CODECS=\"avc1.64001F,mp4a.40.2\""));
+
+        for (attributes, _) in &variants {
+            let bandwidth: u64 = attributes["BANDWIDTH"].parse().unwrap();
+            let average: u64 = attributes["AVERAGE-BANDWIDTH"].parse().unwrap();
+            assert!(bandwidth >= average);
+        }
+    }
+
+    #[test]
+    fn omits_unknown_codecs_and_frame_rate() {
+        let mut stream = variant("360p.m3u8", 500_000, 400_000);
+        stream.codecs.clear();
+        stream.frame_rate = None;
+
+        let variants = parse_master(&render_master_playlist(&[], &[stream]));
+        let (attributes, _) = &variants[0];
+
+        assert!(!attributes.contains_key("CODECS"));
+        assert!(!attributes.contains_key("AUDIO"));
+        assert!(!attributes.contains_key("FRAME-RATE"));
+        assert_eq!(attributes["RESOLUTION"], "1280x720");
+    }
+
+    #[test]
+    fn references_audio_group_from_variants() {
+        let mut stream = variant("720p.m3u8", 2_100_000, 1_500_000);
+        stream.audio_group = Some("audio".to_string());
+        let playlist = render_master_playlist(
+            &[
+                audio(0, "English", Some("en"), true),
+                audio(1, "Commentary \"director\"", None, false),
+            ],
+            &[stream],
+        );
+
+        let media: Vec<HashMap<String, String>> = playlist
+            .lines()
+            .filter_map(|line| line.strip_prefix("#EXT-X-MEDIA:"))
+            .map(|attributes| parse_attribute_list(attributes).into_iter().collect())
+            .collect();
+        assert_eq!(media.len(), 2);
+        assert_eq!(media[0]["TYPE"], "AUDIO");
+        assert_eq!(media[0]["GROUP-ID"], "audio");
+        assert_eq!(media[0]["LANGUAGE"], "en");
+        assert_eq!(media[0]["DEFAULT"], "YES");
+        assert_eq!(media[0]["CHANNELS"], "2");
+        assert_eq!(media[0]["URI"], "audio_0.m3u8");
+        assert_eq!(media[1]["NAME"], "Commentary director");
+        assert_eq!(media[1]["DEFAULT"], "NO");
+        assert!(!media[1].contains_key("LANGUAGE"));
+
+        let variants = parse_master(&playlist);
+        assert_eq!(variants[0].0["AUDIO"], "audio");
+    }
+
+    #[test]
+    fn maps_language_tags() {
+        assert_eq!(language_tag("eng").as_deref(), Some("en"));
+        assert_eq!(language_tag("GER").as_deref(), Some("de"));
+        assert_eq!(language_tag("fr").as_deref(), Some("fr"));
+        assert_eq!(language_tag("haw").as_deref(), Some("haw"));
+        assert_eq!(language_tag("und"), None);
+        assert_eq!(language_tag(""), None);
+    }
+
+    #[test]
+    fn replaces_subtitle_renditions() {
+        let mut stream = variant("720p.m3u8", 2_100_000, 1_500_000);
+        stream.audio_group = Some("audio".to_string());
+        let master = render_master_playlist(&[audio(0, "English", Some("en"), true)], &[stream]);
+        let subtitles = |name: &str| SubtitleRendition {
+            group_id: "subs".to_string(),
+            name: name.to_string(),
+            language: Some("de".to_string()),
+            uri: format!("../subtitles/{}/playlist.m3u8", name),
+        };
+
+        let with_subtitles = set_subtitle_renditions(&master, &[subtitles("Deutsch")]);
+        let replaced = set_subtitle_renditions(&with_subtitles, &[subtitles("Deutsch 2")]);
+
+        assert_eq!(replaced.matches("TYPE=SUBTITLES").count(), 1);
+        assert!(replaced.contains("NAME=\"Deutsch 2\""));
+        assert!(replaced.contains("TYPE=AUDIO"));
+        let variants = parse_master(&replaced);
+        assert_eq!(variants.len(), 1);
+        assert_eq!(variants[0].0["SUBTITLES"], "subs");
+        assert_eq!(variants[0].0["AUDIO"], "audio");
+        assert_eq!(variants[0].0["CODECS"], "avc1.64001F,mp4a.40.2");
+        assert_eq!(variants[0].1, "720p.m3u8");
+
+        let removed = set_subtitle_renditions(&replaced, &[]);
+        assert_eq!(removed, master);
+    }
+}
@@ -0,0 +1,217 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::models::{JobStatus, ProcessingJob};
+
+#[async_trait]
+pub trait JobQueueServiceTrait: Send + Sync {
+    /// Queue processing for a stored original. Returns `None` when the video already has a pending job.
+    async fn enqueue(
+        &self,
+        video_id: Uuid,
+        filename: &str,
+        benchmark_run_id: Option<Uuid>,
+    ) -> Result<Option<Uuid>>;
+
+    /// Atomically take the oldest due job, skipping rows other workers hold locks on.
+    async fn claim_next(&self, worker_id: &str) -> Result<Option<ProcessingJob>>;
+
+    /// Refresh the heartbeat. Returns `false` if the job is no longer held by `worker_id`.
+    async fn heartbeat(&self, job_id: &Uuid, worker_id: &str) -> Result<bool>;
+
+    async fn complete(&self, job_id: &Uuid, worker_id: &str) -> Result<()>;
+
+    /// Record a failed attempt; the job is queued again at `retry_at`, or failed for good when `None`.
+    async fn fail(
+        &self,
+        job_id: &Uuid,
+        worker_id: &str,
+        error: &str,
+        retry_at: Option<DateTime<Utc>>,
+    ) -> Result<JobStatus>;
+
+    /// Release running jobs whose heartbeat is older than `stale_after_secs`.
+    /// Jobs with attempts left are queued again; exhausted jobs are returned with their attempt count.
+    async fn recover_stale_jobs(&self, stale_after_secs: i64) -> Result<StaleJobRecovery>;
+}
+
+#[derive(Debug, Default)]
+pub struct StaleJobRecovery {
+    pub requeued: u64,
+    /// `(video_id, attempts)` of jobs that ran out of attempts
+    pub failed_jobs: Vec<(Uuid, i32)>,
+}
+
+#[derive(Clone)]
+pub struct JobQueueService {
+    pool: PgPool,
+}
+
+impl JobQueueService {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl JobQueueServiceTrait for JobQueueService {
+    async fn enqueue(
+        &self,
+        video_id: Uuid,
+        filename: &str,
+        benchmark_run_id: Option<Uuid>,
+    ) -> Result<Option<Uuid>> {
+        let job_id = sqlx::query_scalar!(
+            r#"
+                INSERT INTO processing_jobs (video_id, filename, benchmark_run_id)
+                VALUES ($1, $2, $3)
+                ON CONFLICT (video_id) WHERE status IN ('queued', 'running') DO NOTHING
+                RETURNING id
+            "#,
+            video_id,
+            filename,
+            benchmark_run_id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(job_id)
+    }
+
+    async fn claim_next(&self, worker_id: &str) -> Result<Option<ProcessingJob>> {
+        let job = sqlx::query_as!(
+            ProcessingJob,
+            r#"
+                UPDATE processing_jobs
+                SET status = 'running',
+                    attempts = attempts + 1,
+                    locked_by = $1,
+                    heartbeat_at = NOW(),
+                    started_at = NOW()
+                WHERE id = (
+                    SELECT id
+                    FROM processing_jobs
+                    WHERE status = 'queued' AND run_after <= NOW()
+                    ORDER BY run_after, created_at
+                    LIMIT 1
+                    FOR UPDATE SKIP LOCKED
+                )
+                RETURNING *
+            "#,
+            worker_id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(job)
+    }
+
+    async fn heartbeat(&self, job_id: &Uuid, worker_id: &str) -> Result<bool> {
+        let result = sqlx::query!(
+            "UPDATE processing_jobs SET heartbeat_at = NOW() WHERE id = $1 AND locked_by = $2 AND status = 'running'",
+            job_id,
+            worker_id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn complete(&self, job_id: &Uuid, worker_id: &str) -> Result<()> {
+        sqlx::query!(
+            r#"
+                UPDATE processing_jobs
+                SET status = 'completed', locked_by = NULL, last_error = NULL, finished_at = NOW()
+                WHERE id = $1 AND locked_by = $2
+            "#,
+            job_id,
+            worker_id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn fail(
+        &self,
+        job_id: &Uuid,
+        worker_id: &str,
+        error: &str,
+        retry_at: Option<DateTime<Utc>>,
+    ) -> Result<JobStatus> {
+        let status = match retry_at {
+            Some(_) => JobStatus::Queued,
+            None => JobStatus::Failed,
+        };
+
+        sqlx::query!(
+            r#"
+                UPDATE processing_jobs
+                SET status = $3::VARCHAR,
+                    run_after = COALESCE($4, run_after),
+                    locked_by = NULL,
+                    last_error = $5,
+                    finished_at = CASE WHEN $3::VARCHAR = 'failed' THEN NOW() ELSE NULL END
+                WHERE id = $1 AND locked_by = $2
+            "#,
+            job_id,
+            worker_id,
+            status.to_string(),
+            retry_at,
+            error
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(status)
+    }
+
+    async fn recover_stale_jobs(&self, stale_after_secs: i64) -> Result<StaleJobRecovery> {
+        let requeued = sqlx::query!(
+            r#"
+                UPDATE processing_jobs
+                SET status = 'queued',
+                    locked_by = NULL,
+                    run_after = NOW(),
+                    last_error = 'Worker heartbeat expired'
+                WHERE status = 'running'
+                  AND heartbeat_at < NOW() - ($1::bigint * INTERVAL '1 second')
+                  AND attempts < max_attempts
+            "#,
+            stale_after_secs
+        )
+        .execute(&self.pool)
+        .await?
+        .rows_affected();
+
+        let failed_jobs = sqlx::query!(
+            r#"
+                UPDATE processing_jobs
+                SET status = 'failed',
+                    locked_by = NULL,
+                    last_error = 'Worker heartbeat expired',
+                    finished_at = NOW()
+                WHERE status = 'running'
+                  AND heartbeat_at < NOW() - ($1::bigint * INTERVAL '1 second')
+                  AND attempts >= max_attempts
+                RETURNING video_id, attempts
+            "#,
+            stale_after_secs
+        )
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .map(|row| (row.video_id, row.attempts))
+        .collect();
+
+        Ok(StaleJobRecovery {
+            requeued,
+            failed_jobs,
+        })
+    }
+}
@@ -0,0 +1,213 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use std::env;
+use std::io::ErrorKind;
+use std::path::{Component, Path, PathBuf};
+use tokio::fs;
+use uuid::Uuid;
+
+use crate::services::CloudStorageService;
+
+/// Route prefix the API server uses to serve files stored by [`LocalFsStorageService`].
+pub const LOCAL_STORAGE_ROUTE: &str = "/storage";
+
+/// Stores objects as plain files under a root directory so the full
+/// upload → HLS pipeline can run without cloud credentials.
+#[derive(Clone)]
+pub struct LocalFsStorageService {
+    root_dir: PathBuf,
+    public_base_url: String,
+}
+
+impl LocalFsStorageService {
+    pub async fn new() -> Result<Self> {
+        log::info!("📁Initializing local filesystem storage service");
+        let root_dir = Self::root_dir_from_env();
+
+        fs::create_dir_all(&root_dir).await.with_context(|| {
+            format!(
+                "Failed to create local storage root {}",
+                root_dir.display()
+            )
+        })?;
+
+        let public_base_url = env::var("LOCAL_STORAGE_PUBLIC_URL").unwrap_or_else(|_| {
+            let port = env::var("PORT").unwrap_or_else(|_| "8080".to_string());
+            format!("http://localhost:{}{}", port, LOCAL_STORAGE_ROUTE)
+        });
+
+        log::info!("Local storage root: {}", root_dir.display());
+        log::info!("Local storage public URL: {}", public_base_url);
+
+        Ok(Self {
+            root_dir,
+            public_base_url: public_base_url.trim_end_matches('/').to_string(),
+        })
+    }
+
+    /// Root directory configured through `LOCAL_STORAGE_ROOT` (defaults to `storage`).
+    pub fn root_dir_from_env() -> PathBuf {
+        PathBuf::from(env::var("LOCAL_STORAGE_ROOT").unwrap_or_else(|_| "storage".to_string()))
+    }
+
+    /// Map a storage key onto a path below the root, rejecting keys that could escape it.
+    fn resolve(&self, remote_path: &str) -> Result<PathBuf> {
+        let relative = Path::new(remote_path.trim_start_matches('/'));
+
+        if relative
+            .components()
+            .any(|component| !matches!(component, Component::Normal(_) | Component::CurDir))
+        {
+            return Err(anyhow::anyhow!("Invalid storage path: {}", remote_path));
+        }
+
+        Ok(self.root_dir.join(relative))
+    }
+}
+
+#[async_trait]
+impl CloudStorageService for LocalFsStorageService {
+    async fn upload_file_data(&self, file_data: Vec<u8>, remote_path: &str) -> Result<String> {
+        let target_path = self.resolve(remote_path)?;
+        log::info!(
+            "Writing file data ({} bytes) to {}",
+            file_data.len(),
+            target_path.display()
+        );
+
+        if let Some(parent) = target_path.parent() {
+            fs::create_dir_all(parent)
+                .await
+                .with_context(|| format!("Failed to create directory {}", parent.display()))?;
+        }
+
+        fs::write(&target_path, file_data)
+            .await
+            .with_context(|| format!("Failed to write file {}", target_path.display()))?;
+
+        log::info!("Successfully stored {} on local filesystem", remote_path);
+        Ok(format!("file://{}", target_path.display()))
+    }
+
+    async fn upload_file(&self, local_path: &str, remote_path: &str) -> Result<String> {
+        let target_path = self.resolve(remote_path)?;
+        log::info!("Copying file {} to {}", local_path, target_path.display());
+
+        if let Some(parent) = target_path.parent() {
+            fs::create_dir_all(parent)
+                .await
+                .with_context(|| format!("Failed to create directory {}", parent.display()))?;
+        }
+
+        fs::copy(local_path, &target_path).await.with_context(|| {
+            format!("Failed to copy {} to {}", local_path, target_path.display())
+        })?;
+
+        log::info!("Successfully stored {} on local filesystem", remote_path);
+        Ok(format!("file://{}", target_path.display()))
+    }
+
+    async fn download_file(&self, remote_path: &str, local_path: &str) -> Result<()> {
+        let source_path = self.resolve(remote_path)?;
+        log::info!(
+            "Copying file from {} to {}",
+            source_path.display(),
+            local_path
+        );
+
+        fs::copy(&source_path, local_path).await.with_context(|| {
+            format!(
+                "Failed to copy {} to {}",
+                source_path.display(),
+                local_path
+            )
+        })?;
+
+        log::info!("Successfully copied {} from local storage", remote_path);
+        Ok(())
+    }
+
+    async fn file_exists(&self, remote_path: &str) -> Result<bool> {
+        let path = self.resolve(remote_path)?;
+        fs::try_exists(&path)
+            .await
+            .with_context(|| format!("Failed to look up {}", path.display()))
+    }
+
+    /// Delete the file named exactly `folder_prefix` (if any) and everything below `folder_prefix/`,
+    /// mirroring the prefix semantics of `GcsService::delete_folder`.
+    async fn delete_folder(&self, folder_prefix: &str) -> Result<()> {
+        let normalized = folder_prefix.trim_matches('/');
+
+        if normalized.is_empty() {
+            log::warn!("delete_folder called with empty prefix; skipping");
+            return Ok(());
+        }
+
+        let target_path = self.resolve(normalized)?;
+        log::info!("Deleting local storage prefix {}", target_path.display());
+
+        let metadata = match fs::metadata(&target_path).await {
+            Ok(metadata) => metadata,
+            Err(e) if e.kind() == ErrorKind::NotFound => {
+                log::debug!("Nothing stored under {}", target_path.display());
+                return Ok(());
+            }
+            Err(e) => {
+                return Err(anyhow::anyhow!(
+                    "Failed to inspect {}: {}",
+                    target_path.display(),
+                    e
+                ))
+            }
+        };
+
+        if metadata.is_dir() {
+            fs::remove_dir_all(&target_path).await
+        } else {
+            fs::remove_file(&target_path).await
+        }
+        .with_context(|| format!("Failed to delete {}", target_path.display()))?;
+
+        log::info!("Completed prefix cleanup for {}", target_path.display());
+        Ok(())
+    }
+
+    fn get_public_url(&self, remote_path: &str) -> String {
+        format!(
+            "{}/{}",
+            self.public_base_url,
+            remote_path.trim_start_matches('/')
+        )
+    }
+
+    #[allow(dead_code)]
+    fn get_signed_url(&self, remote_path: &str, _expiration_hours: u32) -> Result<String> {
+        // Files served from the local route are not access controlled
+        Ok(self.get_public_url(remote_path))
+    }
+
+    fn get_video_path(&self, video_id: &Uuid, filename: &str) -> String {
+        format!("{}/videos/{}", video_id, filename)
+    }
+
+    fn get_thumbnails_path(&self, video_id: &Uuid) -> String {
+        format!("{}/thumbnails/", video_id)
+    }
+
+    fn get_hls_path(&self, video_id: &Uuid) -> String {
+        format!("{}/hls/", video_id)
+    }
+
+    fn get_subtitles_path(&self, video_id: &Uuid) -> String {
+        format!("{}/subtitles/", video_id)
+    }
+
+    fn get_trickplay_path(&self, video_id: &Uuid) -> String {
+        format!("{}/trickplay/", video_id)
+    }
+
+    fn get_preview_path(&self, video_id: &Uuid) -> String {
+        format!("{}/preview/", video_id)
+    }
+}
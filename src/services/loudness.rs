@@ -0,0 +1,200 @@
+use anyhow::{anyhow, Context, Result};
+use serde_json::Value;
+use tokio::process::Command;
+
+use crate::models::LoudnessInfo;
+use crate::services::FfmpegError;
+
+/// Maximum true peak after normalization, below EBU R128's -1 dBTP to leave headroom for the
+/// AAC encoder.
+const LOUDNORM_TRUE_PEAK: f64 = -1.5;
+
+/// Loudness range `loudnorm` keeps; wider sources get dynamic instead of linear gain.
+const LOUDNORM_LOUDNESS_RANGE: f64 = 11.0;
+
+/// Target integrated loudness `loudnorm` accepts, in LUFS.
+pub const LOUDNESS_TARGET_RANGE: std::ops::RangeInclusive<f64> = -70.0..=-5.0;
+
+/// First `loudnorm` pass: measure the EBU R128 loudness of audio stream `stream_index` of the
+/// input against `target_lufs`, decoding only that stream.
+pub async fn measure_loudness(
+    input_path: &str,
+    stream_index: usize,
+    target_lufs: f64,
+) -> Result<LoudnessInfo> {
+    let map = format!("0:a:{}", stream_index);
+    let filter = format!(
+        "loudnorm=I={}:TP={}:LRA={}:print_format=json",
+        target_lufs, LOUDNORM_TRUE_PEAK, LOUDNORM_LOUDNESS_RANGE
+    );
+    let output = Command::new("ffmpeg")
+        .args([
+            "-hide_banner",
+            "-nostats",
+            "-i",
+            input_path,
+            "-map",
+            &map,
+            "-af",
+            &filter,
+            "-f",
+            "null",
+            "-",
+        ])
+        .output()
+        .await
+        .context("Failed to execute FFmpeg loudness measurement")?;
+
+    if !output.status.success() {
+        return Err(FfmpegError::new("FFmpeg loudness measurement error", &output.stderr).into());
+    }
+
+    parse_loudnorm_report(&String::from_utf8_lossy(&output.stderr), target_lufs)
+}
+
+/// Loudness from the JSON report `loudnorm` prints with `print_format=json`, which is the last
+/// JSON object on stderr after the usual FFmpeg logging.
+fn parse_loudnorm_report(stderr: &str, target_lufs: f64) -> Result<LoudnessInfo> {
+    let report = stderr
+        .rfind('{')
+        .and_then(|start| {
+            let end = stderr[start..].find('}')?;
+            Some(&stderr[start..=start + end])
+        })
+        .ok_or_else(|| anyhow!("FFmpeg printed no loudnorm measurement"))?;
+    let report: Value =
+        serde_json::from_str(report).context("Failed to parse loudnorm measurement")?;
+
+    // Silent streams measure as `-inf`, which the second pass cannot normalize
+    let field = |name: &str| -> Result<f64> {
+        report[name]
+            .as_str()
+            .and_then(|value| value.trim().parse::<f64>().ok())
+            .filter(|value| value.is_finite())
+            .ok_or_else(|| anyhow!("loudnorm measured no usable {}", name))
+    };
+
+    Ok(LoudnessInfo {
+        integrated_lufs: field("input_i")?,
+        true_peak_dbtp: field("input_tp")?,
+        loudness_range: field("input_lra")?,
+        threshold_lufs: field("input_thresh")?,
+        target_lufs,
+        target_offset: field("target_offset")?,
+    })
+}
+
+/// Second `loudnorm` pass: the filter normalizing a stream to its target with the gain from
+/// the measurement, linearly where the loudness range and true peak allow.
+pub fn loudnorm_filter(loudness: &LoudnessInfo) -> String {
+    format!(
+        "loudnorm=I={}:TP={}:LRA={}:measured_I={}:measured_TP={}:measured_LRA={}:measured_thresh={}:offset={}:linear=true",
+        loudness.target_lufs,
+        LOUDNORM_TRUE_PEAK,
+        LOUDNORM_LOUDNESS_RANGE,
+        loudness.integrated_lufs,
+        loudness.true_peak_dbtp,
+        loudness.loudness_range,
+        loudness.threshold_lufs,
+        loudness.target_offset
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STDERR_PREFIX: &str = "Input #0, mov,mp4,m4a,3gp,3g2,mj2, from 'input.mp4':
+  Metadata:
+    title           : {untitled}
+  Duration: 00:00:12.03, start: 0.000000, bitrate: 1524 kb/s
+Stream mapping:
+  Stream #0:1 -> #0:0 (aac (native) -> pcm_s16le (native))
+Output #0, null, to 'pipe:':
+size=N/A time=00:00:12.00 bitrate=N/A speed= 180x
+";
+
+    fn stderr(report: &str) -> String {
+        format!(
+            "{}[Parsed_loudnorm_0 @ 0x5581c3a7e2c0] \n{}\n",
+            STDERR_PREFIX, report
+        )
+    }
+
+    #[test]
+    fn parses_loudnorm_report() {
+        let stderr = stderr(
+            r#"{
+	"input_i" : "-27.61",
+	"input_tp" : "-4.47",
+	"input_lra" : "18.06",
+	"input_thresh" : "-39.20",
+	"output_i" : "-16.58",
+	"output_tp" : "-1.50",
+	"output_lra" : "14.78",
+	"output_thresh" : "-27.71",
+	"normalization_type" : "dynamic",
+	"target_offset" : "0.58"
+}"#,
+        );
+
+        assert_eq!(
+            parse_loudnorm_report(&stderr, -16.0).unwrap(),
+            LoudnessInfo {
+                integrated_lufs: -27.61,
+                true_peak_dbtp: -4.47,
+                loudness_range: 18.06,
+                threshold_lufs: -39.2,
+                target_lufs: -16.0,
+                target_offset: 0.58,
+            }
+        );
+    }
+
+    #[test]
+    fn rejects_silent_streams() {
+        let stderr = stderr(
+            r#"{
+	"input_i" : "-inf",
+	"input_tp" : "-inf",
+	"input_lra" : "0.00",
+	"input_thresh" : "-70.00",
+	"output_i" : "-inf",
+	"output_tp" : "-inf",
+	"output_lra" : "0.00",
+	"output_thresh" : "-70.00",
+	"normalization_type" : "dynamic",
+	"target_offset" : "inf"
+}"#,
+        );
+
+        let err = parse_loudnorm_report(&stderr, -16.0).unwrap_err();
+        assert!(err.to_string().contains("input_i"), "{}", err);
+    }
+
+    #[test]
+    fn rejects_logs_without_a_report() {
+        assert!(parse_loudnorm_report(STDERR_PREFIX, -16.0).is_err());
+        assert!(parse_loudnorm_report("", -16.0).is_err());
+        // Cut off before the report was complete
+        let truncated = stderr("{\n\t\"input_i\" : \"-27.61\",");
+        assert!(parse_loudnorm_report(&truncated, -16.0).is_err());
+    }
+
+    #[test]
+    fn renders_second_pass_filter() {
+        let loudness = LoudnessInfo {
+            integrated_lufs: -27.61,
+            true_peak_dbtp: -4.47,
+            loudness_range: 18.06,
+            threshold_lufs: -39.2,
+            target_lufs: -16.0,
+            target_offset: 0.58,
+        };
+
+        assert_eq!(
+            loudnorm_filter(&loudness),
+            "loudnorm=I=-16:TP=-1.5:LRA=11:measured_I=-27.61:measured_TP=-4.47:measured_LRA=18.06:measured_thresh=-39.2:offset=0.58:linear=true"
+        );
+    }
+}
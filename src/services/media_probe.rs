@@ -0,0 +1,347 @@
+use anyhow::{anyhow, Context, Result};
+use serde_json::Value;
+use tokio::process::Command;
+
+use crate::models::{
+    AudioStreamInfo, DynamicRange, MediaInfo, SubtitleStreamInfo, VideoStreamInfo,
+};
+use crate::services::{language_tag, parse_frame_rate, FfmpegError};
+
+/// Probe the container and every stream of `input_path` in a single FFprobe run.
+pub async fn probe_media(input_path: &str) -> Result<MediaInfo> {
+    let output = Command::new("ffprobe")
+        .args([
+            "-v",
+            "error",
+            "-show_format",
+            "-show_streams",
+            "-of",
+            "json",
+            input_path,
+        ])
+        .output()
+        .await
+        .context("Failed to execute FFprobe command")?;
+
+    if !output.status.success() {
+        return Err(FfmpegError::new("FFprobe error", &output.stderr).into());
+    }
+
+    let probe: Value =
+        serde_json::from_slice(&output.stdout).context("Failed to parse FFprobe output")?;
+    parse_media_info(&probe)
+}
+
+/// `MediaInfo` from the JSON of `ffprobe -show_format -show_streams -of json`.
+pub fn parse_media_info(probe: &Value) -> Result<MediaInfo> {
+    let format = &probe["format"];
+    let streams = probe["streams"].as_array().cloned().unwrap_or_default();
+
+    // Containers such as raw elementary streams only report durations per stream
+    let duration_secs = number(&format["duration"])
+        .or_else(|| {
+            streams
+                .iter()
+                .filter_map(|stream| number(&stream["duration"]))
+                .reduce(f64::max)
+        })
+        .filter(|duration| *duration > 0.0)
+        .ok_or_else(|| anyhow!("FFprobe reported no duration for the input"))?;
+
+    let mut info = MediaInfo {
+        container: string(&format["format_name"]).unwrap_or_default(),
+        duration_ms: (duration_secs * 1000.0).round() as i64,
+        bit_rate: number(&format["bit_rate"]).map(|rate| rate as u64),
+        size: number(&format["size"]).map(|size| size as u64),
+        video: Vec::new(),
+        audio: Vec::new(),
+        subtitles: Vec::new(),
+    };
+
+    for stream in &streams {
+        let codec = string(&stream["codec_name"]).unwrap_or_default();
+        let language = stream["tags"]["language"].as_str().and_then(language_tag);
+        let title = string(&stream["tags"]["title"]);
+        let default = stream["disposition"]["default"].as_i64() == Some(1);
+
+        match stream["codec_type"].as_str() {
+            // Cover art is a single picture, not something to encode
+            Some("video") if stream["disposition"]["attached_pic"].as_i64() != Some(1) => {
+                info.video.push(VideoStreamInfo {
+                    codec,
+                    profile: string(&stream["profile"]),
+                    width: stream["width"].as_u64().unwrap_or(0) as u32,
+                    height: stream["height"].as_u64().unwrap_or(0) as u32,
+                    frame_rate: stream["avg_frame_rate"]
+                        .as_str()
+                        .and_then(parse_frame_rate)
+                        .or_else(|| stream["r_frame_rate"].as_str().and_then(parse_frame_rate)),
+                    bit_rate: number(&stream["bit_rate"]).map(|rate| rate as u64),
+                    pixel_format: string(&stream["pix_fmt"]),
+                    rotation: rotation(stream),
+                    color_space: string(&stream["color_space"]),
+                    color_transfer: string(&stream["color_transfer"]),
+                    color_primaries: string(&stream["color_primaries"]),
+                    color_range: string(&stream["color_range"]),
+                    dynamic_range: dynamic_range(stream),
+                })
+            }
+            Some("audio") => info.audio.push(AudioStreamInfo {
+                codec,
+                profile: string(&stream["profile"]),
+                channels: stream["channels"].as_u64().unwrap_or(0) as u32,
+                channel_layout: string(&stream["channel_layout"]),
+                sample_rate: number(&stream["sample_rate"]).map(|rate| rate as u32),
+                bit_rate: number(&stream["bit_rate"]).map(|rate| rate as u64),
+                language,
+                title,
+                default,
+                loudness: None,
+            }),
+            Some("subtitle") => info.subtitles.push(SubtitleStreamInfo {
+                codec,
+                language,
+                title,
+                default,
+            }),
+            _ => {}
+        }
+    }
+
+    Ok(info)
+}
+
+/// Time of the first frame and of every keyframe of the first video stream, in ascending order.
+/// Times are in seconds from the start of the container, the timeline FFmpeg outputs frames on.
+/// Only packets are read, so no frame is decoded.
+pub async fn probe_keyframe_times(input_path: &str) -> Result<(f64, Vec<f64>)> {
+    let output = Command::new("ffprobe")
+        .args([
+            "-v",
+            "error",
+            "-select_streams",
+            "v:0",
+            "-show_entries",
+            "packet=pts_time,flags:format=start_time",
+            "-of",
+            "csv=p=0",
+            input_path,
+        ])
+        .output()
+        .await
+        .context("Failed to execute FFprobe command for keyframes")?;
+
+    if !output.status.success() {
+        return Err(FfmpegError::new("FFprobe error", &output.stderr).into());
+    }
+
+    // Packets are listed in decoding order as `pts_time,flags`, such as `2.002000,K__`, followed
+    // by the container start time on a line of its own
+    let listing = String::from_utf8_lossy(&output.stdout);
+    let mut start_time = None;
+    let mut first_frame: Option<f64> = None;
+    let mut keyframes = Vec::new();
+    for line in listing.lines().map(str::trim) {
+        let Some((pts, flags)) = line.split_once(',') else {
+            start_time = line.parse::<f64>().ok();
+            continue;
+        };
+        let Ok(pts) = pts.parse::<f64>() else {
+            continue;
+        };
+        first_frame = Some(first_frame.map_or(pts, |first| first.min(pts)));
+        if flags.starts_with('K') {
+            keyframes.push(pts);
+        }
+    }
+
+    let first_frame = first_frame.ok_or_else(|| anyhow!("FFprobe listed no video packets"))?;
+    let start_time = start_time.unwrap_or(first_frame);
+    let mut keyframes: Vec<f64> = keyframes.into_iter().map(|pts| pts - start_time).collect();
+    keyframes.sort_by(f64::total_cmp);
+    Ok((first_frame - start_time, keyframes))
+}
+
+/// FFprobe prints most numbers as strings, such as `"bit_rate": "128000"`.
+fn number(value: &Value) -> Option<f64> {
+    match value {
+        Value::String(text) => text.trim().parse().ok(),
+        _ => value.as_f64(),
+    }
+}
+
+/// Non-empty string values; FFprobe reports `unknown` for unset color properties.
+fn string(value: &Value) -> Option<String> {
+    value
+        .as_str()
+        .map(str::trim)
+        .filter(|text| !text.is_empty() && *text != "unknown")
+        .map(str::to_string)
+}
+
+/// Clockwise display rotation, from the display matrix or the legacy `rotate` tag. The matrix
+/// states the counter-clockwise angle, so `-90` means a turn of 90 degrees clockwise.
+fn rotation(stream: &Value) -> u32 {
+    let degrees = stream["side_data_list"]
+        .as_array()
+        .and_then(|side_data| {
+            side_data
+                .iter()
+                .find_map(|entry| number(&entry["rotation"]))
+        })
+        .map(|degrees| -degrees)
+        .or_else(|| number(&stream["tags"]["rotate"]))
+        .unwrap_or(0.0);
+    // Round to the nearest quarter turn, as players only rotate in those steps
+    (((degrees / 90.0).round() as i64 * 90).rem_euclid(360)) as u32
+}
+
+fn dynamic_range(stream: &Value) -> DynamicRange {
+    let dolby_vision = stream["side_data_list"]
+        .as_array()
+        .map(|side_data| {
+            side_data.iter().any(|entry| {
+                entry["side_data_type"]
+                    .as_str()
+                    .is_some_and(|kind| kind.starts_with("DOVI configuration"))
+            })
+        })
+        .unwrap_or(false);
+    if dolby_vision {
+        return DynamicRange::DolbyVision;
+    }
+
+    match stream["color_transfer"].as_str() {
+        Some("smpte2084") => DynamicRange::Hdr10,
+        Some("arib-std-b67") => DynamicRange::Hlg,
+        _ => DynamicRange::Sdr,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn probe(video: Value) -> Value {
+        json!({
+            "format": {
+                "format_name": "mov,mp4,m4a,3gp,3g2,mj2",
+                "duration": "12.345678",
+                "bit_rate": "4512000",
+                "size": "6963200"
+            },
+            "streams": [
+                video,
+                {
+                    "codec_type": "audio",
+                    "codec_name": "aac",
+                    "profile": "LC",
+                    "channels": 2,
+                    "channel_layout": "stereo",
+                    "sample_rate": "48000",
+                    "bit_rate": "128000",
+                    "disposition": { "default": 1 },
+                    "tags": { "language": "eng" }
+                }
+            ]
+        })
+    }
+
+    fn video_stream(extra: Value) -> Value {
+        let mut stream = json!({
+            "codec_type": "video",
+            "codec_name": "h264",
+            "profile": "High",
+            "width": 1920,
+            "height": 1080,
+            "avg_frame_rate": "30000/1001",
+            "pix_fmt": "yuv420p",
+            "color_transfer": "unknown",
+            "disposition": { "default": 1, "attached_pic": 0 }
+        });
+        stream
+            .as_object_mut()
+            .unwrap()
+            .extend(extra.as_object().unwrap().clone());
+        stream
+    }
+
+    #[test]
+    fn parses_format_and_streams() {
+        let info = parse_media_info(&probe(video_stream(json!({})))).unwrap();
+
+        assert_eq!(info.duration_ms, 12_346);
+        assert_eq!(info.duration_secs(), 12.346);
+        assert_eq!(info.bit_rate, Some(4_512_000));
+        let video = info.primary_video().unwrap();
+        assert_eq!((video.width, video.height, video.rotation), (1920, 1080, 0));
+        assert_eq!(video.display_dimensions(), (1920, 1080));
+        assert_eq!(video.color_transfer, None);
+        assert_eq!(video.dynamic_range, DynamicRange::Sdr);
+        assert!((video.frame_rate.unwrap() - 29.97).abs() < 0.01);
+        assert_eq!(info.audio[0].sample_rate, Some(48000));
+        assert!(info.audio[0].default);
+    }
+
+    #[test]
+    fn reads_rotation_from_display_matrix() {
+        // Portrait phone recording: landscape coded frames turned a quarter clockwise
+        let info = parse_media_info(&probe(video_stream(json!({
+            "side_data_list": [
+                { "side_data_type": "Display Matrix", "rotation": -90 }
+            ]
+        }))))
+        .unwrap();
+        let video = info.primary_video().unwrap();
+
+        assert_eq!(video.rotation, 90);
+        assert_eq!(video.display_dimensions(), (1080, 1920));
+
+        let info = parse_media_info(&probe(video_stream(json!({
+            "side_data_list": [
+                { "side_data_type": "Display Matrix", "rotation": 90 }
+            ]
+        }))))
+        .unwrap();
+        assert_eq!(info.primary_video().unwrap().rotation, 270);
+    }
+
+    #[test]
+    fn reads_rotation_from_legacy_tag() {
+        let info = parse_media_info(&probe(video_stream(json!({
+            "tags": { "rotate": "180" }
+        }))))
+        .unwrap();
+        let video = info.primary_video().unwrap();
+
+        assert_eq!(video.rotation, 180);
+        assert_eq!(video.display_dimensions(), (1920, 1080));
+    }
+
+    #[test]
+    fn falls_back_to_stream_durations() {
+        let mut probe = probe(video_stream(json!({ "duration": "8.500000" })));
+        probe["format"].as_object_mut().unwrap().remove("duration");
+
+        assert_eq!(parse_media_info(&probe).unwrap().duration_ms, 8_500);
+
+        probe["streams"][0]
+            .as_object_mut()
+            .unwrap()
+            .remove("duration");
+        assert!(parse_media_info(&probe).is_err());
+    }
+
+    #[test]
+    fn skips_cover_art() {
+        let info = parse_media_info(&probe(video_stream(json!({
+            "codec_name": "mjpeg",
+            "disposition": { "default": 0, "attached_pic": 1 }
+        }))))
+        .unwrap();
+
+        assert!(info.primary_video().is_none());
+        assert_eq!(info.audio.len(), 1);
+    }
+}
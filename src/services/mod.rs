@@ -1,15 +1,61 @@
 pub mod auth;
+pub mod dash_manifest;
 pub mod database;
+pub mod encoding_ladder;
 pub mod gcs;
 pub mod google_auth;
+pub mod hls_playlist;
+pub mod job_queue;
+pub mod local_storage;
+pub mod loudness;
+pub mod media_probe;
 pub mod metrics;
+pub mod preview;
+pub mod process_usage;
+pub mod processing_failure;
+pub mod processing_progress;
+pub mod processing_worker;
+pub mod s3;
+pub mod storage;
+pub mod subtitle;
+pub mod thumbnail;
+pub mod trickplay;
+pub mod upload;
+pub mod upload_validation;
 pub mod video;
+pub mod video_codec;
+pub mod video_events;
 pub mod video_processing;
+pub mod video_recovery;
+pub mod webvtt;
 
 pub use auth::*;
+pub use dash_manifest::*;
 pub use database::*;
+pub use encoding_ladder::*;
 pub use gcs::*;
 pub use google_auth::*;
+pub use hls_playlist::*;
+pub use job_queue::*;
+pub use local_storage::*;
+pub use loudness::*;
+pub use media_probe::*;
 pub use metrics::*;
+pub use preview::*;
+pub use process_usage::*;
+pub use processing_failure::*;
+pub use processing_progress::*;
+pub use processing_worker::*;
+pub use s3::*;
+pub use storage::*;
+pub use subtitle::*;
+pub use thumbnail::*;
+pub use trickplay::*;
+pub use upload::*;
+pub use upload_validation::*;
 pub use video::*;
+pub use video_codec::*;
+pub use video_events::*;
 pub use video_processing::*;
+pub use video_recovery::*;
+pub use webvtt::*;
@@ -0,0 +1,88 @@
+/// Size of the hover preview, the small thumbnail size it is shown in place of.
+const PREVIEW_WIDTH: u32 = 320;
+const PREVIEW_HEIGHT: u32 = 180;
+
+const PREVIEW_FRAME_RATE: u32 = 12;
+
+/// Clips stitched into the preview and their length, about five seconds in total.
+const PREVIEW_CLIP_COUNT: usize = 4;
+const PREVIEW_CLIP_SECONDS: f64 = 1.25;
+
+pub const PREVIEW_MP4_FILENAME: &str = "preview.mp4";
+pub const PREVIEW_WEBP_FILENAME: &str = "preview.webp";
+
+/// (start, length) of the clips the preview is stitched from, centred in equal parts of the
+/// video. A video shorter than the preview is used whole as a single clip.
+pub fn preview_clips(duration_secs: f64) -> Vec<(f64, f64)> {
+    let preview_secs = PREVIEW_CLIP_COUNT as f64 * PREVIEW_CLIP_SECONDS;
+    if duration_secs <= preview_secs {
+        return vec![(0.0, duration_secs.max(PREVIEW_CLIP_SECONDS))];
+    }
+
+    let part_secs = duration_secs / PREVIEW_CLIP_COUNT as f64;
+    (0..PREVIEW_CLIP_COUNT)
+        .map(|index| {
+            let start = part_secs * (index as f64 + 0.5) - PREVIEW_CLIP_SECONDS / 2.0;
+            (start.max(0.0), PREVIEW_CLIP_SECONDS)
+        })
+        .collect()
+}
+
+/// FFmpeg arguments encoding the clips into a silent MP4 and an animated WebP in `output_dir`.
+/// Each clip is its own input, so FFmpeg only decodes around the clips instead of the whole
+/// video.
+pub fn preview_args(input_path: &str, clips: &[(f64, f64)], output_dir: &str) -> Vec<String> {
+    let mut args = vec!["-y".to_string()];
+    for (start, length) in clips {
+        args.extend([
+            "-ss".to_string(),
+            format!("{:.3}", start),
+            "-t".to_string(),
+            format!("{:.3}", length),
+            "-i".to_string(),
+            input_path.to_string(),
+        ]);
+    }
+
+    let inputs: String = (0..clips.len())
+        .map(|index| format!("[{}:v]", index))
+        .collect();
+    let filter = format!(
+        "{inputs}concat=n={count}:v=1:a=0,fps={fps},scale={w}:{h}:force_original_aspect_ratio=decrease,pad={w}:{h}:({w}-iw)/2:({h}-ih)/2,setsar=1,split=2[mp4][webp]",
+        inputs = inputs,
+        count = clips.len(),
+        fps = PREVIEW_FRAME_RATE,
+        w = PREVIEW_WIDTH,
+        h = PREVIEW_HEIGHT,
+    );
+    args.extend(["-filter_complex".to_string(), filter]);
+
+    args.extend(
+        [
+            "-map",
+            "[mp4]",
+            "-an",
+            "-c:v",
+            "libx264",
+            "-preset",
+            "veryfast",
+            "-crf",
+            "30",
+            "-pix_fmt",
+            "yuv420p",
+            "-movflags",
+            "+faststart",
+        ]
+        .map(String::from),
+    );
+    args.push(format!("{}/{}", output_dir, PREVIEW_MP4_FILENAME));
+    args.extend(
+        [
+            "-map", "[webp]", "-an", "-c:v", "libwebp", "-loop", "0", "-quality", "60",
+        ]
+        .map(String::from),
+    );
+    args.push(format!("{}/{}", output_dir, PREVIEW_WEBP_FILENAME));
+
+    args
+}
@@ -0,0 +1,97 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use sysinfo::{Pid, ProcessRefreshKind, ProcessesToUpdate, System};
+use tokio::task::JoinHandle;
+
+const SAMPLE_INTERVAL: Duration = Duration::from_millis(500);
+
+/// CPU and memory used by a group of child processes, in the units of `video_processing_metrics`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ProcessUsage {
+    /// Average CPU over the tracked span, in percent of one core
+    pub cpu_avg: Option<f64>,
+    /// Highest combined resident memory observed, in bytes
+    pub mem_peak: Option<i64>,
+}
+
+#[derive(Default)]
+struct UsageSamples {
+    pids: Vec<Pid>,
+    cpu_time_ms: HashMap<Pid, u64>,
+    mem_peak: u64,
+}
+
+/// Samples CPU time and resident memory of the processes registered with `track`.
+/// Processes are sampled periodically, so CPU time spent after the last sample is not counted.
+#[derive(Clone)]
+pub struct ProcessUsageTracker {
+    samples: Arc<Mutex<UsageSamples>>,
+    sampler: Arc<JoinHandle<()>>,
+    started_at: Instant,
+}
+
+impl ProcessUsageTracker {
+    pub fn start() -> Self {
+        let samples = Arc::new(Mutex::new(UsageSamples::default()));
+        let sampler_samples = Arc::clone(&samples);
+
+        let sampler = tokio::spawn(async move {
+            let mut system = System::new();
+            loop {
+                tokio::time::sleep(SAMPLE_INTERVAL).await;
+                let pids = sampler_samples.lock().unwrap().pids.clone();
+                if pids.is_empty() {
+                    continue;
+                }
+
+                system.refresh_processes_specifics(
+                    ProcessesToUpdate::Some(&pids),
+                    true,
+                    ProcessRefreshKind::nothing().with_cpu().with_memory(),
+                );
+
+                let mut samples = sampler_samples.lock().unwrap();
+                let mut memory = 0;
+                for pid in &pids {
+                    if let Some(process) = system.process(*pid) {
+                        samples
+                            .cpu_time_ms
+                            .insert(*pid, process.accumulated_cpu_time());
+                        memory += process.memory();
+                    }
+                }
+                samples.mem_peak = samples.mem_peak.max(memory);
+            }
+        });
+
+        Self {
+            samples,
+            sampler: Arc::new(sampler),
+            started_at: Instant::now(),
+        }
+    }
+
+    pub fn track(&self, pid: Option<u32>) {
+        if let Some(pid) = pid {
+            self.samples.lock().unwrap().pids.push(Pid::from_u32(pid));
+        }
+    }
+
+    /// Stop sampling and summarize; `None` values mean nothing was sampled.
+    pub fn finish(&self) -> ProcessUsage {
+        self.sampler.abort();
+
+        let samples = self.samples.lock().unwrap();
+        if samples.cpu_time_ms.is_empty() {
+            return ProcessUsage::default();
+        }
+
+        let cpu_time_ms: u64 = samples.cpu_time_ms.values().sum();
+        let elapsed_ms = self.started_at.elapsed().as_millis().max(1) as f64;
+        ProcessUsage {
+            cpu_avg: Some(cpu_time_ms as f64 / elapsed_ms * 100.0),
+            mem_peak: Some(samples.mem_peak as i64),
+        }
+    }
+}
@@ -0,0 +1,214 @@
+use crate::models::{FailureCategory, ProcessingFailure};
+
+const STDERR_TAIL_LINES: usize = 20;
+
+/// Pipeline steps that only talk to the storage backend.
+const STORAGE_STEPS: [&str; 3] = [
+    "download_original_video",
+    "upload_hls_files",
+    "upload_thumbnail",
+];
+
+/// Error context naming the `record_video_processing_step` step that failed.
+/// Displays as the plain message, so error chains read the same as with a string context.
+#[derive(Debug)]
+pub struct FailedStep {
+    pub step: &'static str,
+    message: &'static str,
+}
+
+impl FailedStep {
+    pub fn new(step: &'static str, message: &'static str) -> Self {
+        Self { step, message }
+    }
+}
+
+impl std::fmt::Display for FailedStep {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+/// Non-zero exit of ffmpeg or ffprobe, keeping stderr around for classification.
+#[derive(Debug)]
+pub struct FfmpegError {
+    label: &'static str,
+    stderr: String,
+}
+
+impl FfmpegError {
+    pub fn new(label: &'static str, stderr: &[u8]) -> Self {
+        Self {
+            label,
+            stderr: String::from_utf8_lossy(stderr).into_owned(),
+        }
+    }
+
+    /// Last lines of stderr, where ffmpeg puts the error that made it give up.
+    pub fn stderr_tail(&self) -> String {
+        let lines: Vec<&str> = self.stderr.trim_end().lines().collect();
+        lines[lines.len().saturating_sub(STDERR_TAIL_LINES)..].join("\n")
+    }
+}
+
+impl std::fmt::Display for FfmpegError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self
+            .stderr
+            .lines()
+            .rev()
+            .find(|line| !line.trim().is_empty())
+        {
+            Some(line) => write!(f, "{}: {}", self.label, line.trim()),
+            None => write!(f, "{}", self.label),
+        }
+    }
+}
+
+impl std::error::Error for FfmpegError {}
+
+/// Build the failure record for an error returned by the processing pipeline.
+pub fn classify_failure(err: &anyhow::Error, attempts: i32) -> ProcessingFailure {
+    let message = format!("{:#}", err);
+    let step = err.downcast_ref::<FailedStep>().map(|failed| failed.step);
+    let ffmpeg = err.downcast_ref::<FfmpegError>();
+
+    let category = if step.is_some_and(|step| STORAGE_STEPS.contains(&step)) {
+        FailureCategory::StorageError
+    } else {
+        ffmpeg
+            .and_then(|ffmpeg| classify_ffmpeg_output(&ffmpeg.stderr))
+            .or_else(|| classify_ffmpeg_output(&message))
+            .unwrap_or(FailureCategory::Internal)
+    };
+
+    let mut failure = ProcessingFailure::new(category, message, attempts);
+    failure.step = step.map(str::to_string);
+    failure.stderr_tail = ffmpeg.map(FfmpegError::stderr_tail);
+    failure
+}
+
+/// Map well-known ffmpeg/ffprobe complaints to a category; `None` if nothing matches.
+fn classify_ffmpeg_output(output: &str) -> Option<FailureCategory> {
+    let output = output.to_lowercase();
+    let matches = |patterns: &[&str]| patterns.iter().any(|pattern| output.contains(pattern));
+
+    if matches(&[
+        "decoder (codec",
+        "unknown codec",
+        "unsupported codec",
+        "codec not currently supported",
+        "no decoder for",
+    ]) {
+        Some(FailureCategory::UnsupportedCodec)
+    } else if matches(&[
+        "does not contain any stream",
+        "matches no streams",
+        "no video stream",
+    ]) {
+        Some(FailureCategory::MissingVideoStream)
+    } else if matches(&[
+        "invalid data found when processing input",
+        "moov atom not found",
+        "could not find codec parameters",
+        "error while decoding",
+        "invalid nal unit",
+        "header missing",
+        "corrupt",
+        "truncated",
+        "end of file",
+    ]) {
+        Some(FailureCategory::CorruptInput)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ffmpeg_failure(step: &'static str, stderr: &str) -> ProcessingFailure {
+        let err = anyhow::Error::new(FfmpegError::new("FFmpeg error", stderr.as_bytes()))
+            .context(FailedStep::new(step, "Failed to generate HLS streams"));
+        classify_failure(&err, 2)
+    }
+
+    #[test]
+    fn classifies_ffmpeg_stderr() {
+        let cases = [
+            (
+                "ffmpeg version 6.1.1 Copyright (c) 2000-2023 the FFmpeg developers\n\
+                 /tmp/video_processing/0b6e/input.mp4: No such file or directory\n",
+                FailureCategory::Internal,
+            ),
+            (
+                "[mov,mp4,m4a,3gp,3g2,mj2 @ 0x55d5c8e3a040] moov atom not found\n\
+                 /tmp/video_processing/0b6e/input.mp4: Invalid data found when processing input\n",
+                FailureCategory::CorruptInput,
+            ),
+            (
+                "[matroska,webm @ 0x5616e0a4c2c0] Could not find codec parameters for stream 0 \
+                 (Video: none (V_QUICKTIME / 0x0000), none, 1920x1080): unknown codec\n\
+                 Consider increasing the value for the 'analyzeduration' and 'probesize' options\n\
+                 Decoder (codec none) not found for input stream #0:0\n",
+                FailureCategory::UnsupportedCodec,
+            ),
+            (
+                "[hls @ 0x5616e0a51e80] Opening '/tmp/video_processing/0b6e/hls/1080p_012.ts' for writing\n\
+                 av_interleaved_write_frame(): No space left on device\n\
+                 Error writing trailer of /tmp/video_processing/0b6e/hls/1080p.m3u8: No space left on device\n",
+                FailureCategory::Internal,
+            ),
+            (
+                "Output #0, hls, to '/tmp/video_processing/0b6e/hls/1080p.m3u8':\n\
+                 Stream map '0:v:0' matches no streams.\n",
+                FailureCategory::MissingVideoStream,
+            ),
+        ];
+
+        for (stderr, category) in cases {
+            let failure = ffmpeg_failure("generate_hls_streams", stderr);
+            assert_eq!(failure.category, category, "{}", stderr);
+            assert_eq!(failure.step.as_deref(), Some("generate_hls_streams"));
+            assert_eq!(failure.stderr_tail.as_deref(), Some(stderr.trim_end()));
+            assert_eq!(failure.attempts, 2);
+        }
+    }
+
+    #[test]
+    fn storage_steps_win_over_stderr() {
+        let failure = ffmpeg_failure(
+            "download_original_video",
+            "input.mp4: Invalid data found when processing input\n",
+        );
+
+        assert_eq!(failure.category, FailureCategory::StorageError);
+    }
+
+    #[test]
+    fn classifies_plain_errors_by_message() {
+        let err = anyhow::anyhow!("Failed to probe input: moov atom not found");
+        let failure = classify_failure(&err, 1);
+
+        assert_eq!(failure.category, FailureCategory::CorruptInput);
+        assert_eq!(failure.step, None);
+        assert_eq!(failure.stderr_tail, None);
+
+        let err = anyhow::anyhow!("Failed to create temp directory: Permission denied");
+        assert_eq!(
+            classify_failure(&err, 1).category,
+            FailureCategory::Internal
+        );
+    }
+
+    #[test]
+    fn keeps_last_stderr_lines() {
+        let stderr: String = (1..=30).map(|line| format!("line {}\n", line)).collect();
+        let error = FfmpegError::new("FFmpeg error", stderr.as_bytes());
+
+        assert_eq!(error.to_string(), "FFmpeg error: line 30");
+        assert_eq!(error.stderr_tail().lines().count(), STDERR_TAIL_LINES);
+        assert!(error.stderr_tail().starts_with("line 11\n"));
+    }
+}
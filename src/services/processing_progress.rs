@@ -0,0 +1,155 @@
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+use crate::services::VideoServiceTrait;
+
+/// Share of the overall percentage covered by encoding; uploads and bookkeeping make up the rest.
+pub const ENCODE_PROGRESS_SHARE: f64 = 90.0;
+/// Overall percentage once the HLS output is in storage.
+pub const UPLOADED_PROGRESS: f64 = 95.0;
+
+const WRITE_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Cheap handle the ffmpeg tasks use to report per-rendition progress.
+#[derive(Clone)]
+pub struct ProgressReporter {
+    tx: mpsc::UnboundedSender<(String, f64)>,
+}
+
+impl ProgressReporter {
+    pub fn report(&self, rendition: &str, percent: f64) {
+        // The writer only goes away once every reporter is dropped
+        let _ = self
+            .tx
+            .send((rendition.to_string(), percent.clamp(0.0, 100.0)));
+    }
+}
+
+/// Persist reported progress for `video_id`, throttled to one write every couple of seconds.
+/// The returned task finishes with the final per-rendition map once every reporter is dropped.
+pub fn spawn_progress_writer(
+    video_id: Uuid,
+    video_service: Arc<dyn VideoServiceTrait>,
+) -> (ProgressReporter, JoinHandle<serde_json::Value>) {
+    let (tx, mut rx) = mpsc::unbounded_channel::<(String, f64)>();
+
+    let handle = tokio::spawn(async move {
+        let mut renditions: BTreeMap<String, f64> = BTreeMap::new();
+        let mut last_write: Option<Instant> = None;
+        let mut dirty = false;
+
+        loop {
+            let update = rx.recv().await;
+            let closed = update.is_none();
+            if let Some((rendition, percent)) = update {
+                renditions.insert(rendition, percent);
+                dirty = true;
+            }
+
+            let due = last_write.is_none_or(|at| at.elapsed() >= WRITE_INTERVAL);
+            if dirty && (due || closed) {
+                let overall = if renditions.is_empty() {
+                    0.0
+                } else {
+                    renditions.values().sum::<f64>() / renditions.len() as f64
+                        * ENCODE_PROGRESS_SHARE
+                        / 100.0
+                };
+                if let Err(err) = video_service
+                    .update_processing_progress(&video_id, overall, json!(renditions))
+                    .await
+                {
+                    log::warn!(
+                        "Failed to record processing progress for {}: {}",
+                        video_id,
+                        err
+                    );
+                }
+                last_write = Some(Instant::now());
+                dirty = false;
+            }
+
+            if closed {
+                return json!(renditions);
+            }
+        }
+    });
+
+    (ProgressReporter { tx }, handle)
+}
+
+/// Percentage of `duration_secs` covered by one line of `ffmpeg -progress` output, if it carries a position.
+pub(crate) fn parse_ffmpeg_progress(line: &str, duration_secs: f64) -> Option<f64> {
+    let (key, value) = line.trim().split_once('=')?;
+    match key {
+        "progress" if value == "end" => Some(100.0),
+        // out_time_ms is in microseconds as well, older builds only emit that one
+        "out_time_us" | "out_time_ms" if duration_secs > 0.0 => {
+            let micros: f64 = value.parse().ok()?;
+            Some((micros / 1_000_000.0 / duration_secs * 100.0).clamp(0.0, 100.0))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_out_time_in_microseconds() {
+        assert_eq!(
+            parse_ffmpeg_progress("out_time_us=5000000", 20.0),
+            Some(25.0)
+        );
+        // Despite the name, out_time_ms is in microseconds too
+        assert_eq!(
+            parse_ffmpeg_progress("out_time_ms=5000000", 20.0),
+            Some(25.0)
+        );
+        assert_eq!(
+            parse_ffmpeg_progress(" out_time_us=15000000\n", 20.0),
+            Some(75.0)
+        );
+    }
+
+    #[test]
+    fn ignores_unavailable_values_and_other_keys() {
+        assert_eq!(parse_ffmpeg_progress("out_time_us=N/A", 20.0), None);
+        assert_eq!(parse_ffmpeg_progress("out_time_ms=N/A", 20.0), None);
+        assert_eq!(
+            parse_ffmpeg_progress("out_time=00:00:05.000000", 20.0),
+            None
+        );
+        assert_eq!(parse_ffmpeg_progress("frame=120", 20.0), None);
+        assert_eq!(parse_ffmpeg_progress("progress=continue", 20.0), None);
+        assert_eq!(parse_ffmpeg_progress("", 20.0), None);
+        // Without a known duration a position says nothing about progress
+        assert_eq!(parse_ffmpeg_progress("out_time_us=5000000", 0.0), None);
+    }
+
+    #[test]
+    fn reports_end_as_complete() {
+        assert_eq!(parse_ffmpeg_progress("progress=end", 20.0), Some(100.0));
+        assert_eq!(parse_ffmpeg_progress("progress=end", 0.0), Some(100.0));
+    }
+
+    #[test]
+    fn clamps_to_percentage_range() {
+        // Audio or a longer last frame can run past the probed duration
+        assert_eq!(
+            parse_ffmpeg_progress("out_time_us=21000000", 20.0),
+            Some(100.0)
+        );
+        // ffmpeg reports a negative position before the first frame is out
+        assert_eq!(
+            parse_ffmpeg_progress("out_time_us=-9223372036854775807", 20.0),
+            Some(0.0)
+        );
+    }
+}
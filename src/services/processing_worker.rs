@@ -0,0 +1,309 @@
+use chrono::Utc;
+use std::env;
+use std::sync::Arc;
+use std::time::Duration;
+use uuid::Uuid;
+
+use crate::models::{FailureCategory, JobStatus, ProcessingFailure, ProcessingJob};
+use crate::services::{
+    classify_failure, JobQueueServiceTrait, MetricsServiceTrait, VideoProcessingServiceTrait,
+    VideoServiceTrait,
+};
+
+const DEFAULT_CONCURRENCY: usize = 1;
+const DEFAULT_POLL_INTERVAL_SECONDS: u64 = 5;
+const DEFAULT_HEARTBEAT_INTERVAL_SECONDS: u64 = 15;
+const DEFAULT_STALE_AFTER_SECONDS: u64 = 120;
+const DEFAULT_RETRY_BASE_SECONDS: u64 = 30;
+const MAX_RETRY_DELAY_SECONDS: u64 = 60 * 60;
+
+#[derive(Debug, Clone)]
+pub struct ProcessingWorkerConfig {
+    pub concurrency: usize,
+    pub poll_interval: Duration,
+    pub heartbeat_interval: Duration,
+    pub stale_after: Duration,
+    pub retry_base: Duration,
+}
+
+impl ProcessingWorkerConfig {
+    pub fn from_env() -> Self {
+        fn env_u64(name: &str, default: u64) -> u64 {
+            env::var(name)
+                .ok()
+                .and_then(|value| value.trim().parse().ok())
+                .unwrap_or(default)
+        }
+
+        Self {
+            concurrency: env_u64("PROCESSING_WORKER_CONCURRENCY", DEFAULT_CONCURRENCY as u64).max(1)
+                as usize,
+            poll_interval: Duration::from_secs(env_u64(
+                "PROCESSING_POLL_INTERVAL_SECONDS",
+                DEFAULT_POLL_INTERVAL_SECONDS,
+            )),
+            heartbeat_interval: Duration::from_secs(env_u64(
+                "PROCESSING_HEARTBEAT_INTERVAL_SECONDS",
+                DEFAULT_HEARTBEAT_INTERVAL_SECONDS,
+            )),
+            stale_after: Duration::from_secs(env_u64(
+                "PROCESSING_JOB_STALE_SECONDS",
+                DEFAULT_STALE_AFTER_SECONDS,
+            )),
+            retry_base: Duration::from_secs(env_u64(
+                "PROCESSING_RETRY_BASE_SECONDS",
+                DEFAULT_RETRY_BASE_SECONDS,
+            )),
+        }
+    }
+
+    /// Exponential backoff for the given (1-based) attempt, capped at one hour.
+    fn retry_delay(&self, attempt: i32) -> Duration {
+        let exponent = attempt.saturating_sub(1).clamp(0, 16) as u32;
+        let delay = self.retry_base.as_secs().saturating_mul(1 << exponent);
+        Duration::from_secs(delay.min(MAX_RETRY_DELAY_SECONDS))
+    }
+}
+
+/// Polls `processing_jobs` and runs claimed jobs through the processing pipeline.
+#[derive(Clone)]
+pub struct ProcessingWorker {
+    worker_id: String,
+    config: ProcessingWorkerConfig,
+    job_queue_service: Arc<dyn JobQueueServiceTrait>,
+    video_processing_service: Arc<dyn VideoProcessingServiceTrait>,
+    video_service: Arc<dyn VideoServiceTrait>,
+    metrics_service: Arc<dyn MetricsServiceTrait>,
+}
+
+impl ProcessingWorker {
+    pub fn new(
+        config: ProcessingWorkerConfig,
+        job_queue_service: Arc<dyn JobQueueServiceTrait>,
+        video_processing_service: Arc<dyn VideoProcessingServiceTrait>,
+        video_service: Arc<dyn VideoServiceTrait>,
+        metrics_service: Arc<dyn MetricsServiceTrait>,
+    ) -> Self {
+        Self {
+            worker_id: format!("worker-{}", Uuid::new_v4()),
+            config,
+            job_queue_service,
+            video_processing_service,
+            video_service,
+            metrics_service,
+        }
+    }
+
+    /// Run the stale-job sweeper and `concurrency` job loops until the process exits.
+    pub async fn run(self) {
+        log::info!(
+            "Starting processing worker {} with {} slot(s)",
+            self.worker_id,
+            self.config.concurrency
+        );
+
+        let sweeper = self.clone();
+        let mut tasks = vec![tokio::spawn(
+            async move { sweeper.sweep_stale_jobs().await },
+        )];
+
+        for _ in 0..self.config.concurrency {
+            let worker = self.clone();
+            tasks.push(tokio::spawn(async move { worker.process_jobs().await }));
+        }
+
+        for task in tasks {
+            if let Err(err) = task.await {
+                log::error!("Processing worker task stopped: {}", err);
+            }
+        }
+    }
+
+    async fn process_jobs(self) {
+        loop {
+            match self.job_queue_service.claim_next(&self.worker_id).await {
+                Ok(Some(job)) => self.execute(job).await,
+                Ok(None) => tokio::time::sleep(self.config.poll_interval).await,
+                Err(err) => {
+                    log::error!("Failed to claim processing job: {}", err);
+                    tokio::time::sleep(self.config.poll_interval).await;
+                }
+            }
+        }
+    }
+
+    async fn execute(&self, job: ProcessingJob) {
+        log::info!(
+            "Claimed processing job {} for {} (attempt {}/{})",
+            job.id,
+            job.video_id,
+            job.attempts,
+            job.max_attempts
+        );
+
+        // Another worker owns the job once the lease is lost, so stop and leave its status alone
+        let mut heartbeat = self.spawn_heartbeat(job.id);
+        let result = tokio::select! {
+            result = self.video_processing_service.run_job(&job) => result,
+            _ = &mut heartbeat => {
+                log::warn!(
+                    "Cancelled processing job {} for {} after losing its lease",
+                    job.id,
+                    job.video_id
+                );
+                return;
+            }
+        };
+        heartbeat.abort();
+
+        let err = match result {
+            Ok(()) => {
+                if let Err(err) = self
+                    .job_queue_service
+                    .complete(&job.id, &self.worker_id)
+                    .await
+                {
+                    log::error!(
+                        "Failed to mark processing job {} completed: {}",
+                        job.id,
+                        err
+                    );
+                }
+                log::info!(
+                    "✅ Processing job {} completed for {}",
+                    job.id,
+                    job.video_id
+                );
+                return;
+            }
+            Err(err) => err,
+        };
+
+        log::error!(
+            "❌ Processing job {} failed for {}: {}",
+            job.id,
+            job.video_id,
+            err
+        );
+        log::error!("🔍 Error details: {:?}", err);
+
+        let retry_at = job.has_attempts_left().then(|| {
+            let delay = self.config.retry_delay(job.attempts);
+            Utc::now() + chrono::Duration::seconds(delay.as_secs() as i64)
+        });
+
+        let reason = format!("{:#}", err);
+        match self
+            .job_queue_service
+            .fail(&job.id, &self.worker_id, &reason, retry_at)
+            .await
+        {
+            Ok(JobStatus::Failed) => {
+                let failure = classify_failure(&err, job.attempts);
+                log::warn!(
+                    "Video {} failed in step {:?} ({})",
+                    job.video_id,
+                    failure.step,
+                    failure.category
+                );
+                self.mark_video_failed(job.video_id, job.benchmark_run_id, &failure)
+                    .await
+            }
+            Ok(status) => log::info!(
+                "Processing job {} is {} again, next attempt at {:?}",
+                job.id,
+                status,
+                retry_at
+            ),
+            Err(err) => log::error!(
+                "Failed to record failure for processing job {}: {}",
+                job.id,
+                err
+            ),
+        }
+    }
+
+    /// Keep the lease on a job alive; the task only finishes once the lease is lost.
+    fn spawn_heartbeat(&self, job_id: Uuid) -> tokio::task::JoinHandle<()> {
+        let job_queue_service = Arc::clone(&self.job_queue_service);
+        let worker_id = self.worker_id.clone();
+        let interval = self.config.heartbeat_interval;
+
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            // The claim already set the first heartbeat
+            ticker.tick().await;
+            loop {
+                ticker.tick().await;
+                match job_queue_service.heartbeat(&job_id, &worker_id).await {
+                    Ok(true) => {}
+                    Ok(false) => {
+                        log::warn!("Lost the lease on processing job {}", job_id);
+                        return;
+                    }
+                    Err(err) => {
+                        log::warn!("Failed to heartbeat processing job {}: {}", job_id, err)
+                    }
+                }
+            }
+        })
+    }
+
+    async fn sweep_stale_jobs(self) {
+        let mut interval = tokio::time::interval(self.config.stale_after);
+        loop {
+            interval.tick().await;
+            match self
+                .job_queue_service
+                .recover_stale_jobs(self.config.stale_after.as_secs() as i64)
+                .await
+            {
+                Ok(recovery) => {
+                    if recovery.requeued > 0 {
+                        log::warn!("Re-queued {} stale processing jobs", recovery.requeued);
+                    }
+                    for (video_id, attempts) in recovery.failed_jobs {
+                        let category = FailureCategory::Interrupted;
+                        let failure =
+                            ProcessingFailure::new(category, category.description(), attempts);
+                        self.mark_video_failed(video_id, None, &failure).await;
+                    }
+                }
+                Err(err) => log::warn!("Failed to recover stale processing jobs: {}", err),
+            }
+        }
+    }
+
+    async fn mark_video_failed(
+        &self,
+        video_id: Uuid,
+        benchmark_run_id: Option<Uuid>,
+        failure: &ProcessingFailure,
+    ) {
+        if let Err(err) = self
+            .metrics_service
+            .record_video_processing_step(
+                benchmark_run_id,
+                Some(video_id),
+                "processing_failed",
+                None,
+                None,
+                None,
+            )
+            .await
+        {
+            log::warn!(
+                "Failed to record processing_failed metric for {}: {}",
+                video_id,
+                err
+            );
+        }
+        if let Err(update_err) = self
+            .video_service
+            .mark_video_failed(&video_id, failure)
+            .await
+        {
+            log::error!("❌ Failed to update video status to failed: {}", update_err);
+        }
+    }
+}
@@ -0,0 +1,400 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use aws_sdk_s3::config::{Region, RequestChecksumCalculation};
+use aws_sdk_s3::error::DisplayErrorContext;
+use aws_sdk_s3::primitives::ByteStream;
+use aws_sdk_s3::Client;
+use std::env;
+use tokio::fs;
+use tokio::io::AsyncWriteExt;
+use uuid::Uuid;
+
+use crate::services::{CloudStorageService, GcsService};
+
+/// Storage backed by AWS S3 or any S3-compatible service such as MinIO.
+#[derive(Clone)]
+pub struct S3StorageService {
+    client: Client,
+    bucket_name: String,
+    public_base_url: String,
+    /// `MaxKeys` for listings; `None` keeps the S3 default of 1000.
+    list_page_size: Option<i32>,
+}
+
+impl S3StorageService {
+    pub async fn new() -> Result<Self> {
+        log::info!("📁Initializing S3 storage service");
+        let bucket_name =
+            env::var("S3_BUCKET").map_err(|_| anyhow::anyhow!("S3_BUCKET not set"))?;
+        log::info!("S3 bucket name: {}", bucket_name);
+
+        let region = env::var("S3_REGION")
+            .or_else(|_| env::var("AWS_REGION"))
+            .unwrap_or_else(|_| "us-east-1".to_string());
+        let endpoint_url = env::var("S3_ENDPOINT_URL").ok();
+        // MinIO and most self-hosted stores only support path-style addressing
+        let force_path_style = env::var("S3_FORCE_PATH_STYLE")
+            .map(|value| matches!(value.to_lowercase().as_str(), "1" | "true" | "yes"))
+            .unwrap_or(endpoint_url.is_some());
+
+        let sdk_config = aws_config::from_env()
+            .region(Region::new(region.clone()))
+            .load()
+            .await;
+
+        let mut config_builder = aws_sdk_s3::config::Builder::from(&sdk_config)
+            .force_path_style(force_path_style)
+            // Only send checksums when S3 requires them; not every S3-compatible store supports the newer ones
+            .request_checksum_calculation(RequestChecksumCalculation::WhenRequired);
+        if let Some(endpoint) = &endpoint_url {
+            log::info!("S3 endpoint: {}", endpoint);
+            config_builder = config_builder.endpoint_url(endpoint);
+        }
+
+        let client = Client::from_conf(config_builder.build());
+        log::info!("📦S3 client initialized");
+
+        let public_base_url = match (env::var("S3_PUBLIC_URL").ok(), &endpoint_url) {
+            (Some(url), _) => url,
+            (None, Some(endpoint)) if force_path_style => format!(
+                "{}/{}",
+                endpoint.trim_end_matches('/'),
+                bucket_name
+            ),
+            _ => format!("https://{}.s3.{}.amazonaws.com", bucket_name, region),
+        };
+
+        Ok(Self {
+            client,
+            bucket_name,
+            public_base_url: public_base_url.trim_end_matches('/').to_string(),
+            list_page_size: None,
+        })
+    }
+}
+
+#[async_trait]
+impl CloudStorageService for S3StorageService {
+    async fn upload_file_data(&self, file_data: Vec<u8>, remote_path: &str) -> Result<String> {
+        log::info!(
+            "Uploading file data ({} bytes) to s3://{}/{}",
+            file_data.len(),
+            self.bucket_name,
+            remote_path
+        );
+
+        self.client
+            .put_object()
+            .bucket(&self.bucket_name)
+            .key(remote_path)
+            .body(ByteStream::from(file_data))
+            .content_type(GcsService::get_content_type(remote_path))
+            .cache_control(GcsService::determine_cache_control(remote_path))
+            .send()
+            .await
+            .map_err(|e| {
+                anyhow::anyhow!(
+                    "Failed to upload file data to S3: {}",
+                    DisplayErrorContext(e)
+                )
+            })?;
+
+        log::info!("Successfully uploaded {} to S3", remote_path);
+        Ok(format!("s3://{}/{}", self.bucket_name, remote_path))
+    }
+
+    async fn upload_file(&self, local_path: &str, remote_path: &str) -> Result<String> {
+        log::info!(
+            "Uploading file {} to s3://{}/{}",
+            local_path,
+            self.bucket_name,
+            remote_path
+        );
+
+        let body = ByteStream::from_path(local_path)
+            .await
+            .with_context(|| format!("Failed to open {}", local_path))?;
+
+        self.client
+            .put_object()
+            .bucket(&self.bucket_name)
+            .key(remote_path)
+            .body(body)
+            .content_type(GcsService::get_content_type(remote_path))
+            .cache_control(GcsService::determine_cache_control(remote_path))
+            .send()
+            .await
+            .map_err(|e| {
+                anyhow::anyhow!("Failed to upload file to S3: {}", DisplayErrorContext(e))
+            })?;
+
+        log::info!("Successfully uploaded {} to S3", remote_path);
+        Ok(format!("s3://{}/{}", self.bucket_name, remote_path))
+    }
+
+    async fn download_file(&self, remote_path: &str, local_path: &str) -> Result<()> {
+        log::info!(
+            "Downloading file from s3://{}/{} to {}",
+            self.bucket_name,
+            remote_path,
+            local_path
+        );
+
+        let mut object = self
+            .client
+            .get_object()
+            .bucket(&self.bucket_name)
+            .key(remote_path)
+            .send()
+            .await
+            .map_err(|e| {
+                anyhow::anyhow!(
+                    "Failed to download file from S3: {}",
+                    DisplayErrorContext(e)
+                )
+            })?;
+
+        let mut file = fs::File::create(local_path)
+            .await
+            .with_context(|| format!("Failed to create {}", local_path))?;
+
+        while let Some(chunk) = object
+            .body
+            .try_next()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to read chunk from stream: {}", e))?
+        {
+            file.write_all(&chunk)
+                .await
+                .with_context(|| format!("Failed to write file to {}", local_path))?;
+        }
+        file.flush()
+            .await
+            .with_context(|| format!("Failed to flush {}", local_path))?;
+
+        log::info!("Successfully downloaded {} from S3", remote_path);
+        Ok(())
+    }
+
+    async fn file_exists(&self, remote_path: &str) -> Result<bool> {
+        match self
+            .client
+            .head_object()
+            .bucket(&self.bucket_name)
+            .key(remote_path)
+            .send()
+            .await
+        {
+            Ok(_) => Ok(true),
+            Err(e) if e.as_service_error().is_some_and(|e| e.is_not_found()) => Ok(false),
+            Err(e) => Err(anyhow::anyhow!(
+                "Failed to look up {} in S3: {}",
+                remote_path,
+                DisplayErrorContext(e)
+            )),
+        }
+    }
+
+    /// Delete every object whose key starts with the provided `folder_prefix`.
+    ///
+    /// Same semantics as `GcsService::delete_folder`: remove an object named exactly like
+    /// the prefix, then page through `ListObjectsV2` results for `prefix/` and delete them.
+    async fn delete_folder(&self, folder_prefix: &str) -> Result<()> {
+        let normalized = folder_prefix.trim_matches('/');
+
+        if normalized.is_empty() {
+            log::warn!("delete_folder called with empty prefix; skipping");
+            return Ok(());
+        }
+
+        let prefix = format!("{}/", normalized);
+
+        log::info!(
+            "Deleting all objects with prefix s3://{}/{}",
+            self.bucket_name,
+            prefix
+        );
+
+        // S3 treats deleting a missing key as success, so this only fails on real errors
+        if let Err(e) = self
+            .client
+            .delete_object()
+            .bucket(&self.bucket_name)
+            .key(normalized)
+            .send()
+            .await
+        {
+            log::debug!(
+                "Failed to delete direct object {}: {}",
+                normalized,
+                DisplayErrorContext(e)
+            );
+        }
+
+        let mut continuation_token: Option<String> = None;
+
+        loop {
+            let response = self
+                .client
+                .list_objects_v2()
+                .bucket(&self.bucket_name)
+                .prefix(&prefix)
+                .set_max_keys(self.list_page_size)
+                .set_continuation_token(continuation_token.take())
+                .send()
+                .await
+                .map_err(|e| {
+                    anyhow::anyhow!(
+                        "Failed to list objects for prefix {} in bucket {}: {}",
+                        prefix,
+                        self.bucket_name,
+                        DisplayErrorContext(e)
+                    )
+                })?;
+
+            for object_key in response.contents().iter().filter_map(|object| object.key()) {
+                self.client
+                    .delete_object()
+                    .bucket(&self.bucket_name)
+                    .key(object_key)
+                    .send()
+                    .await
+                    .map_err(|e| {
+                        anyhow::anyhow!(
+                            "Failed to delete object {} during prefix cleanup: {}",
+                            object_key,
+                            DisplayErrorContext(e)
+                        )
+                    })?;
+
+                log::info!("Deleted object {} during prefix cleanup", object_key);
+            }
+
+            match response.next_continuation_token() {
+                Some(token) if response.is_truncated().unwrap_or(false) => {
+                    continuation_token = Some(token.to_string());
+                }
+                _ => break,
+            }
+        }
+
+        log::info!(
+            "Completed prefix cleanup for s3://{}/{}",
+            self.bucket_name,
+            prefix
+        );
+
+        Ok(())
+    }
+
+    fn get_public_url(&self, remote_path: &str) -> String {
+        format!("{}/{}", self.public_base_url, remote_path)
+    }
+
+    #[allow(dead_code)]
+    fn get_signed_url(&self, remote_path: &str, _expiration_hours: u32) -> Result<String> {
+        // TODO: Implement presigned URLs (the SDK presigner is async, this trait method is not)
+        Ok(self.get_public_url(remote_path))
+    }
+
+    fn get_video_path(&self, video_id: &Uuid, filename: &str) -> String {
+        format!("{}/videos/{}", video_id, filename)
+    }
+
+    fn get_thumbnails_path(&self, video_id: &Uuid) -> String {
+        format!("{}/thumbnails/", video_id)
+    }
+
+    fn get_hls_path(&self, video_id: &Uuid) -> String {
+        format!("{}/hls/", video_id)
+    }
+
+    fn get_subtitles_path(&self, video_id: &Uuid) -> String {
+        format!("{}/subtitles/", video_id)
+    }
+
+    fn get_trickplay_path(&self, video_id: &Uuid) -> String {
+        format!("{}/trickplay/", video_id)
+    }
+
+    fn get_preview_path(&self, video_id: &Uuid) -> String {
+        format!("{}/preview/", video_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Runs against a real store, e.g. MinIO:
+    /// `S3_ENDPOINT_URL=http://localhost:9000 S3_BUCKET=video-stream-test AWS_ACCESS_KEY_ID=...
+    /// AWS_SECRET_ACCESS_KEY=... cargo test s3 -- --ignored`
+    #[tokio::test]
+    #[ignore = "needs an S3-compatible endpoint in S3_ENDPOINT_URL"]
+    async fn stores_and_deletes_objects() {
+        env::var("S3_ENDPOINT_URL").expect("S3_ENDPOINT_URL must be set");
+        let mut storage = S3StorageService::new().await.unwrap();
+        // Tiny pages so delete_folder has to follow continuation tokens
+        storage.list_page_size = Some(2);
+
+        if let Err(e) = storage
+            .client
+            .create_bucket()
+            .bucket(&storage.bucket_name)
+            .send()
+            .await
+        {
+            let exists = e.as_service_error().is_some_and(|e| {
+                e.is_bucket_already_owned_by_you() || e.is_bucket_already_exists()
+            });
+            assert!(exists, "{}", DisplayErrorContext(e));
+        }
+
+        let prefix = format!("s3-test-{}", Uuid::new_v4());
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("source.mp4");
+        let data: Vec<u8> = (0..=255u8).cycle().take(256 * 1024).collect();
+        fs::write(&source, &data).await.unwrap();
+
+        let video_path = format!("{}/videos/source.mp4", prefix);
+        assert!(!storage.file_exists(&video_path).await.unwrap());
+        storage
+            .upload_file(source.to_str().unwrap(), &video_path)
+            .await
+            .unwrap();
+        assert!(storage.file_exists(&video_path).await.unwrap());
+
+        let downloaded = dir.path().join("downloaded.mp4");
+        storage
+            .download_file(&video_path, downloaded.to_str().unwrap())
+            .await
+            .unwrap();
+        assert_eq!(fs::read(&downloaded).await.unwrap(), data);
+
+        let segment_paths: Vec<String> = (0..7)
+            .map(|i| format!("{}/hls/720p/segment_{:03}.ts", prefix, i))
+            .collect();
+        for (i, path) in segment_paths.iter().enumerate() {
+            storage
+                .upload_file_data(vec![i as u8; 16], path)
+                .await
+                .unwrap();
+        }
+
+        storage.delete_folder(&prefix).await.unwrap();
+
+        assert!(!storage.file_exists(&video_path).await.unwrap());
+        for path in &segment_paths {
+            assert!(!storage.file_exists(path).await.unwrap());
+        }
+        let remaining = storage
+            .client
+            .list_objects_v2()
+            .bucket(&storage.bucket_name)
+            .prefix(format!("{}/", prefix))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(remaining.key_count(), Some(0));
+    }
+}
@@ -0,0 +1,57 @@
+use anyhow::Result;
+use std::env;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use crate::services::{CloudStorageService, GcsService, LocalFsStorageService, S3StorageService};
+
+/// Storage implementation selected at startup through `STORAGE_BACKEND`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StorageBackend {
+    Gcs,
+    S3,
+    Local,
+}
+
+impl StorageBackend {
+    /// Read `STORAGE_BACKEND`, defaulting to GCS when unset.
+    pub fn from_env() -> Result<Self> {
+        match env::var("STORAGE_BACKEND") {
+            Ok(value) => value.parse().map_err(|e: String| anyhow::anyhow!(e)),
+            Err(_) => Ok(StorageBackend::Gcs),
+        }
+    }
+
+    pub async fn build(self) -> Result<Arc<dyn CloudStorageService>> {
+        log::info!("Using {} storage backend", self);
+        let storage_service: Arc<dyn CloudStorageService> = match self {
+            StorageBackend::Gcs => Arc::new(GcsService::new().await?),
+            StorageBackend::S3 => Arc::new(S3StorageService::new().await?),
+            StorageBackend::Local => Arc::new(LocalFsStorageService::new().await?),
+        };
+        Ok(storage_service)
+    }
+}
+
+impl FromStr for StorageBackend {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "gcs" => Ok(StorageBackend::Gcs),
+            "s3" | "minio" => Ok(StorageBackend::S3),
+            "local" => Ok(StorageBackend::Local),
+            _ => Err(format!("Invalid storage backend: {}", s)),
+        }
+    }
+}
+
+impl std::fmt::Display for StorageBackend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StorageBackend::Gcs => write!(f, "gcs"),
+            StorageBackend::S3 => write!(f, "s3"),
+            StorageBackend::Local => write!(f, "local"),
+        }
+    }
+}
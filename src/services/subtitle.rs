@@ -0,0 +1,281 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use sqlx::PgPool;
+use std::sync::Arc;
+use tempfile::NamedTempFile;
+use uuid::Uuid;
+
+use crate::models::{
+    SubtitleSource, SubtitleTrack, SUBTITLE_PLAYLIST_FILENAME, SUBTITLE_WEBVTT_FILENAME,
+};
+use crate::services::{
+    render_subtitle_playlist, render_webvtt, segment_webvtt, set_subtitle_renditions,
+    CloudStorageService, Cue, HlsSegmentFormat, SubtitleRendition,
+};
+
+/// Length of subtitle segments, the same as the video segments.
+const SUBTITLE_SEGMENT_SECONDS: f64 = 10.0;
+
+/// `GROUP-ID` of the subtitle renditions in the master playlist.
+const SUBTITLE_GROUP_ID: &str = "subs";
+
+/// A parsed subtitle track ready to be stored.
+#[derive(Debug, Clone)]
+pub struct NewSubtitleTrack {
+    pub language: Option<String>,
+    pub name: String,
+    pub source: SubtitleSource,
+    pub cues: Vec<Cue>,
+}
+
+#[async_trait]
+pub trait SubtitleServiceTrait: Send + Sync {
+    async fn list_subtitle_tracks(&self, video_id: &Uuid) -> Result<Vec<SubtitleTrack>>;
+
+    /// Store the track as a WebVTT file plus HLS segments and playlist. An upload replaces an
+    /// earlier upload in the same language.
+    async fn add_subtitle_track(
+        &self,
+        video_id: &Uuid,
+        track: NewSubtitleTrack,
+        segment_format: HlsSegmentFormat,
+        duration_secs: f64,
+    ) -> Result<SubtitleTrack>;
+
+    async fn delete_subtitle_track(&self, video_id: &Uuid, track_id: &Uuid) -> Result<bool>;
+
+    /// Remove the tracks an earlier processing run extracted from the source.
+    async fn delete_embedded_subtitle_tracks(&self, video_id: &Uuid) -> Result<()>;
+
+    /// Rewrite the subtitle group of the stored master playlist to match the video's tracks.
+    async fn update_master_playlist(
+        &self,
+        video_id: &Uuid,
+        master_playlist_path: &str,
+    ) -> Result<()>;
+}
+
+pub struct SubtitleService {
+    pool: PgPool,
+    storage_service: Arc<dyn CloudStorageService>,
+}
+
+impl SubtitleService {
+    pub fn new(pool: PgPool, storage_service: Arc<dyn CloudStorageService>) -> Self {
+        Self {
+            pool,
+            storage_service,
+        }
+    }
+
+    async fn delete_track_files(&self, track: &SubtitleTrack) {
+        if let Err(err) = self
+            .storage_service
+            .delete_folder(&track.storage_path)
+            .await
+        {
+            log::warn!(
+                "Failed to delete subtitle files of track {}: {}",
+                track.id,
+                err
+            );
+        }
+    }
+}
+
+/// Master playlist entries for the tracks, with names made unique as HLS requires.
+pub fn subtitle_renditions(tracks: &[SubtitleTrack]) -> Vec<SubtitleRendition> {
+    let mut renditions: Vec<SubtitleRendition> = Vec::with_capacity(tracks.len());
+    for (index, track) in tracks.iter().enumerate() {
+        let mut name = track.name.clone();
+        if renditions.iter().any(|rendition| rendition.name == name) {
+            name = format!("{} {}", name, index + 1);
+        }
+        renditions.push(SubtitleRendition {
+            group_id: SUBTITLE_GROUP_ID.to_string(),
+            name,
+            language: track.language.clone(),
+            // Subtitle folders sit next to the `hls/` folder holding the master playlist
+            uri: format!("../subtitles/{}/{}", track.id, SUBTITLE_PLAYLIST_FILENAME),
+        });
+    }
+    renditions
+}
+
+#[async_trait]
+impl SubtitleServiceTrait for SubtitleService {
+    async fn list_subtitle_tracks(&self, video_id: &Uuid) -> Result<Vec<SubtitleTrack>> {
+        let tracks = sqlx::query_as!(
+            SubtitleTrack,
+            "SELECT * FROM video_subtitles WHERE video_id = $1 ORDER BY created_at, name",
+            video_id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(tracks)
+    }
+
+    async fn add_subtitle_track(
+        &self,
+        video_id: &Uuid,
+        track: NewSubtitleTrack,
+        segment_format: HlsSegmentFormat,
+        duration_secs: f64,
+    ) -> Result<SubtitleTrack> {
+        if track.source == SubtitleSource::Upload {
+            let replaced = sqlx::query_as!(
+                SubtitleTrack,
+                "DELETE FROM video_subtitles WHERE video_id = $1 AND language = $2 AND source = $3 RETURNING *",
+                video_id,
+                track.language,
+                SubtitleSource::Upload.to_string()
+            )
+            .fetch_optional(&self.pool)
+            .await?;
+            if let Some(replaced) = replaced {
+                log::info!("Replacing subtitle track {}", replaced.id);
+                self.delete_track_files(&replaced).await;
+            }
+        }
+
+        let track_id = Uuid::new_v4();
+        let storage_path = format!(
+            "{}{}/",
+            self.storage_service.get_subtitles_path(video_id),
+            track_id
+        );
+
+        self.storage_service
+            .upload_file_data(
+                render_webvtt(&track.cues, None).into_bytes(),
+                &format!("{}{}", storage_path, SUBTITLE_WEBVTT_FILENAME),
+            )
+            .await
+            .context("Failed to upload WebVTT file")?;
+
+        let segments = segment_webvtt(
+            &track.cues,
+            duration_secs,
+            SUBTITLE_SEGMENT_SECONDS,
+            segment_format.subtitle_mpegts_offset(),
+        );
+        let mut playlist_segments = Vec::with_capacity(segments.len());
+        for (index, (duration, webvtt)) in segments.into_iter().enumerate() {
+            let segment_name = format!("segment_{:03}.vtt", index);
+            self.storage_service
+                .upload_file_data(
+                    webvtt.into_bytes(),
+                    &format!("{}{}", storage_path, segment_name),
+                )
+                .await
+                .with_context(|| format!("Failed to upload subtitle segment {}", segment_name))?;
+            playlist_segments.push((duration, segment_name));
+        }
+
+        self.storage_service
+            .upload_file_data(
+                render_subtitle_playlist(&playlist_segments).into_bytes(),
+                &format!("{}{}", storage_path, SUBTITLE_PLAYLIST_FILENAME),
+            )
+            .await
+            .context("Failed to upload subtitle playlist")?;
+
+        let stored = sqlx::query_as!(
+            SubtitleTrack,
+            r#"
+            INSERT INTO video_subtitles (id, video_id, language, name, source, storage_path)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            RETURNING *
+            "#,
+            track_id,
+            video_id,
+            track.language,
+            track.name,
+            track.source.to_string(),
+            storage_path
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        log::info!(
+            "Stored {} subtitle track {} ({:?}) for video {} with {} segments",
+            stored.source,
+            stored.id,
+            stored.language,
+            video_id,
+            playlist_segments.len()
+        );
+        Ok(stored)
+    }
+
+    async fn delete_subtitle_track(&self, video_id: &Uuid, track_id: &Uuid) -> Result<bool> {
+        let deleted = sqlx::query_as!(
+            SubtitleTrack,
+            "DELETE FROM video_subtitles WHERE id = $1 AND video_id = $2 RETURNING *",
+            track_id,
+            video_id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        match deleted {
+            Some(track) => {
+                self.delete_track_files(&track).await;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    async fn delete_embedded_subtitle_tracks(&self, video_id: &Uuid) -> Result<()> {
+        let deleted = sqlx::query_as!(
+            SubtitleTrack,
+            "DELETE FROM video_subtitles WHERE video_id = $1 AND source = $2 RETURNING *",
+            video_id,
+            SubtitleSource::Embedded.to_string()
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        for track in &deleted {
+            self.delete_track_files(track).await;
+        }
+        Ok(())
+    }
+
+    async fn update_master_playlist(
+        &self,
+        video_id: &Uuid,
+        master_playlist_path: &str,
+    ) -> Result<()> {
+        let tracks = self.list_subtitle_tracks(video_id).await?;
+
+        let local_master = NamedTempFile::new()
+            .context("Failed to create temporary master playlist")?
+            .into_temp_path();
+        let local_master_path = local_master.to_string_lossy().to_string();
+        self.storage_service
+            .download_file(master_playlist_path, &local_master_path)
+            .await
+            .context("Failed to download master playlist")?;
+        let master = tokio::fs::read_to_string(&local_master_path)
+            .await
+            .context("Failed to read master playlist")?;
+
+        self.storage_service
+            .upload_file_data(
+                set_subtitle_renditions(&master, &subtitle_renditions(&tracks)).into_bytes(),
+                master_playlist_path,
+            )
+            .await
+            .context("Failed to upload master playlist")?;
+
+        log::info!(
+            "Master playlist of video {} now lists {} subtitle tracks",
+            video_id,
+            tracks.len()
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,215 @@
+use anyhow::{anyhow, Context, Result};
+use tokio::fs;
+use tokio::process::Command;
+
+use crate::models::{ThumbnailCandidate, ThumbnailFormat, ThumbnailSize, ThumbnailVariant};
+use crate::services::{CloudStorageService, FfmpegError};
+
+const DEFAULT_THUMBNAIL_CANDIDATE_COUNT: usize = 5;
+
+/// Frames the `thumbnail` filter compares when picking a candidate, about two seconds of video.
+const THUMBNAIL_CANDIDATE_FRAMES: u32 = 50;
+
+/// Candidates are kept at the largest thumbnail size, so any of them can be selected later.
+const THUMBNAIL_CANDIDATE_SIZE: ThumbnailSize = ThumbnailSize::Large;
+
+/// Subfolder of the thumbnails folder holding the candidates.
+pub const THUMBNAIL_CANDIDATES_FOLDER: &str = "candidates/";
+
+/// Number of candidate frames to pick, configurable through `THUMBNAIL_CANDIDATES`
+/// (defaults to 5).
+pub fn thumbnail_candidate_count() -> usize {
+    std::env::var("THUMBNAIL_CANDIDATES")
+        .ok()
+        .and_then(|value| value.trim().parse().ok())
+        .filter(|count| *count > 0)
+        .unwrap_or(DEFAULT_THUMBNAIL_CANDIDATE_COUNT)
+}
+
+fn thumbnail_filter(size: ThumbnailSize) -> String {
+    let (width, height) = size.dimensions();
+    format!(
+        "scale={w}:{h}:force_original_aspect_ratio=decrease,pad={w}:{h}:({w}-iw)/2:({h}-ih)/2,setsar=1",
+        w = width,
+        h = height
+    )
+}
+
+/// Pick `count` candidate frames spread evenly over the video into `output_dir`, returned as
+/// (time in seconds, file name). Each is the most representative frame of a short window, as
+/// chosen by FFmpeg's `thumbnail` filter, which passes over black frames and fades.
+pub async fn generate_thumbnail_candidates(
+    input_path: &str,
+    output_dir: &str,
+    duration_secs: f64,
+    count: usize,
+) -> Result<Vec<(f64, String)>> {
+    fs::create_dir_all(output_dir)
+        .await
+        .context("Failed to create thumbnail candidate directory")?;
+
+    let filter = format!(
+        "thumbnail=n={},{}",
+        THUMBNAIL_CANDIDATE_FRAMES,
+        thumbnail_filter(THUMBNAIL_CANDIDATE_SIZE)
+    );
+    let mut candidates = Vec::with_capacity(count);
+    let mut last_error = None;
+    for index in 0..count {
+        // Windows are centred in equal parts of the video, skipping the very start and end
+        let time_secs = duration_secs * (index as f64 + 0.5) / count as f64;
+        let filename = format!("candidate_{}.jpg", index);
+        let output_path = format!("{}/{}", output_dir, filename);
+        let output = Command::new("ffmpeg")
+            .args([
+                "-ss",
+                &format!("{:.3}", time_secs),
+                "-i",
+                input_path,
+                "-vf",
+                &filter,
+                "-frames:v",
+                "1",
+                "-q:v",
+                "2",
+                "-y",
+                &output_path,
+            ])
+            .output()
+            .await
+            .context("Failed to execute FFmpeg thumbnail command")?;
+
+        if !output.status.success() {
+            last_error = Some(FfmpegError::new("FFmpeg thumbnail error", &output.stderr));
+            continue;
+        }
+        // A window past the last decodable frame yields no image
+        if !fs::try_exists(&output_path).await.unwrap_or(false) {
+            continue;
+        }
+        candidates.push((time_secs, filename));
+    }
+
+    if candidates.is_empty() {
+        return Err(match last_error {
+            Some(err) => err.into(),
+            None => anyhow!("FFmpeg produced no thumbnail candidates"),
+        });
+    }
+
+    log::info!(
+        "Generated {} thumbnail candidates in {}",
+        candidates.len(),
+        output_dir
+    );
+    Ok(candidates)
+}
+
+/// Position in `candidates` of the default thumbnail: the largest JPEG, as detailed frames
+/// compress worst while dark and flat ones compress best.
+pub async fn pick_thumbnail_candidate(output_dir: &str, candidates: &[(f64, String)]) -> usize {
+    let mut best = (0, 0);
+    for (position, (_, filename)) in candidates.iter().enumerate() {
+        let size = fs::metadata(format!("{}/{}", output_dir, filename))
+            .await
+            .map(|metadata| metadata.len())
+            .unwrap_or(0);
+        if size > best.1 {
+            best = (position, size);
+        }
+    }
+    best.0
+}
+
+/// Render `source_path` in every size and format into `output_dir` and upload the files to
+/// `storage_dir`, replacing the previous thumbnail.
+pub async fn store_thumbnail_variants(
+    storage_service: &dyn CloudStorageService,
+    source_path: &str,
+    output_dir: &str,
+    storage_dir: &str,
+) -> Result<Vec<ThumbnailVariant>> {
+    fs::create_dir_all(output_dir)
+        .await
+        .context("Failed to create thumbnail directory")?;
+
+    let mut variants = Vec::new();
+    for size in ThumbnailSize::ALL {
+        for format in ThumbnailFormat::ALL {
+            let filename = format!("{}.{}", size, format.extension());
+            let output_path = format!("{}/{}", output_dir, filename);
+            let filter = thumbnail_filter(size);
+            let mut args = vec!["-i", source_path, "-vf", &filter, "-frames:v", "1"];
+            match format {
+                ThumbnailFormat::Jpeg => args.extend(["-q:v", "2"]),
+                ThumbnailFormat::Webp => args.extend(["-c:v", "libwebp", "-quality", "80"]),
+            }
+            args.extend(["-y", &output_path]);
+
+            let output = Command::new("ffmpeg")
+                .args(&args)
+                .output()
+                .await
+                .context("Failed to execute FFmpeg thumbnail command")?;
+            if !output.status.success() {
+                return Err(FfmpegError::new("FFmpeg thumbnail error", &output.stderr).into());
+            }
+
+            let storage_path = format!("{}{}", storage_dir, filename);
+            storage_service
+                .upload_file(&output_path, &storage_path)
+                .await
+                .with_context(|| format!("Failed to upload {} to storage", filename))?;
+
+            let (width, height) = size.dimensions();
+            variants.push(ThumbnailVariant {
+                size,
+                format,
+                width,
+                height,
+                path: storage_path,
+            });
+        }
+    }
+
+    log::info!(
+        "Stored {} thumbnail variants in {}",
+        variants.len(),
+        storage_dir
+    );
+    Ok(variants)
+}
+
+/// Upload the candidates generated in `output_dir` under `storage_dir`.
+pub async fn store_thumbnail_candidates(
+    storage_service: &dyn CloudStorageService,
+    candidates: &[(f64, String)],
+    output_dir: &str,
+    storage_dir: &str,
+) -> Result<Vec<ThumbnailCandidate>> {
+    let mut stored = Vec::with_capacity(candidates.len());
+    for (index, (time_secs, filename)) in candidates.iter().enumerate() {
+        let storage_path = format!("{}{}{}", storage_dir, THUMBNAIL_CANDIDATES_FOLDER, filename);
+        storage_service
+            .upload_file(&format!("{}/{}", output_dir, filename), &storage_path)
+            .await
+            .with_context(|| format!("Failed to upload {} to storage", filename))?;
+        stored.push(ThumbnailCandidate {
+            index,
+            time_secs: *time_secs,
+            path: storage_path,
+        });
+    }
+    Ok(stored)
+}
+
+/// Path kept in `videos.thumbnail_path`: the small JPEG, the size the single thumbnail used
+/// to have.
+pub fn default_thumbnail_path(variants: &[ThumbnailVariant]) -> Option<String> {
+    variants
+        .iter()
+        .find(|variant| {
+            variant.size == ThumbnailSize::Small && variant.format == ThumbnailFormat::Jpeg
+        })
+        .map(|variant| variant.path.clone())
+}
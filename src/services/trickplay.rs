@@ -0,0 +1,145 @@
+use crate::services::webvtt::format_timestamp;
+
+/// Size of one scrubbing thumbnail.
+const TRICKPLAY_TILE_WIDTH: u32 = 160;
+const TRICKPLAY_TILE_HEIGHT: u32 = 90;
+
+/// Thumbnails per sprite sheet row and per column, so a sheet holds 100 of them.
+const TRICKPLAY_SHEET_COLUMNS: u32 = 10;
+const TRICKPLAY_SHEET_ROWS: u32 = 10;
+
+const DEFAULT_TRICKPLAY_INTERVAL_SECONDS: u32 = 10;
+
+/// Written next to the sprite sheets, which it references by relative URL.
+pub const TRICKPLAY_STORYBOARD_FILENAME: &str = "storyboard.vtt";
+
+/// Seconds between scrubbing thumbnails, configurable through `TRICKPLAY_INTERVAL_SECONDS`
+/// (defaults to 10). `None` when set to 0, which turns trick-play generation off.
+pub fn trickplay_interval() -> Option<u32> {
+    let interval = std::env::var("TRICKPLAY_INTERVAL_SECONDS")
+        .ok()
+        .and_then(|value| value.trim().parse().ok())
+        .unwrap_or(DEFAULT_TRICKPLAY_INTERVAL_SECONDS);
+    (interval > 0).then_some(interval)
+}
+
+/// FFmpeg filter turning the video into sprite sheets with one thumbnail every `interval_secs`,
+/// laid out left to right and top to bottom.
+pub fn trickplay_filter(interval_secs: u32) -> String {
+    format!(
+        "fps=1/{interval},scale={w}:{h}:force_original_aspect_ratio=decrease,pad={w}:{h}:({w}-iw)/2:({h}-ih)/2,setsar=1,tile={columns}x{rows}",
+        interval = interval_secs,
+        w = TRICKPLAY_TILE_WIDTH,
+        h = TRICKPLAY_TILE_HEIGHT,
+        columns = TRICKPLAY_SHEET_COLUMNS,
+        rows = TRICKPLAY_SHEET_ROWS,
+    )
+}
+
+/// WebVTT storyboard mapping every `interval_secs` range of the video to the `#xywh` region of
+/// its thumbnail. `sprites` are the sheet file names in the order FFmpeg wrote them.
+pub fn render_storyboard(duration_secs: f64, interval_secs: u32, sprites: &[String]) -> String {
+    let interval = interval_secs as f64;
+    let per_sheet = (TRICKPLAY_SHEET_COLUMNS * TRICKPLAY_SHEET_ROWS) as usize;
+    let thumbnail_count = ((duration_secs / interval).ceil() as usize)
+        .max(1)
+        .min(sprites.len() * per_sheet);
+
+    let mut storyboard = String::from("WEBVTT\n");
+    for index in 0..thumbnail_count {
+        let start = index as f64 * interval;
+        let end = (start + interval).min(duration_secs.max(start + 0.001));
+        let tile = (index % per_sheet) as u32;
+        storyboard.push_str(&format!(
+            "\n{} --> {}\n{}#xywh={},{},{},{}\n",
+            format_timestamp(start),
+            format_timestamp(end),
+            sprites[index / per_sheet],
+            tile % TRICKPLAY_SHEET_COLUMNS * TRICKPLAY_TILE_WIDTH,
+            tile / TRICKPLAY_SHEET_COLUMNS * TRICKPLAY_TILE_HEIGHT,
+            TRICKPLAY_TILE_WIDTH,
+            TRICKPLAY_TILE_HEIGHT
+        ));
+    }
+    storyboard
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sprites(count: usize) -> Vec<String> {
+        (1..=count)
+            .map(|index| format!("sprite_{:03}.jpg", index))
+            .collect()
+    }
+
+    /// (start, end, target) of every cue
+    fn cues(storyboard: &str) -> Vec<(String, String, String)> {
+        storyboard
+            .split("\n\n")
+            .skip(1)
+            .map(|cue| {
+                let (timing, target) = cue.trim_end().split_once('\n').unwrap();
+                let (start, end) = timing.split_once(" --> ").unwrap();
+                (start.to_string(), end.to_string(), target.to_string())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn renders_storyboard() {
+        assert_eq!(
+            render_storyboard(25.0, 10, &sprites(1)),
+            "WEBVTT
+
+00:00:00.000 --> 00:00:10.000
+sprite_001.jpg#xywh=0,0,160,90
+
+00:00:10.000 --> 00:00:20.000
+sprite_001.jpg#xywh=160,0,160,90
+
+00:00:20.000 --> 00:00:25.000
+sprite_001.jpg#xywh=320,0,160,90
+"
+        );
+    }
+
+    #[test]
+    fn lays_tiles_out_in_rows_and_sheets() {
+        let cues = cues(&render_storyboard(1005.0, 10, &sprites(2)));
+
+        assert_eq!(cues.len(), 101);
+        assert_eq!(cues[9].2, "sprite_001.jpg#xywh=1440,0,160,90");
+        assert_eq!(cues[10].2, "sprite_001.jpg#xywh=0,90,160,90");
+        assert_eq!(cues[99].2, "sprite_001.jpg#xywh=1440,810,160,90");
+        assert_eq!(cues[100].2, "sprite_002.jpg#xywh=0,0,160,90");
+        assert_eq!(
+            (cues[100].0.as_str(), cues[100].1.as_str()),
+            ("00:16:40.000", "00:16:45.000")
+        );
+    }
+
+    #[test]
+    fn stops_at_the_thumbnails_written() {
+        let cues = cues(&render_storyboard(2000.0, 10, &sprites(1)));
+
+        assert_eq!(cues.len(), 100);
+        assert_eq!(cues[99].1, "00:16:40.000");
+    }
+
+    #[test]
+    fn covers_very_short_videos_with_one_thumbnail() {
+        let cues = cues(&render_storyboard(0.0, 10, &sprites(1)));
+
+        assert_eq!(
+            cues,
+            vec![(
+                "00:00:00.000".to_string(),
+                "00:00:00.001".to_string(),
+                "sprite_001.jpg#xywh=0,0,160,90".to_string()
+            )]
+        );
+        assert_eq!(render_storyboard(25.0, 10, &[]), "WEBVTT\n");
+    }
+}
@@ -0,0 +1,292 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use chrono::{Duration, Utc};
+use sqlx::PgPool;
+use std::env;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+use tokio::fs;
+use uuid::Uuid;
+
+use crate::models::{CreateUploadSessionRequest, CreateVideoRequest, UploadSession, Video};
+use crate::services::insert_video;
+
+const DEFAULT_UPLOAD_EXPIRATION_HOURS: i64 = 24;
+
+/// How long a request holds an upload before another may take it over; writers renew it.
+pub const UPLOAD_LOCK_SECONDS: i64 = 60;
+
+#[async_trait]
+pub trait UploadServiceTrait: Send + Sync {
+    async fn create_upload(
+        &self,
+        user_id: Uuid,
+        request: CreateUploadSessionRequest,
+    ) -> Result<UploadSession>;
+
+    async fn get_upload(&self, upload_id: &Uuid) -> Result<Option<UploadSession>>;
+
+    /// Lease the upload to `lock_id` for `UPLOAD_LOCK_SECONDS`, so only one request writes to it
+    /// at a time. Returns `None` when the upload is gone or another request holds the lease.
+    async fn lock_upload(&self, upload_id: &Uuid, lock_id: &Uuid) -> Result<Option<UploadSession>>;
+
+    /// Extend the lease; `false` when it expired and another request took the upload over.
+    async fn renew_lock(&self, upload_id: &Uuid, lock_id: &Uuid) -> Result<bool>;
+
+    async fn unlock_upload(&self, upload_id: &Uuid, lock_id: &Uuid) -> Result<()>;
+
+    /// Move the stored offset from `expected_offset` to `new_offset` while `lock_id` holds the
+    /// lease. Returns `false` when the upload changed hands or moved on in the meantime.
+    async fn advance_offset(
+        &self,
+        upload_id: &Uuid,
+        lock_id: &Uuid,
+        expected_offset: i64,
+        new_offset: i64,
+    ) -> Result<bool>;
+
+    /// Create the video for a fully received upload and mark the upload complete, both or
+    /// neither. Returns `None` when the upload is already complete or `lock_id` lost the lease.
+    async fn complete_upload(
+        &self,
+        upload_id: &Uuid,
+        lock_id: &Uuid,
+        request: CreateVideoRequest,
+        filename: String,
+    ) -> Result<Option<Video>>;
+
+    /// Remove an unfinished upload and its partial data. Completed uploads are left alone,
+    /// since their data now belongs to the video pipeline, and so are uploads another request
+    /// is writing to; `lock_id` lets the lease holder remove its own upload.
+    async fn delete_upload(&self, upload_id: &Uuid, lock_id: Option<&Uuid>) -> Result<bool>;
+
+    /// Remove expired unfinished sessions together with their partial data, returning how many were removed.
+    async fn delete_expired_uploads(&self) -> Result<u64>;
+
+    /// Local file holding the bytes received so far for an upload.
+    fn part_path(&self, upload_id: &Uuid) -> PathBuf;
+}
+
+#[derive(Clone)]
+pub struct UploadService {
+    pool: PgPool,
+    upload_dir: PathBuf,
+    expiration: Duration,
+}
+
+impl UploadService {
+    pub fn new(pool: PgPool) -> Self {
+        let upload_dir = env::var("UPLOAD_DIR").unwrap_or_else(|_| "uploads".to_string());
+        let expiration_hours = env::var("TUS_UPLOAD_EXPIRATION_HOURS")
+            .ok()
+            .and_then(|value| value.trim().parse().ok())
+            .unwrap_or(DEFAULT_UPLOAD_EXPIRATION_HOURS);
+
+        Self {
+            pool,
+            upload_dir: PathBuf::from(upload_dir).join("tus"),
+            expiration: Duration::hours(expiration_hours),
+        }
+    }
+
+    async fn remove_part_file(&self, upload_id: &Uuid) -> Result<()> {
+        let part_path = self.part_path(upload_id);
+        match fs::remove_file(&part_path).await {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(anyhow::anyhow!(
+                "Failed to remove {}: {}",
+                part_path.display(),
+                e
+            )),
+        }
+    }
+}
+
+#[async_trait]
+impl UploadServiceTrait for UploadService {
+    async fn create_upload(
+        &self,
+        user_id: Uuid,
+        request: CreateUploadSessionRequest,
+    ) -> Result<UploadSession> {
+        let expires_at = Utc::now() + self.expiration;
+
+        let session = sqlx::query_as!(
+            UploadSession,
+            "INSERT INTO upload_sessions (user_id, title, description, original_filename, upload_length, metadata, expires_at)
+             VALUES ($1, $2, $3, $4, $5, $6, $7) RETURNING *",
+            user_id,
+            request.title,
+            request.description,
+            request.original_filename,
+            request.upload_length,
+            request.metadata,
+            expires_at
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        fs::create_dir_all(&self.upload_dir)
+            .await
+            .with_context(|| format!("Failed to create {}", self.upload_dir.display()))?;
+        let part_path = self.part_path(&session.id);
+        fs::File::create(&part_path)
+            .await
+            .with_context(|| format!("Failed to create {}", part_path.display()))?;
+
+        Ok(session)
+    }
+
+    async fn get_upload(&self, upload_id: &Uuid) -> Result<Option<UploadSession>> {
+        let session = sqlx::query_as!(
+            UploadSession,
+            "SELECT * FROM upload_sessions WHERE id = $1",
+            upload_id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(session)
+    }
+
+    async fn lock_upload(&self, upload_id: &Uuid, lock_id: &Uuid) -> Result<Option<UploadSession>> {
+        let session = sqlx::query_as!(
+            UploadSession,
+            "UPDATE upload_sessions SET locked_by = $1, locked_until = NOW() + $2 * INTERVAL '1 second'
+             WHERE id = $3 AND (locked_until IS NULL OR locked_until < NOW()) RETURNING *",
+            lock_id,
+            UPLOAD_LOCK_SECONDS as f64,
+            upload_id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(session)
+    }
+
+    async fn renew_lock(&self, upload_id: &Uuid, lock_id: &Uuid) -> Result<bool> {
+        let result = sqlx::query!(
+            "UPDATE upload_sessions SET locked_until = NOW() + $1 * INTERVAL '1 second' WHERE id = $2 AND locked_by = $3",
+            UPLOAD_LOCK_SECONDS as f64,
+            upload_id,
+            lock_id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn unlock_upload(&self, upload_id: &Uuid, lock_id: &Uuid) -> Result<()> {
+        sqlx::query!(
+            "UPDATE upload_sessions SET locked_by = NULL, locked_until = NULL WHERE id = $1 AND locked_by = $2",
+            upload_id,
+            lock_id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn advance_offset(
+        &self,
+        upload_id: &Uuid,
+        lock_id: &Uuid,
+        expected_offset: i64,
+        new_offset: i64,
+    ) -> Result<bool> {
+        let result = sqlx::query!(
+            "UPDATE upload_sessions SET upload_offset = $1 WHERE id = $2 AND locked_by = $3 AND upload_offset = $4",
+            new_offset,
+            upload_id,
+            lock_id,
+            expected_offset
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn complete_upload(
+        &self,
+        upload_id: &Uuid,
+        lock_id: &Uuid,
+        request: CreateVideoRequest,
+        filename: String,
+    ) -> Result<Option<Video>> {
+        let mut tx = self.pool.begin().await?;
+        let Some(session) = sqlx::query_as!(
+            UploadSession,
+            "UPDATE upload_sessions SET completed_at = NOW() WHERE id = $1 AND locked_by = $2 AND completed_at IS NULL RETURNING *",
+            upload_id,
+            lock_id
+        )
+        .fetch_optional(&mut *tx)
+        .await?
+        else {
+            return Ok(None);
+        };
+
+        let video = insert_video(
+            &mut *tx,
+            request,
+            session.user_id,
+            filename,
+            session.original_filename,
+            session.upload_length,
+        )
+        .await?;
+        sqlx::query!(
+            "UPDATE upload_sessions SET video_id = $1 WHERE id = $2",
+            video.id,
+            upload_id
+        )
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        Ok(Some(video))
+    }
+
+    async fn delete_upload(&self, upload_id: &Uuid, lock_id: Option<&Uuid>) -> Result<bool> {
+        let result = sqlx::query!(
+            "DELETE FROM upload_sessions WHERE id = $1 AND completed_at IS NULL
+             AND (locked_until IS NULL OR locked_until < NOW() OR locked_by = $2)",
+            upload_id,
+            lock_id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Ok(false);
+        }
+        self.remove_part_file(upload_id).await?;
+
+        Ok(true)
+    }
+
+    async fn delete_expired_uploads(&self) -> Result<u64> {
+        let expired_ids = sqlx::query_scalar!(
+            "DELETE FROM upload_sessions WHERE expires_at <= NOW() AND completed_at IS NULL
+             AND (locked_until IS NULL OR locked_until < NOW()) RETURNING id"
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        for upload_id in &expired_ids {
+            if let Err(e) = self.remove_part_file(upload_id).await {
+                log::warn!("Failed to remove expired upload {}: {}", upload_id, e);
+            }
+        }
+
+        Ok(expired_ids.len() as u64)
+    }
+
+    fn part_path(&self, upload_id: &Uuid) -> PathBuf {
+        self.upload_dir.join(format!("{}.part", upload_id))
+    }
+}
@@ -0,0 +1,351 @@
+use anyhow::{Context, Result};
+use tokio::fs;
+use tokio::io::AsyncReadExt;
+use tokio::process::Command;
+
+use crate::models::MediaInfo;
+use crate::services::{probe_media, FfmpegError};
+
+/// Bytes read to recognise the container: enough for the second MPEG-TS sync byte.
+pub const CONTAINER_HEADER_LEN: usize = 189;
+
+const MPEG_TS_PACKET_LEN: usize = 188;
+
+const DEFAULT_MAX_VIDEO_DURATION_SECONDS: f64 = 6.0 * 60.0 * 60.0;
+
+/// Longer and shorter side, so portrait videos get the same limit as landscape ones.
+const DEFAULT_MAX_VIDEO_RESOLUTION: (u32, u32) = (7680, 4320);
+
+/// Upload whose content is not something the pipeline can process, with a message for the
+/// uploader.
+#[derive(Debug)]
+pub enum UploadRejection {
+    /// The bytes are not a video container at all, whatever the file name says
+    UnsupportedFormat,
+    /// A video container without a decodable video stream, or outside the configured limits
+    InvalidContent(String),
+}
+
+impl std::fmt::Display for UploadRejection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UploadRejection::UnsupportedFormat => write!(
+                f,
+                "File is not a supported video format. Supported containers: MP4, MOV, MKV, WebM, AVI, FLV, WMV, MPEG-PS, MPEG-TS, Ogg"
+            ),
+            UploadRejection::InvalidContent(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl std::error::Error for UploadRejection {}
+
+/// Upload that passed validation.
+#[derive(Debug, Clone)]
+pub struct ValidatedUpload {
+    /// File extension matching the detected container, used for the stored original
+    pub extension: &'static str,
+    pub media_info: MediaInfo,
+}
+
+/// Limits on what uploads may contain, configurable through `MAX_VIDEO_DURATION_SECONDS`
+/// (defaults to 6 hours), `MAX_VIDEO_RESOLUTION` (`WIDTHxHEIGHT`, defaults to 7680x4320) and
+/// `ALLOWED_VIDEO_CODECS` (comma separated FFprobe codec names such as `h264,hevc`; any
+/// decodable codec when unset).
+#[derive(Debug, Clone)]
+pub struct UploadLimits {
+    pub max_duration_secs: f64,
+    pub max_resolution: (u32, u32),
+    pub allowed_codecs: Option<Vec<String>>,
+}
+
+impl UploadLimits {
+    pub fn from_env() -> Self {
+        let max_duration_secs = std::env::var("MAX_VIDEO_DURATION_SECONDS")
+            .ok()
+            .and_then(|value| value.trim().parse().ok())
+            .filter(|secs: &f64| *secs > 0.0)
+            .unwrap_or(DEFAULT_MAX_VIDEO_DURATION_SECONDS);
+        let max_resolution = std::env::var("MAX_VIDEO_RESOLUTION")
+            .ok()
+            .and_then(|value| {
+                let (width, height) = value.trim().split_once(['x', 'X'])?;
+                Some((width.trim().parse().ok()?, height.trim().parse().ok()?))
+            })
+            .map(|(width, height): (u32, u32)| (width.max(height), width.min(height)))
+            .unwrap_or(DEFAULT_MAX_VIDEO_RESOLUTION);
+        let allowed_codecs = std::env::var("ALLOWED_VIDEO_CODECS")
+            .ok()
+            .map(|value| {
+                value
+                    .split(',')
+                    .map(|codec| codec.trim().to_lowercase())
+                    .filter(|codec| !codec.is_empty())
+                    .collect::<Vec<_>>()
+            })
+            .filter(|codecs| !codecs.is_empty());
+
+        Self {
+            max_duration_secs,
+            max_resolution,
+            allowed_codecs,
+        }
+    }
+
+    fn check(&self, media_info: &MediaInfo) -> std::result::Result<(), UploadRejection> {
+        if media_info.duration_secs() > self.max_duration_secs {
+            return Err(UploadRejection::InvalidContent(format!(
+                "Video is too long: {} seconds. Maximum allowed: {} seconds",
+                media_info.duration_secs().round(),
+                self.max_duration_secs.round()
+            )));
+        }
+
+        let Some(video) = media_info.primary_video() else {
+            return Ok(());
+        };
+        let (width, height) = video.display_dimensions();
+        let (max_long, max_short) = self.max_resolution;
+        if width.max(height) > max_long || width.min(height) > max_short {
+            return Err(UploadRejection::InvalidContent(format!(
+                "Video resolution too large: {}x{}. Maximum allowed: {}x{}",
+                width, height, max_long, max_short
+            )));
+        }
+
+        if let Some(codecs) = &self.allowed_codecs {
+            if !codecs.contains(&video.codec) {
+                return Err(UploadRejection::InvalidContent(format!(
+                    "Unsupported video codec: {}. Supported codecs: {}",
+                    video.codec,
+                    codecs.join(", ")
+                )));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Extension of the video container the file starts with, recognised by its magic number.
+pub fn detect_container(header: &[u8]) -> Option<&'static str> {
+    match header {
+        // ISO base media: a box size followed by `ftyp`, or a bare `moov`/`mdat` in old QuickTime
+        [_, _, _, _, b'f', b't', b'y', b'p', b'q', b't', b' ', b' ', ..] => Some("mov"),
+        [_, _, _, _, b'f', b't', b'y', b'p', ..] => Some("mp4"),
+        [_, _, _, _, b'm', b'o', b'o', b'v', ..]
+        | [_, _, _, _, b'm', b'd', b'a', b't', ..]
+        | [_, _, _, _, b'w', b'i', b'd', b'e', ..]
+        | [_, _, _, _, b'f', b'r', b'e', b'e', ..] => Some("mov"),
+        // EBML header, whose DocType tells WebM from Matroska
+        [0x1A, 0x45, 0xDF, 0xA3, ..] if header.windows(4).any(|doc_type| doc_type == b"webm") => {
+            Some("webm")
+        }
+        [0x1A, 0x45, 0xDF, 0xA3, ..] => Some("mkv"),
+        [b'R', b'I', b'F', b'F', _, _, _, _, b'A', b'V', b'I', b' ', ..] => Some("avi"),
+        [b'F', b'L', b'V', 0x01, ..] => Some("flv"),
+        // ASF header object GUID
+        [0x30, 0x26, 0xB2, 0x75, 0x8E, 0x66, 0xCF, 0x11, ..] => Some("wmv"),
+        [0x00, 0x00, 0x01, 0xBA, ..] => Some("mpg"),
+        [b'O', b'g', b'g', b'S', ..] => Some("ogv"),
+        [0x47, ..] if header.get(MPEG_TS_PACKET_LEN) == Some(&0x47) => Some("ts"),
+        _ => None,
+    }
+}
+
+/// First bytes of the file at `path`, up to `CONTAINER_HEADER_LEN`.
+pub async fn read_container_header(path: &str) -> Result<Vec<u8>> {
+    let mut file = fs::File::open(path)
+        .await
+        .with_context(|| format!("Failed to open {}", path))?;
+    let mut header = Vec::with_capacity(CONTAINER_HEADER_LEN);
+    (&mut file)
+        .take(CONTAINER_HEADER_LEN as u64)
+        .read_to_end(&mut header)
+        .await
+        .with_context(|| format!("Failed to read {}", path))?;
+    Ok(header)
+}
+
+/// Check that the upload at `path` is a video the pipeline can process: a known container, a
+/// video stream FFmpeg decodes, and within `limits`. Content problems are returned as an
+/// `UploadRejection`; any other error means the check itself failed.
+pub async fn validate_upload(path: &str, limits: &UploadLimits) -> Result<ValidatedUpload> {
+    let header = read_container_header(path).await?;
+    let extension = detect_container(&header).ok_or(UploadRejection::UnsupportedFormat)?;
+
+    let media_info = match probe_media(path).await {
+        Ok(media_info) => media_info,
+        // Only a failure to run FFprobe is ours; anything it reports is about the file
+        Err(err) if err.downcast_ref::<std::io::Error>().is_some() => return Err(err),
+        Err(err) => {
+            log::info!("Rejecting upload {}: {:#}", path, err);
+            return Err(UploadRejection::InvalidContent(
+                "File could not be read as a video".to_string(),
+            )
+            .into());
+        }
+    };
+    if media_info.primary_video().is_none() {
+        return Err(
+            UploadRejection::InvalidContent("File contains no video stream".to_string()).into(),
+        );
+    }
+
+    limits.check(&media_info)?;
+
+    // FFprobe only reads headers, so decode a frame to be sure the stream is usable
+    let output = Command::new("ffmpeg")
+        .args([
+            "-v",
+            "error",
+            "-i",
+            path,
+            "-map",
+            "0:v:0",
+            "-frames:v",
+            "1",
+            "-f",
+            "null",
+            "-",
+        ])
+        .output()
+        .await
+        .context("Failed to execute FFmpeg decode check")?;
+    if !output.status.success() {
+        log::info!(
+            "Rejecting upload {}: {}",
+            path,
+            FfmpegError::new("FFmpeg decode error", &output.stderr)
+        );
+        return Err(UploadRejection::InvalidContent(
+            "The video stream could not be decoded".to_string(),
+        )
+        .into());
+    }
+
+    Ok(ValidatedUpload {
+        extension,
+        media_info,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{DynamicRange, VideoStreamInfo};
+
+    fn header(prefix: &[u8]) -> Vec<u8> {
+        let mut header = prefix.to_vec();
+        header.resize(CONTAINER_HEADER_LEN, 0);
+        header
+    }
+
+    fn ebml(doc_type: &[u8]) -> Vec<u8> {
+        let mut prefix = vec![0x1A, 0x45, 0xDF, 0xA3, 0x9F, 0x42, 0x82, 0x84];
+        prefix.extend_from_slice(doc_type);
+        header(&prefix)
+    }
+
+    fn media_info(width: u32, height: u32, rotation: u32) -> MediaInfo {
+        MediaInfo {
+            container: "mov,mp4,m4a,3gp,3g2,mj2".to_string(),
+            duration_ms: 60_000,
+            bit_rate: None,
+            size: None,
+            video: vec![VideoStreamInfo {
+                codec: "h264".to_string(),
+                profile: None,
+                width,
+                height,
+                frame_rate: Some(30.0),
+                bit_rate: None,
+                pixel_format: None,
+                rotation,
+                color_space: None,
+                color_transfer: None,
+                color_primaries: None,
+                color_range: None,
+                dynamic_range: DynamicRange::Sdr,
+            }],
+            audio: Vec::new(),
+            subtitles: Vec::new(),
+        }
+    }
+
+    fn limits(max_resolution: (u32, u32)) -> UploadLimits {
+        UploadLimits {
+            max_duration_secs: DEFAULT_MAX_VIDEO_DURATION_SECONDS,
+            max_resolution,
+            allowed_codecs: None,
+        }
+    }
+
+    #[test]
+    fn detects_iso_base_media_brands() {
+        assert_eq!(
+            detect_container(&header(b"\0\0\0\x20ftypisom")),
+            Some("mp4")
+        );
+        assert_eq!(
+            detect_container(&header(b"\0\0\0\x14ftypqt  ")),
+            Some("mov")
+        );
+        assert_eq!(detect_container(&header(b"\0\0\0\x08wide")), Some("mov"));
+        assert_eq!(detect_container(&header(b"\0\0\0\x20ftyp")), Some("mp4"));
+    }
+
+    #[test]
+    fn tells_webm_from_matroska_by_doc_type() {
+        assert_eq!(detect_container(&ebml(b"webm")), Some("webm"));
+        assert_eq!(detect_container(&ebml(b"matroska")), Some("mkv"));
+    }
+
+    #[test]
+    fn detects_mpeg_ts_by_the_second_sync_byte() {
+        let mut ts = header(&[0x47]);
+        assert_eq!(detect_container(&ts), None);
+
+        ts[MPEG_TS_PACKET_LEN] = 0x47;
+        assert_eq!(detect_container(&ts), Some("ts"));
+        // A file shorter than one packet cannot be told apart from anything starting with `G`
+        assert_eq!(detect_container(&ts[..MPEG_TS_PACKET_LEN]), None);
+    }
+
+    #[test]
+    fn rejects_non_video_headers() {
+        assert_eq!(detect_container(&header(b"%PDF-1.7")), None);
+        assert_eq!(detect_container(&header(b"\x89PNG\r\n\x1a\n")), None);
+        assert_eq!(detect_container(b""), None);
+    }
+
+    #[test]
+    fn applies_resolution_limit_to_portrait_and_landscape_alike() {
+        let limits = limits((3840, 2160));
+
+        assert!(limits.check(&media_info(3840, 2160, 0)).is_ok());
+        assert!(limits.check(&media_info(2160, 3840, 0)).is_ok());
+        // Coded landscape, displayed portrait
+        assert!(limits.check(&media_info(3840, 2160, 90)).is_ok());
+
+        assert!(limits.check(&media_info(4096, 2160, 0)).is_err());
+        assert!(limits.check(&media_info(2160, 4096, 0)).is_err());
+        // Within the long side, but the short side is too large in either orientation
+        assert!(limits.check(&media_info(2400, 3840, 0)).is_err());
+        assert!(limits.check(&media_info(3840, 2400, 270)).is_err());
+    }
+
+    #[test]
+    fn checks_duration_and_codec_limits() {
+        let mut limits = limits(DEFAULT_MAX_VIDEO_RESOLUTION);
+        limits.max_duration_secs = 30.0;
+        assert!(limits.check(&media_info(1280, 720, 0)).is_err());
+
+        limits.max_duration_secs = 60.0;
+        limits.allowed_codecs = Some(vec!["hevc".to_string()]);
+        assert!(limits.check(&media_info(1280, 720, 0)).is_err());
+
+        limits.allowed_codecs = Some(vec!["hevc".to_string(), "h264".to_string()]);
+        assert!(limits.check(&media_info(1280, 720, 0)).is_ok());
+    }
+}
@@ -1,9 +1,59 @@
-use crate::models::{CreateVideoRequest, PaginatedResponse, PaginationMeta, Video, VideoStatus};
+use crate::models::{
+    AudioTrack, CreateVideoRequest, MediaInfo, PaginatedResponse, PaginationMeta,
+    ProcessingFailure, ThumbnailCandidate, ThumbnailVariant, Video, VideoEvent, VideoEventKind,
+    VideoStatus,
+};
+use crate::services::{publish_video_event, CloudStorageService};
 use anyhow::Result;
 use async_trait::async_trait;
-use sqlx::PgPool;
+use sqlx::{PgExecutor, PgPool};
+use std::sync::Arc;
 use uuid::Uuid;
 
+/// Outputs of a processing run, stored once it succeeded.
+#[derive(Debug, Clone)]
+pub struct VideoMetadata {
+    pub duration: Option<i32>,
+    pub duration_ms: Option<i64>,
+    pub media_info: Option<MediaInfo>,
+    pub thumbnail_path: Option<String>,
+    pub hls_playlist_path: Option<String>,
+    pub dash_manifest_path: Option<String>,
+    pub storyboard_path: Option<String>,
+    pub preview_path: Option<String>,
+    pub preview_webp_path: Option<String>,
+    pub audio_tracks: Vec<AudioTrack>,
+}
+
+/// Insert a new video in the `uploading` state. Shared with tus uploads, which create the video
+/// in the same transaction that completes the upload.
+pub async fn insert_video<'e, E: PgExecutor<'e>>(
+    executor: E,
+    request: CreateVideoRequest,
+    user_id: Uuid,
+    filename: String,
+    original_filename: String,
+    file_size: i64,
+) -> Result<Video> {
+    let video = sqlx::query_as!(
+        Video,
+        "INSERT INTO videos (title, description, filename, original_filename, file_size, status, user_id, encoding_ladder) 
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8) RETURNING *",
+        request.title,
+        request.description as Option<String>,
+        filename,
+        original_filename,
+        file_size,
+        VideoStatus::Uploading.to_string(),
+        user_id,
+        request.encoding_ladder as Option<String>
+    )
+    .fetch_one(executor)
+    .await?;
+
+    Ok(video)
+}
+
 #[async_trait]
 pub trait VideoServiceTrait: Send + Sync {
     async fn create_video(
@@ -24,16 +74,36 @@ pub trait VideoServiceTrait: Send + Sync {
         offset: i64,
     ) -> Result<PaginatedResponse<Video>>;
 
+    /// Set the status, clearing any recorded failure once the video is processing or ready.
     async fn update_video_status(&self, video_id: &Uuid, status: VideoStatus) -> Result<()>;
 
-    async fn update_video_metadata(
+    /// Set the video to failed, recording the failure message and its structured details.
+    async fn mark_video_failed(&self, video_id: &Uuid, failure: &ProcessingFailure) -> Result<()>;
+
+    async fn update_processing_progress(
         &self,
         video_id: &Uuid,
-        duration: Option<i32>,
-        thumbnail_path: Option<String>,
-        hls_playlist_path: Option<String>,
+        progress_percent: f64,
+        rendition_progress: serde_json::Value,
     ) -> Result<()>;
 
+    /// Videos still uploading or processing that were last touched more than `stale_after_secs`
+    /// ago and have no queued or running processing job.
+    async fn list_stale_videos(&self, stale_after_secs: i64) -> Result<Vec<Video>>;
+
+    async fn update_video_metadata(&self, video_id: &Uuid, metadata: VideoMetadata) -> Result<()>;
+
+    /// Store the thumbnail variants, made from `candidate` or from a custom upload when `None`.
+    /// `candidates` replaces the stored candidates when given.
+    async fn update_video_thumbnail(
+        &self,
+        video_id: &Uuid,
+        thumbnail_path: Option<String>,
+        variants: &[ThumbnailVariant],
+        candidate: Option<i32>,
+        candidates: Option<&[ThumbnailCandidate]>,
+    ) -> Result<Video>;
+
     async fn delete_video(&self, video_id: &Uuid, user_id: &Uuid) -> Result<bool>;
 
     async fn update_video_details(
@@ -47,11 +117,36 @@ pub trait VideoServiceTrait: Send + Sync {
 #[derive(Clone)]
 pub struct VideoService {
     pool: PgPool,
+    storage_service: Arc<dyn CloudStorageService>,
 }
 
 impl VideoService {
-    pub fn new(pool: PgPool) -> Self {
-        Self { pool }
+    pub fn new(pool: PgPool, storage_service: Arc<dyn CloudStorageService>) -> Self {
+        Self {
+            pool,
+            storage_service,
+        }
+    }
+
+    /// Notify listeners about a change; `user_id` is `None` when the update matched no row.
+    async fn publish(&self, video_id: &Uuid, user_id: Option<Uuid>, kind: VideoEventKind) {
+        let Some(user_id) = user_id else {
+            return;
+        };
+
+        let event = VideoEvent {
+            video_id: *video_id,
+            user_id,
+            kind,
+        };
+        if let Err(err) = publish_video_event(&self.pool, &event).await {
+            log::warn!(
+                "Failed to publish {} event for {}: {}",
+                event.kind.name(),
+                video_id,
+                err
+            );
+        }
     }
 }
 
@@ -65,26 +160,15 @@ impl VideoServiceTrait for VideoService {
         original_filename: String,
         file_size: i64,
     ) -> Result<Video> {
-        let video_id = sqlx::query_scalar!(
-            "INSERT INTO videos (title, description, filename, original_filename, file_size, status, user_id) 
-             VALUES ($1, $2, $3, $4, $5, $6, $7) RETURNING id",
-            request.title,
-            request.description as Option<String>,
+        insert_video(
+            &self.pool,
+            request,
+            user_id,
             filename,
             original_filename,
             file_size,
-            VideoStatus::Uploading.to_string(),
-            user_id
         )
-        .fetch_one(&self.pool)
-        .await?;
-
-        // Fetch the complete video record
-        let video = sqlx::query_as!(Video, "SELECT * FROM videos WHERE id = $1", video_id)
-            .fetch_one(&self.pool)
-            .await?;
-
-        Ok(video)
+        .await
     }
 
     async fn get_video_by_id(&self, video_id: &Uuid) -> Result<Option<Video>> {
@@ -151,54 +235,194 @@ impl VideoServiceTrait for VideoService {
     }
 
     async fn update_video_status(&self, video_id: &Uuid, status: VideoStatus) -> Result<()> {
-        sqlx::query!(
-            "UPDATE videos SET status = $1, updated_at = NOW() WHERE id = $2",
+        // A retry or reprocessing that gets going again supersedes the previous failure
+        let clear_failure = matches!(status, VideoStatus::Processing | VideoStatus::Ready);
+        let user_id = sqlx::query_scalar!(
+            "UPDATE videos SET status = $1,
+                failure_reason = CASE WHEN $2 THEN NULL ELSE failure_reason END,
+                failure_details = CASE WHEN $2 THEN NULL ELSE failure_details END,
+                updated_at = NOW()
+             WHERE id = $3 RETURNING user_id",
             status.to_string(),
+            clear_failure,
             video_id
         )
-        .execute(&self.pool)
+        .fetch_optional(&self.pool)
         .await?;
 
+        self.publish(
+            video_id,
+            user_id,
+            VideoEventKind::Status {
+                status,
+                failure_reason: None,
+            },
+        )
+        .await;
+
         Ok(())
     }
 
-    async fn update_video_metadata(
+    async fn mark_video_failed(&self, video_id: &Uuid, failure: &ProcessingFailure) -> Result<()> {
+        let user_id = sqlx::query_scalar!(
+            "UPDATE videos SET status = $1, failure_reason = $2, failure_details = $3, updated_at = NOW() WHERE id = $4 RETURNING user_id",
+            VideoStatus::Failed.to_string(),
+            failure.message,
+            serde_json::to_value(failure)?,
+            video_id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        self.publish(
+            video_id,
+            user_id,
+            VideoEventKind::Status {
+                status: VideoStatus::Failed,
+                failure_reason: Some(failure.message.clone()),
+            },
+        )
+        .await;
+
+        Ok(())
+    }
+
+    async fn update_processing_progress(
         &self,
         video_id: &Uuid,
-        duration: Option<i32>,
-        thumbnail_path: Option<String>,
-        hls_playlist_path: Option<String>,
+        progress_percent: f64,
+        rendition_progress: serde_json::Value,
     ) -> Result<()> {
+        let user_id = sqlx::query_scalar!(
+            "UPDATE videos SET progress_percent = $1, rendition_progress = $2, progress_updated_at = NOW() WHERE id = $3 RETURNING user_id",
+            progress_percent,
+            rendition_progress,
+            video_id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        self.publish(
+            video_id,
+            user_id,
+            VideoEventKind::Progress {
+                progress_percent,
+                renditions: rendition_progress,
+            },
+        )
+        .await;
+
+        Ok(())
+    }
+
+    async fn list_stale_videos(&self, stale_after_secs: i64) -> Result<Vec<Video>> {
+        let videos = sqlx::query_as!(
+            Video,
+            r#"
+                SELECT v.* FROM videos v
+                WHERE v.status IN ('uploading', 'processing')
+                  AND v.updated_at < NOW() - ($1::bigint * INTERVAL '1 second')
+                  AND NOT EXISTS (
+                      SELECT 1 FROM processing_jobs j
+                      WHERE j.video_id = v.id AND j.status IN ('queued', 'running')
+                  )
+                ORDER BY v.updated_at
+            "#,
+            stale_after_secs
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(videos)
+    }
+
+    async fn update_video_metadata(&self, video_id: &Uuid, metadata: VideoMetadata) -> Result<()> {
+        let VideoMetadata {
+            duration,
+            duration_ms,
+            media_info,
+            thumbnail_path,
+            hls_playlist_path,
+            dash_manifest_path,
+            storyboard_path,
+            preview_path,
+            preview_webp_path,
+            audio_tracks,
+        } = metadata;
+
         log::info!("🚀 Updating video metadata for video_id: {}", video_id);
-        log::info!("🔹 Duration: {:?}", duration);
+        log::info!("🔹 Duration: {:?} ({:?} ms)", duration, duration_ms);
         log::info!("🔹 Thumbnail path: {:?}", thumbnail_path);
         log::info!("🔹 HLS playlist path: {:?}", hls_playlist_path);
+        log::info!("🔹 DASH manifest path: {:?}", dash_manifest_path);
+        log::info!("🔹 Storyboard path: {:?}", storyboard_path);
+        log::info!("🔹 Preview path: {:?}", preview_path);
+        log::info!("🔹 Audio tracks: {}", audio_tracks.len());
 
-        let result = sqlx::query!(
-            "UPDATE videos SET duration = $1, thumbnail_path = $2, hls_playlist_path = $3, updated_at = NOW() WHERE id = $4",
+        let video = sqlx::query_as!(
+            Video,
+            "UPDATE videos SET duration = $1, duration_ms = $2, media_info = $3, thumbnail_path = $4, hls_playlist_path = $5, dash_manifest_path = $6, storyboard_path = $7, preview_path = $8, preview_webp_path = $9, audio_tracks = $10, updated_at = NOW() WHERE id = $11 RETURNING *",
             duration,
+            duration_ms,
+            media_info.as_ref().map(serde_json::to_value).transpose()?,
             thumbnail_path,
             hls_playlist_path,
+            dash_manifest_path,
+            storyboard_path,
+            preview_path,
+            preview_webp_path,
+            serde_json::to_value(&audio_tracks)?,
             video_id
         )
-        .execute(&self.pool)
+        .fetch_optional(&self.pool)
         .await?;
 
         log::info!(
             "✅ Database update result: {} rows affected",
-            result.rows_affected()
+            usize::from(video.is_some())
         );
 
-        if result.rows_affected() == 0 {
-            log::warn!(
+        match video {
+            Some(video) => {
+                let kind = VideoEventKind::metadata(&video, self.storage_service.as_ref());
+                self.publish(video_id, Some(video.user_id), kind).await;
+            }
+            None => log::warn!(
                 "⚠️ No rows were updated! Video ID {} might not exist",
                 video_id
-            );
+            ),
         }
 
         Ok(())
     }
 
+    async fn update_video_thumbnail(
+        &self,
+        video_id: &Uuid,
+        thumbnail_path: Option<String>,
+        variants: &[ThumbnailVariant],
+        candidate: Option<i32>,
+        candidates: Option<&[ThumbnailCandidate]>,
+    ) -> Result<Video> {
+        let candidates = candidates.map(serde_json::to_value).transpose()?;
+        let video = sqlx::query_as!(
+            Video,
+            "UPDATE videos SET thumbnail_path = $1, thumbnail_variants = $2, thumbnail_candidate = $3, thumbnail_candidates = COALESCE($4, thumbnail_candidates), updated_at = NOW() WHERE id = $5 RETURNING *",
+            thumbnail_path,
+            serde_json::to_value(variants)?,
+            candidate,
+            candidates,
+            video_id
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        let kind = VideoEventKind::metadata(&video, self.storage_service.as_ref());
+        self.publish(video_id, Some(video.user_id), kind).await;
+
+        Ok(video)
+    }
+
     async fn delete_video(&self, video_id: &Uuid, user_id: &Uuid) -> Result<bool> {
         let result = sqlx::query!(
             "DELETE FROM videos WHERE id = $1 AND user_id = $2",
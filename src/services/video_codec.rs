@@ -0,0 +1,215 @@
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::services::Rendition;
+
+/// Video codec a rendition can be encoded with, configured per rendition in the encoding ladders.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VideoCodec {
+    H264,
+    Hevc,
+    Vp9,
+    Av1,
+}
+
+impl VideoCodec {
+    pub const ALL: [VideoCodec; 4] = [
+        VideoCodec::H264,
+        VideoCodec::Hevc,
+        VideoCodec::Vp9,
+        VideoCodec::Av1,
+    ];
+
+    /// ffmpeg encoders for the codec, most preferred first.
+    fn encoder_candidates(&self) -> &'static [&'static str] {
+        match self {
+            VideoCodec::H264 => &["libx264"],
+            VideoCodec::Hevc => &["libx265"],
+            VideoCodec::Vp9 => &["libvpx-vp9"],
+            VideoCodec::Av1 => &["libsvtav1", "libaom-av1"],
+        }
+    }
+
+    /// Suffix added to rendition names, so `1080p` becomes `1080p_hevc`. H.264 keeps plain names.
+    fn rendition_suffix(&self) -> Option<&'static str> {
+        match self {
+            VideoCodec::H264 => None,
+            VideoCodec::Hevc => Some("hevc"),
+            VideoCodec::Vp9 => Some("vp9"),
+            VideoCodec::Av1 => Some("av1"),
+        }
+    }
+}
+
+impl std::fmt::Display for VideoCodec {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VideoCodec::H264 => write!(f, "h264"),
+            VideoCodec::Hevc => write!(f, "hevc"),
+            VideoCodec::Vp9 => write!(f, "vp9"),
+            VideoCodec::Av1 => write!(f, "av1"),
+        }
+    }
+}
+
+/// An ffmpeg encoder picked for a codec.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VideoEncoder {
+    pub codec: VideoCodec,
+    pub name: String,
+}
+
+impl Default for VideoEncoder {
+    fn default() -> Self {
+        Self {
+            codec: VideoCodec::H264,
+            name: "libx264".to_string(),
+        }
+    }
+}
+
+impl VideoEncoder {
+    /// `-c:v` and the encoder-specific options keeping keyframes on the forced GOP boundaries.
+    pub fn args(&self) -> Vec<String> {
+        let mut args = vec!["-c:v".to_string(), self.name.clone()];
+        let extra: &[&str] = match self.name.as_str() {
+            // Forced keyframes must be IDR frames for segments to start with a closed GOP
+            "libx265" => &[
+                "-tag:v",
+                "hvc1",
+                "-x265-params",
+                "scenecut=0:open-gop=0:forced-idr=1",
+            ],
+            "libvpx-vp9" => &["-row-mt", "1", "-deadline", "good", "-cpu-used", "4"],
+            "libsvtav1" => &["-preset", "8", "-svtav1-params", "scd=0"],
+            "libaom-av1" => &["-row-mt", "1", "-cpu-used", "6"],
+            _ => &[],
+        };
+        args.extend(extra.iter().map(|arg| arg.to_string()));
+        if self.codec != VideoCodec::H264 {
+            args.extend(["-pix_fmt".to_string(), "yuv420p".to_string()]);
+        }
+        args
+    }
+}
+
+/// Video encoders the local ffmpeg build provides.
+#[derive(Debug, Clone)]
+pub struct VideoEncoders {
+    available: HashMap<VideoCodec, String>,
+}
+
+impl Default for VideoEncoders {
+    /// Only libx264, which the pipeline has always required.
+    fn default() -> Self {
+        Self {
+            available: HashMap::from([(VideoCodec::H264, "libx264".to_string())]),
+        }
+    }
+}
+
+impl VideoEncoders {
+    /// Ask ffmpeg which of the supported encoders it was built with.
+    pub fn detect() -> Result<Self> {
+        let output = std::process::Command::new("ffmpeg")
+            .args(["-hide_banner", "-encoders"])
+            .output()
+            .context("Failed to execute FFmpeg command for encoders")?;
+
+        if !output.status.success() {
+            return Err(anyhow!("FFmpeg could not list its encoders"));
+        }
+
+        // Encoder lines look like ` V....D libx264    libx264 H.264 / AVC ...`
+        let listing = String::from_utf8_lossy(&output.stdout);
+        let built_in: Vec<&str> = listing
+            .lines()
+            .filter_map(|line| line.split_whitespace().nth(1))
+            .collect();
+
+        let available = VideoCodec::ALL
+            .iter()
+            .filter_map(|codec| {
+                codec
+                    .encoder_candidates()
+                    .iter()
+                    .find(|candidate| built_in.contains(candidate))
+                    .map(|encoder| (*codec, encoder.to_string()))
+            })
+            .collect();
+
+        Ok(Self { available })
+    }
+
+    pub fn get(&self, codec: VideoCodec) -> Option<VideoEncoder> {
+        self.available.get(&codec).map(|name| VideoEncoder {
+            codec,
+            name: name.clone(),
+        })
+    }
+
+    /// `codec (encoder)` pairs for logging, in codec order.
+    pub fn describe(&self) -> Vec<String> {
+        let mut codecs: Vec<_> = self.available.iter().collect();
+        codecs.sort_unstable();
+        codecs
+            .into_iter()
+            .map(|(codec, encoder)| format!("{} ({})", codec, encoder))
+            .collect()
+    }
+
+    /// One rendition per configured codec, grouped by codec with H.264 first. Codecs without an
+    /// available encoder are skipped, as are codecs other than H.264 when `fmp4_segments` is false,
+    /// since HLS only carries them in fragmented MP4.
+    pub fn expand_renditions(
+        &self,
+        renditions: &[Rendition],
+        fmp4_segments: bool,
+    ) -> Result<Vec<Rendition>> {
+        let mut expanded = Vec::new();
+
+        for codec in VideoCodec::ALL {
+            let wanted: Vec<&Rendition> = renditions
+                .iter()
+                .filter(|rendition| rendition.codecs.contains(&codec))
+                .collect();
+            if wanted.is_empty() {
+                continue;
+            }
+
+            let Some(encoder) = self.get(codec) else {
+                log::warn!("No {} encoder available, skipping its renditions", codec);
+                continue;
+            };
+            if codec != VideoCodec::H264 && !fmp4_segments {
+                log::warn!(
+                    "{} renditions need fmp4 segments, skipping them for ts output",
+                    codec
+                );
+                continue;
+            }
+
+            for rendition in wanted {
+                let mut variant = rendition.clone();
+                if let Some(suffix) = codec.rendition_suffix() {
+                    variant.name = format!("{}_{}", rendition.name, suffix);
+                }
+                if let Some(bitrate) = rendition.codec_bitrates.get(&codec) {
+                    variant.video_bitrate = bitrate.clone();
+                }
+                variant.codecs = vec![codec];
+                variant.encoder = encoder.clone();
+                expanded.push(variant);
+            }
+        }
+
+        if expanded.is_empty() {
+            return Err(anyhow!(
+                "No rendition can be encoded with the available encoders"
+            ));
+        }
+        Ok(expanded)
+    }
+}
@@ -0,0 +1,147 @@
+use anyhow::Result;
+use sqlx::postgres::PgListener;
+use sqlx::PgPool;
+use std::time::Duration;
+use tokio::sync::broadcast;
+
+use crate::models::{VideoEvent, VideoEventKind};
+
+/// Postgres NOTIFY channel carrying JSON-encoded `VideoEvent`s between instances.
+pub const VIDEO_EVENTS_CHANNEL: &str = "video_events";
+
+/// Postgres rejects NOTIFY payloads of 8000 bytes or more.
+const MAX_NOTIFY_PAYLOAD_BYTES: usize = 7999;
+
+const BUS_CAPACITY: usize = 1024;
+const LISTENER_RETRY_DELAY: Duration = Duration::from_secs(5);
+
+/// Publish a video event to every instance listening on `VIDEO_EVENTS_CHANNEL`.
+pub async fn publish_video_event(pool: &PgPool, event: &VideoEvent) -> Result<()> {
+    let payload = notify_payload(event)?;
+    sqlx::query!("SELECT pg_notify($1, $2)", VIDEO_EVENTS_CHANNEL, payload)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+/// JSON payload for `event`, replaced by a `changed` event when it does not fit in a notification.
+fn notify_payload(event: &VideoEvent) -> Result<String> {
+    let payload = serde_json::to_string(event)?;
+    if payload.len() <= MAX_NOTIFY_PAYLOAD_BYTES {
+        return Ok(payload);
+    }
+
+    log::warn!(
+        "{} event for {} is {} bytes, too large to publish; sending a changed event instead",
+        event.kind.name(),
+        event.video_id,
+        payload.len()
+    );
+    let changed = VideoEvent {
+        video_id: event.video_id,
+        user_id: event.user_id,
+        kind: VideoEventKind::Changed {
+            event: event.kind.name().to_string(),
+        },
+    };
+    Ok(serde_json::to_string(&changed)?)
+}
+
+/// In-process fan-out of video events received over Postgres LISTEN/NOTIFY.
+#[derive(Clone)]
+pub struct VideoEventBus {
+    sender: broadcast::Sender<VideoEvent>,
+}
+
+impl Default for VideoEventBus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl VideoEventBus {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(BUS_CAPACITY);
+        Self { sender }
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<VideoEvent> {
+        self.sender.subscribe()
+    }
+
+    /// Forward notifications from Postgres to local subscribers, reconnecting on errors.
+    pub fn spawn_listener(&self, pool: PgPool) {
+        let sender = self.sender.clone();
+        tokio::spawn(async move {
+            loop {
+                if let Err(err) = Self::listen(&pool, &sender).await {
+                    log::warn!("Video event listener stopped: {}", err);
+                }
+                tokio::time::sleep(LISTENER_RETRY_DELAY).await;
+            }
+        });
+    }
+
+    async fn listen(pool: &PgPool, sender: &broadcast::Sender<VideoEvent>) -> Result<()> {
+        let mut listener = PgListener::connect_with(pool).await?;
+        listener.listen(VIDEO_EVENTS_CHANNEL).await?;
+        log::info!("Listening for video events on {}", VIDEO_EVENTS_CHANNEL);
+
+        loop {
+            let notification = listener.recv().await?;
+            match serde_json::from_str::<VideoEvent>(notification.payload()) {
+                // Sending only fails when nobody is subscribed
+                Ok(event) => {
+                    let _ = sender.send(event);
+                }
+                Err(err) => log::warn!("Ignoring malformed video event: {}", err),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::VideoStatus;
+    use uuid::Uuid;
+
+    fn event(kind: VideoEventKind) -> VideoEvent {
+        VideoEvent {
+            video_id: Uuid::new_v4(),
+            user_id: Uuid::new_v4(),
+            kind,
+        }
+    }
+
+    #[test]
+    fn small_events_are_sent_whole() {
+        let status = event(VideoEventKind::Status {
+            status: VideoStatus::Failed,
+            failure_reason: Some("Corrupt input".to_string()),
+        });
+
+        let payload = notify_payload(&status).unwrap();
+        let decoded: VideoEvent = serde_json::from_str(&payload).unwrap();
+        assert!(matches!(
+            decoded.kind,
+            VideoEventKind::Status { failure_reason: Some(reason), .. } if reason == "Corrupt input"
+        ));
+    }
+
+    #[test]
+    fn oversized_events_become_changed_events() {
+        let status = event(VideoEventKind::Status {
+            status: VideoStatus::Failed,
+            failure_reason: Some("x".repeat(10_000)),
+        });
+
+        let payload = notify_payload(&status).unwrap();
+        assert!(payload.len() <= MAX_NOTIFY_PAYLOAD_BYTES);
+        let decoded: VideoEvent = serde_json::from_str(&payload).unwrap();
+        assert_eq!(decoded.video_id, status.video_id);
+        assert_eq!(decoded.user_id, status.user_id);
+        assert!(matches!(decoded.kind, VideoEventKind::Changed { event } if event == "status"));
+    }
+}
@@ -1,34 +1,297 @@
 use anyhow::{anyhow, Context, Result};
 use async_trait::async_trait;
 use serde_json::json;
-use std::path::Path;
+use std::process::Stdio;
+use std::str::FromStr;
 use std::sync::Arc;
 use std::time::Instant;
 use tokio::fs;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, BufReader};
 use tokio::process::Command;
 use uuid::Uuid;
 
-use crate::models::VideoStatus;
-use crate::services::{CloudStorageService, MetricsServiceTrait, VideoServiceTrait};
+use crate::models::{
+    AudioStreamInfo, AudioTrack, MediaInfo, ProcessingJob, SubtitleSource, SubtitleStreamInfo,
+    Video, VideoStatus,
+};
+use crate::services::processing_progress::parse_ffmpeg_progress;
+use crate::services::{
+    audio_codec, default_thumbnail_path, generate_thumbnail_candidates, loudnorm_filter,
+    measure_bitrates, measure_loudness, parse_bitrate, parse_frame_rate, parse_media_playlist,
+    parse_subtitles, pick_thumbnail_candidate, preview_args, preview_clips, probe_keyframe_times,
+    probe_media, render_dash_manifest, render_master_playlist, render_storyboard,
+    set_subtitle_renditions, spawn_progress_writer, store_thumbnail_candidates,
+    store_thumbnail_variants, subtitle_renditions, thumbnail_candidate_count, trickplay_filter,
+    trickplay_interval, video_codec, AudioRendition, CloudStorageService, DashAudioRepresentation,
+    DashRepresentation, EncodingLadders, FailedStep, FfmpegError, JobQueueServiceTrait,
+    MediaPlaylist, MetricsServiceTrait, NewSubtitleTrack, ProcessUsageTracker, ProgressReporter,
+    Rendition, SubtitleServiceTrait, VariantStream, VideoCodec, VideoEncoders, VideoMetadata,
+    VideoServiceTrait, PREVIEW_MP4_FILENAME, PREVIEW_WEBP_FILENAME, TRICKPLAY_STORYBOARD_FILENAME,
+    UPLOADED_PROGRESS,
+};
+
+const DEFAULT_MAX_FILE_SIZE: u64 = 2 * 1024 * 1024 * 1024;
+
+const HLS_SEGMENT_SECONDS: &str = "10";
+
+/// Written next to `playlist.m3u8` when the segments are CMAF.
+const DASH_MANIFEST_FILENAME: &str = "manifest.mpd";
+
+/// Progress entry of the audio renditions, reported next to the video renditions.
+const AUDIO_PROGRESS_KEY: &str = "audio";
+
+/// `GROUP-ID` of the audio renditions in the master playlist.
+const AUDIO_GROUP_ID: &str = "audio";
+
+/// Subtitle codecs FFmpeg can convert to WebVTT; bitmap formats such as PGS cannot be.
+const TEXT_SUBTITLE_CODECS: [&str; 6] = ["subrip", "ass", "ssa", "webvtt", "mov_text", "text"];
+
+/// Keyframe interval shared by every rendition; `HLS_SEGMENT_SECONDS` must be a multiple of it.
+const GOP_SECONDS: f64 = 2.0;
+
+/// H.264 profiles every HLS player decodes, which a source may be stream copied in.
+const PASSTHROUGH_H264_PROFILES: [&str; 4] = ["Constrained Baseline", "Baseline", "Main", "High"];
+
+/// How far the source video bitrate may exceed the rendition bitrate and still be stream copied.
+const PASSTHROUGH_BITRATE_TOLERANCE: f64 = 1.5;
+
+/// Source keyframes the other renditions can be forced onto; more would not fit in one ffmpeg
+/// argument.
+const MAX_PASSTHROUGH_KEYFRAMES: usize = 10_000;
+
+/// How renditions are encoded, selected with `HLS_ENCODING_MODE`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HlsEncodingMode {
+    /// One ffmpeg process per rendition, each decoding the source (default)
+    PerRendition,
+    /// One ffmpeg process decoding the source once and splitting it into every rendition
+    SingleDecode,
+}
+
+impl HlsEncodingMode {
+    pub fn from_env() -> Self {
+        match std::env::var("HLS_ENCODING_MODE") {
+            Ok(mode) => mode.parse().unwrap_or_else(|err| {
+                log::warn!("{}, falling back to per_rendition", err);
+                HlsEncodingMode::PerRendition
+            }),
+            Err(_) => HlsEncodingMode::PerRendition,
+        }
+    }
+
+    /// Step name the encode is additionally recorded under in `video_processing_metrics`.
+    pub fn metric_step(&self) -> &'static str {
+        match self {
+            HlsEncodingMode::PerRendition => "generate_hls_streams_per_rendition",
+            HlsEncodingMode::SingleDecode => "generate_hls_streams_single_decode",
+        }
+    }
+}
+
+impl FromStr for HlsEncodingMode {
+    type Err = String;
 
-const THUMBNAIL_FILTER: &str =
-    "scale=320:180:force_original_aspect_ratio=decrease,pad=320:180:(320-iw)/2:(180-ih)/2,setsar=1";
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "per_rendition" => Ok(HlsEncodingMode::PerRendition),
+            "single_decode" => Ok(HlsEncodingMode::SingleDecode),
+            _ => Err(format!("Invalid HLS encoding mode: {}", s)),
+        }
+    }
+}
+
+impl std::fmt::Display for HlsEncodingMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HlsEncodingMode::PerRendition => write!(f, "per_rendition"),
+            HlsEncodingMode::SingleDecode => write!(f, "single_decode"),
+        }
+    }
+}
+
+/// Container of the HLS segments, selected with `HLS_SEGMENT_FORMAT`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HlsSegmentFormat {
+    /// CMAF fragmented MP4 `.m4s` segments with an `#EXT-X-MAP` init section (default)
+    Fmp4,
+    /// MPEG-TS `.ts` segments, for players without fMP4 support
+    Ts,
+}
+
+impl HlsSegmentFormat {
+    pub fn from_env() -> Self {
+        match std::env::var("HLS_SEGMENT_FORMAT") {
+            Ok(format) => format.parse().unwrap_or_else(|err| {
+                log::warn!("{}, falling back to fmp4", err);
+                HlsSegmentFormat::Fmp4
+            }),
+            Err(_) => HlsSegmentFormat::Fmp4,
+        }
+    }
+
+    /// Format a processed video was encoded with, going by its DASH manifest which only CMAF
+    /// output has. Videos not processed yet get the configured format.
+    pub fn of_video(video: &Video) -> Self {
+        match (&video.hls_playlist_path, &video.dash_manifest_path) {
+            (None, _) => Self::from_env(),
+            (Some(_), Some(_)) => HlsSegmentFormat::Fmp4,
+            (Some(_), None) => HlsSegmentFormat::Ts,
+        }
+    }
+
+    /// 90 kHz media timestamp of the first frame, which WebVTT segments map cue time zero to.
+    pub fn subtitle_mpegts_offset(&self) -> u64 {
+        match self {
+            HlsSegmentFormat::Fmp4 => 0,
+            // FFmpeg's MPEG-TS muxer starts timestamps 1.4 seconds in
+            HlsSegmentFormat::Ts => 126_000,
+        }
+    }
+
+    /// HLS muxer options writing the `{name}.m3u8` rendition playlist and its segments.
+    fn muxer_args(&self, output_dir: &str, name: &str) -> Vec<String> {
+        let mut args = vec![
+            "-hls_time".to_string(),
+            HLS_SEGMENT_SECONDS.to_string(),
+            "-hls_playlist_type".to_string(),
+            "vod".to_string(),
+        ];
+        match self {
+            HlsSegmentFormat::Fmp4 => args.extend([
+                "-hls_segment_type".to_string(),
+                "fmp4".to_string(),
+                "-hls_fmp4_init_filename".to_string(),
+                format!("{}_init.mp4", name),
+                "-hls_segment_filename".to_string(),
+                format!("{}/{}_%03d.m4s", output_dir, name),
+            ]),
+            HlsSegmentFormat::Ts => args.extend([
+                "-hls_segment_type".to_string(),
+                "mpegts".to_string(),
+                "-hls_segment_filename".to_string(),
+                format!("{}/{}_%03d.ts", output_dir, name),
+            ]),
+        }
+        args.extend([
+            "-f".to_string(),
+            "hls".to_string(),
+            format!("{}/{}.m3u8", output_dir, name),
+        ]);
+        args
+    }
+}
+
+impl FromStr for HlsSegmentFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "fmp4" | "cmaf" => Ok(HlsSegmentFormat::Fmp4),
+            "ts" | "mpegts" => Ok(HlsSegmentFormat::Ts),
+            _ => Err(format!("Invalid HLS segment format: {}", s)),
+        }
+    }
+}
+
+impl std::fmt::Display for HlsSegmentFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HlsSegmentFormat::Fmp4 => write!(f, "fmp4"),
+            HlsSegmentFormat::Ts => write!(f, "ts"),
+        }
+    }
+}
+
+/// Top H.264 rendition segmented straight from the source with `-c copy`, when the source is
+/// already H.264 at that rung's resolution and bitrate. Disabled with `HLS_REMUX_PASSTHROUGH=false`.
+#[derive(Debug, Clone)]
+struct Passthrough {
+    rendition: String,
+    /// Source keyframe times in seconds, which the transcoded renditions put their keyframes on
+    keyframe_times: Vec<f64>,
+    /// Longest source GOP in frames
+    max_gop_frames: u32,
+}
+
+impl Passthrough {
+    fn enabled() -> bool {
+        std::env::var("HLS_REMUX_PASSTHROUGH")
+            .map(|value| {
+                !matches!(
+                    value.trim().to_lowercase().as_str(),
+                    "0" | "false" | "no" | "off"
+                )
+            })
+            .unwrap_or(true)
+    }
+
+    /// Encoder options replacing `gop_args` for the transcoded renditions: keyframes exactly on
+    /// the source keyframes and none in between, so every rendition is cut into the same
+    /// segments as the stream copied one.
+    fn keyframe_args(&self) -> Vec<String> {
+        // Floored to milliseconds so each time falls on or just before its frame
+        let times = self
+            .keyframe_times
+            .iter()
+            .map(|time| format!("{:.3}", (time * 1000.0).floor() / 1000.0))
+            .collect::<Vec<_>>()
+            .join(",");
+        vec![
+            "-force_key_frames".to_string(),
+            times,
+            "-sc_threshold".to_string(),
+            "0".to_string(),
+            "-flags".to_string(),
+            "+cgop".to_string(),
+            "-g".to_string(),
+            (self.max_gop_frames + 1).to_string(),
+        ]
+    }
+
+    /// Output options segmenting the source video stream without re-encoding it.
+    fn output_args(segment_format: HlsSegmentFormat, output_dir: &str, name: &str) -> Vec<String> {
+        let mut args = vec![
+            "-map".to_string(),
+            "0:v:0".to_string(),
+            "-c:v".to_string(),
+            "copy".to_string(),
+        ];
+        args.extend(segment_format.muxer_args(output_dir, name));
+        args
+    }
+}
 
 #[async_trait]
 pub trait VideoProcessingServiceTrait: Send + Sync {
+    /// Mark the video as processing and queue a job for it; the work itself happens in `run_job`.
     async fn process_video(
         &self,
         video_id: Uuid,
-        video_data: Vec<u8>,
         filename: &str,
         benchmark_run_id: Option<Uuid>,
     ) -> Result<()>;
+
+    /// Run the full pipeline for a claimed job against the stored original.
+    async fn run_job(&self, job: &ProcessingJob) -> Result<()>;
+}
+
+/// An encoded rendition as described in the master playlist and DASH manifest.
+struct MeasuredRendition {
+    playlist: MediaPlaylist,
+    bandwidth: u64,
+    average_bandwidth: u64,
+    codecs: Vec<String>,
+    frame_rate: Option<f64>,
 }
 
 pub struct VideoProcessingService {
     video_service: Arc<dyn VideoServiceTrait>,
     storage_service: Arc<dyn CloudStorageService>,
     metrics_service: Arc<dyn MetricsServiceTrait>,
+    job_queue_service: Arc<dyn JobQueueServiceTrait>,
+    encoding_ladders: Arc<EncodingLadders>,
+    video_encoders: Arc<VideoEncoders>,
+    subtitle_service: Arc<dyn SubtitleServiceTrait>,
 }
 
 impl VideoProcessingService {
@@ -36,11 +299,19 @@ impl VideoProcessingService {
         video_service: Arc<dyn VideoServiceTrait>,
         storage_service: Arc<dyn CloudStorageService>,
         metrics_service: Arc<dyn MetricsServiceTrait>,
+        job_queue_service: Arc<dyn JobQueueServiceTrait>,
+        encoding_ladders: Arc<EncodingLadders>,
+        video_encoders: Arc<VideoEncoders>,
+        subtitle_service: Arc<dyn SubtitleServiceTrait>,
     ) -> Self {
         Self {
             video_service,
             storage_service,
             metrics_service,
+            job_queue_service,
+            encoding_ladders,
+            video_encoders,
+            subtitle_service,
         }
     }
 
@@ -49,43 +320,159 @@ impl VideoProcessingService {
         local_input_path: String,
         temp_dir: String,
         storage_output_dir: String,
-        storage_thumbnail_path: String,
+        storage_thumbnails_dir: String,
         video_service: Arc<dyn VideoServiceTrait>,
         storage_service: Arc<dyn CloudStorageService>,
         metrics_service: Arc<dyn MetricsServiceTrait>,
+        subtitle_service: Arc<dyn SubtitleServiceTrait>,
         benchmark_run_id: Option<Uuid>,
+        renditions: Vec<Rendition>,
+        loudness_target: Option<f64>,
     ) -> Result<()> {
         let local_output_dir = format!("{}/hls", temp_dir);
-        let local_thumbnail_path = format!("{}/thumbnail.jpg", temp_dir);
+        let local_thumbnails_dir = format!("{}/thumbnails", temp_dir);
+        let local_candidates_dir = format!("{}/candidates", local_thumbnails_dir);
 
-        log::info!("process_video_background: Generating HLS streams in multiple resolutions");
-        let hls_timer = Instant::now();
-        let profile_count = Self::generate_hls_streams(&local_input_path, &local_output_dir)
+        log::info!("process_video_background: Probing source media");
+        let probe_timer = Instant::now();
+        let mut media_info = probe_media(&local_input_path)
             .await
-            .context("Failed to generate HLS streams")?;
+            .context(FailedStep::new(
+                "probe_media",
+                "Failed to probe source media",
+            ))?;
+        let duration_secs = media_info.duration_secs();
         if let Err(err) = metrics_service
             .record_video_processing_step(
                 benchmark_run_id,
                 Some(video_id),
-                "generate_hls_streams",
-                Some(hls_timer.elapsed().as_millis() as i64),
+                "probe_media",
+                Some(probe_timer.elapsed().as_millis() as i64),
                 None,
                 None,
             )
             .await
         {
             log::warn!(
-                "Failed to record generate_hls_streams metric for {}: {}",
+                "Failed to record probe_media metric for {}: {}",
                 video_id,
                 err
             );
         }
 
-        log::info!("process_video_background: Generating thumbnail");
+        // Loudness normalization is optional; a stream that cannot be measured keeps its level
+        if let Some(target_lufs) = loudness_target.filter(|_| !media_info.audio.is_empty()) {
+            log::info!(
+                "process_video_background: Measuring audio loudness against {} LUFS",
+                target_lufs
+            );
+            let loudness_timer = Instant::now();
+            for (index, stream) in media_info.audio.iter_mut().enumerate() {
+                match measure_loudness(&local_input_path, index, target_lufs).await {
+                    Ok(loudness) => {
+                        log::info!(
+                            "Audio stream {} of {} measures {} LUFS integrated, {} dBTP true peak",
+                            index,
+                            video_id,
+                            loudness.integrated_lufs,
+                            loudness.true_peak_dbtp
+                        );
+                        stream.loudness = Some(loudness);
+                    }
+                    Err(err) => log::warn!(
+                        "Failed to measure loudness of audio stream {} of {}, keeping its level: {:#}",
+                        index,
+                        video_id,
+                        err
+                    ),
+                }
+            }
+            if let Err(err) = metrics_service
+                .record_video_processing_step(
+                    benchmark_run_id,
+                    Some(video_id),
+                    "measure_loudness",
+                    Some(loudness_timer.elapsed().as_millis() as i64),
+                    None,
+                    None,
+                )
+                .await
+            {
+                log::warn!(
+                    "Failed to record measure_loudness metric for {}: {}",
+                    video_id,
+                    err
+                );
+            }
+        }
+
+        log::info!("process_video_background: Generating HLS streams in multiple resolutions");
+        let hls_timer = Instant::now();
+        let (progress, progress_writer) =
+            spawn_progress_writer(video_id, Arc::clone(&video_service));
+        let encoding_mode = HlsEncodingMode::from_env();
+        let ffmpeg_usage = ProcessUsageTracker::start();
+        let hls_result = Self::generate_hls_streams(
+            &local_input_path,
+            &local_output_dir,
+            &media_info,
+            &renditions,
+            encoding_mode,
+            progress,
+            &ffmpeg_usage,
+        )
+        .await;
+        let usage = ffmpeg_usage.finish();
+        let (audio_tracks, passthrough) = hls_result.context(FailedStep::new(
+            "generate_hls_streams",
+            "Failed to generate HLS streams",
+        ))?;
+        let rendition_progress = progress_writer.await.context(FailedStep::new(
+            "generate_hls_streams",
+            "Progress writer task failed to join",
+        ))?;
+        let hls_ms = hls_timer.elapsed().as_millis() as i64;
+        // The mode and passthrough steps let the insights endpoint compare encoding strategies
+        let passthrough_step = if passthrough {
+            "generate_hls_streams_passthrough"
+        } else {
+            "generate_hls_streams_full_transcode"
+        };
+        for step in [
+            "generate_hls_streams",
+            encoding_mode.metric_step(),
+            passthrough_step,
+        ] {
+            if let Err(err) = metrics_service
+                .record_video_processing_step(
+                    benchmark_run_id,
+                    Some(video_id),
+                    step,
+                    Some(hls_ms),
+                    usage.cpu_avg,
+                    usage.mem_peak,
+                )
+                .await
+            {
+                log::warn!("Failed to record {} metric for {}: {}", step, video_id, err);
+            }
+        }
+
+        log::info!("process_video_background: Generating thumbnail candidates");
         let thumbnail_timer = Instant::now();
-        Self::generate_thumbnail(&local_input_path, &local_thumbnail_path)
-            .await
-            .context("Failed to generate thumbnail")?;
+        let thumbnail_candidates = generate_thumbnail_candidates(
+            &local_input_path,
+            &local_candidates_dir,
+            duration_secs,
+            thumbnail_candidate_count(),
+        )
+        .await
+        .context(FailedStep::new(
+            "generate_thumbnail",
+            "Failed to generate thumbnail",
+        ))?;
+        let selected_candidate =
+            pick_thumbnail_candidate(&local_candidates_dir, &thumbnail_candidates).await;
         if let Err(err) = metrics_service
             .record_video_processing_step(
                 benchmark_run_id,
@@ -104,6 +491,60 @@ impl VideoProcessingService {
             );
         }
 
+        // Subtitles are optional extras, so a failure here never fails the video
+        log::info!("process_video_background: Extracting embedded subtitles");
+        let subtitles_timer = Instant::now();
+        match Self::extract_subtitles(
+            video_id,
+            &local_input_path,
+            &media_info.subtitles,
+            &temp_dir,
+            duration_secs,
+            subtitle_service.as_ref(),
+        )
+        .await
+        {
+            Ok(count) => log::info!("Extracted {} embedded subtitle tracks", count),
+            Err(err) => log::warn!(
+                "Failed to extract embedded subtitles for {}: {:#}",
+                video_id,
+                err
+            ),
+        }
+        match subtitle_service.list_subtitle_tracks(&video_id).await {
+            Ok(tracks) if !tracks.is_empty() => {
+                let master_path = format!("{}/playlist.m3u8", local_output_dir);
+                let master = fs::read_to_string(&master_path)
+                    .await
+                    .context("Failed to read master playlist")?;
+                fs::write(
+                    &master_path,
+                    set_subtitle_renditions(&master, &subtitle_renditions(&tracks)),
+                )
+                .await
+                .context("Failed to write master playlist")?;
+            }
+            Ok(_) => {}
+            Err(err) => log::warn!("Failed to list subtitle tracks for {}: {}", video_id, err),
+        }
+        if let Err(err) = metrics_service
+            .record_video_processing_step(
+                benchmark_run_id,
+                Some(video_id),
+                "extract_subtitles",
+                Some(subtitles_timer.elapsed().as_millis() as i64),
+                None,
+                None,
+            )
+            .await
+        {
+            log::warn!(
+                "Failed to record extract_subtitles metric for {}: {}",
+                video_id,
+                err
+            );
+        }
+
         log::info!("process_video_background: Uploading HLS files to cloud storage");
         let upload_hls_timer = Instant::now();
         let uploaded_files = Self::upload_hls_files_to_storage(
@@ -112,7 +553,20 @@ impl VideoProcessingService {
             Arc::clone(&storage_service),
         )
         .await
-        .context("Failed to upload HLS files to storage")?;
+        .context(FailedStep::new(
+            "upload_hls_files",
+            "Failed to upload HLS files to storage",
+        ))?;
+        if let Err(err) = video_service
+            .update_processing_progress(&video_id, UPLOADED_PROGRESS, rendition_progress.clone())
+            .await
+        {
+            log::warn!(
+                "Failed to record processing progress for {}: {}",
+                video_id,
+                err
+            );
+        }
         if let Err(err) = metrics_service
             .record_video_processing_step(
                 benchmark_run_id,
@@ -131,15 +585,33 @@ impl VideoProcessingService {
             );
         }
 
-        log::info!("process_video_background: Uploading thumbnail to cloud storage");
+        log::info!("process_video_background: Uploading thumbnails to cloud storage");
         let upload_thumbnail_timer = Instant::now();
-        let thumbnail_data = fs::read(&local_thumbnail_path)
-            .await
-            .context("Failed to read thumbnail file")?;
-        storage_service
-            .upload_file_data(thumbnail_data, &storage_thumbnail_path)
-            .await
-            .context("Failed to upload thumbnail to storage")?;
+        let stored_candidates = store_thumbnail_candidates(
+            storage_service.as_ref(),
+            &thumbnail_candidates,
+            &local_candidates_dir,
+            &storage_thumbnails_dir,
+        )
+        .await
+        .context(FailedStep::new(
+            "upload_thumbnail",
+            "Failed to upload thumbnail candidates to storage",
+        ))?;
+        let thumbnail_variants = store_thumbnail_variants(
+            storage_service.as_ref(),
+            &format!(
+                "{}/{}",
+                local_candidates_dir, thumbnail_candidates[selected_candidate].1
+            ),
+            &local_thumbnails_dir,
+            &storage_thumbnails_dir,
+        )
+        .await
+        .context(FailedStep::new(
+            "upload_thumbnail",
+            "Failed to upload thumbnail to storage",
+        ))?;
         if let Err(err) = metrics_service
             .record_video_processing_step(
                 benchmark_run_id,
@@ -158,40 +630,153 @@ impl VideoProcessingService {
             );
         }
 
-        log::info!("process_video_background: Getting video duration");
-        let duration_timer = Instant::now();
-        let duration = Self::get_video_duration(&local_input_path)
+        // Scrubbing previews are optional extras, so a failure here never fails the video
+        let storyboard_path = match trickplay_interval() {
+            Some(interval_secs) => {
+                log::info!("process_video_background: Generating trick-play sprites");
+                let trickplay_timer = Instant::now();
+                let local_trickplay_dir = format!("{}/trickplay", temp_dir);
+                let storage_trickplay_dir = storage_service.get_trickplay_path(&video_id);
+                let trickplay_result = async {
+                    Self::generate_trickplay(
+                        &local_input_path,
+                        &local_trickplay_dir,
+                        duration_secs,
+                        interval_secs,
+                    )
+                    .await?;
+                    Self::upload_hls_files_to_storage(
+                        &local_trickplay_dir,
+                        &storage_trickplay_dir,
+                        Arc::clone(&storage_service),
+                    )
+                    .await
+                }
+                .await;
+                if let Err(err) = metrics_service
+                    .record_video_processing_step(
+                        benchmark_run_id,
+                        Some(video_id),
+                        "generate_trickplay",
+                        Some(trickplay_timer.elapsed().as_millis() as i64),
+                        None,
+                        None,
+                    )
+                    .await
+                {
+                    log::warn!(
+                        "Failed to record generate_trickplay metric for {}: {}",
+                        video_id,
+                        err
+                    );
+                }
+                match trickplay_result {
+                    Ok(_) => Some(format!(
+                        "{}{}",
+                        storage_trickplay_dir, TRICKPLAY_STORYBOARD_FILENAME
+                    )),
+                    Err(err) => {
+                        log::warn!(
+                            "Failed to generate trick-play sprites for {}: {:#}",
+                            video_id,
+                            err
+                        );
+                        None
+                    }
+                }
+            }
+            None => None,
+        };
+
+        // The hover preview is optional as well
+        log::info!("process_video_background: Generating animated preview");
+        let preview_timer = Instant::now();
+        let local_preview_dir = format!("{}/preview", temp_dir);
+        let storage_preview_dir = storage_service.get_preview_path(&video_id);
+        let preview_result = async {
+            Self::generate_preview(&local_input_path, &local_preview_dir, duration_secs).await?;
+            Self::upload_hls_files_to_storage(
+                &local_preview_dir,
+                &storage_preview_dir,
+                Arc::clone(&storage_service),
+            )
             .await
-            .context("Failed to get video duration")?;
+        }
+        .await;
         if let Err(err) = metrics_service
             .record_video_processing_step(
                 benchmark_run_id,
                 Some(video_id),
-                "get_video_duration",
-                Some(duration_timer.elapsed().as_millis() as i64),
+                "generate_preview",
+                Some(preview_timer.elapsed().as_millis() as i64),
                 None,
                 None,
             )
             .await
         {
             log::warn!(
-                "Failed to record get_video_duration metric for {}: {}",
+                "Failed to record generate_preview metric for {}: {}",
                 video_id,
                 err
             );
         }
+        let (preview_path, preview_webp_path) = match preview_result {
+            Ok(_) => (
+                Some(format!("{}{}", storage_preview_dir, PREVIEW_MP4_FILENAME)),
+                Some(format!("{}{}", storage_preview_dir, PREVIEW_WEBP_FILENAME)),
+            ),
+            Err(err) => {
+                log::warn!(
+                    "Failed to generate animated preview for {}: {:#}",
+                    video_id,
+                    err
+                );
+                (None, None)
+            }
+        };
 
         log::info!("process_video_background: Updating video metadata");
         let metadata_timer = Instant::now();
+        let thumbnail_path = default_thumbnail_path(&thumbnail_variants);
+        video_service
+            .update_video_thumbnail(
+                &video_id,
+                thumbnail_path.clone(),
+                &thumbnail_variants,
+                Some(selected_candidate as i32),
+                Some(&stored_candidates),
+            )
+            .await
+            .context(FailedStep::new(
+                "update_video_metadata",
+                "Failed to update video thumbnail",
+            ))?;
+        let dash_manifest_path =
+            fs::try_exists(format!("{}/{}", local_output_dir, DASH_MANIFEST_FILENAME))
+                .await
+                .unwrap_or(false)
+                .then(|| format!("{}{}", storage_output_dir, DASH_MANIFEST_FILENAME));
         video_service
             .update_video_metadata(
                 &video_id,
-                Some(duration),
-                Some(storage_thumbnail_path),
-                Some(format!("{}playlist.m3u8", storage_output_dir)),
+                VideoMetadata {
+                    duration: Some((media_info.duration_ms / 1000) as i32),
+                    duration_ms: Some(media_info.duration_ms),
+                    media_info: Some(media_info),
+                    thumbnail_path,
+                    hls_playlist_path: Some(format!("{}playlist.m3u8", storage_output_dir)),
+                    dash_manifest_path,
+                    storyboard_path,
+                    preview_path,
+                    preview_webp_path,
+                    audio_tracks,
+                },
             )
             .await
-            .context("Failed to update video metadata")?;
+            .context(FailedStep::new(
+                "update_video_metadata",
+                "Failed to update video metadata",
+            ))?;
         if let Err(err) = metrics_service
             .record_video_processing_step(
                 benchmark_run_id,
@@ -212,10 +797,23 @@ impl VideoProcessingService {
 
         log::info!("process_video_background: Updating video status to ready");
         let status_timer = Instant::now();
+        if let Err(err) = video_service
+            .update_processing_progress(&video_id, 100.0, rendition_progress)
+            .await
+        {
+            log::warn!(
+                "Failed to record processing progress for {}: {}",
+                video_id,
+                err
+            );
+        }
         video_service
             .update_video_status(&video_id, VideoStatus::Ready)
             .await
-            .context("Failed to update video status to ready")?;
+            .context(FailedStep::new(
+                "update_video_status_ready",
+                "Failed to update video status to ready",
+            ))?;
         if let Err(err) = metrics_service
             .record_video_processing_step(
                 benchmark_run_id,
@@ -279,99 +877,200 @@ impl VideoProcessingService {
         Ok(())
     }
 
-    async fn generate_hls_streams(input_path: &str, output_dir: &str) -> Result<usize> {
+    async fn generate_hls_streams(
+        input_path: &str,
+        output_dir: &str,
+        media_info: &MediaInfo,
+        renditions: &[Rendition],
+        encoding_mode: HlsEncodingMode,
+        progress: ProgressReporter,
+        usage: &ProcessUsageTracker,
+    ) -> Result<(Vec<AudioTrack>, bool)> {
         fs::create_dir_all(output_dir)
             .await
             .context("Failed to create output directory")?;
 
-        let profiles = vec![
-            ("1080p", 1920, 1080, "2000k", "192k"),
-            ("720p", 1280, 720, "1000k", "128k"),
-            ("480p", 854, 480, "500k", "96k"),
-            ("360p", 640, 360, "250k", "64k"),
-        ];
+        let source = media_info
+            .primary_video()
+            .ok_or_else(|| anyhow!("No video stream found in input"))?;
+        // FFmpeg rotates while decoding, so renditions are planned from the displayed size
+        let (source_width, source_height) = source.display_dimensions();
+        let (source_width, source_height) = (source_width as i32, source_height as i32);
+        let duration_secs = media_info.duration_secs();
+        let frame_rate = source.frame_rate;
+        let audio_tracks = Self::audio_tracks(&media_info.audio);
+        let segment_format = HlsSegmentFormat::from_env();
+
+        let profiles = Self::plan_renditions(renditions, source_width, source_height);
+        let passthrough = if Passthrough::enabled() {
+            match Self::plan_passthrough(input_path, media_info, &profiles).await {
+                Ok(passthrough) => Some(passthrough),
+                Err(reason) => {
+                    log::info!("Transcoding every rendition: {}", reason);
+                    None
+                }
+            }
+        } else {
+            None
+        };
+        let is_passthrough = |rendition: &Rendition| {
+            passthrough
+                .as_ref()
+                .is_some_and(|passthrough| passthrough.rendition == rendition.name)
+        };
+
+        let keyframe_args = match &passthrough {
+            Some(passthrough) => passthrough.keyframe_args(),
+            None => Self::gop_args(frame_rate),
+        };
+        // Keyframe and muxer options shared by every transcoded rendition, whichever the encoding mode
+        let output_args = |name: &str| -> Vec<String> {
+            let mut args = keyframe_args.clone();
+            args.extend(segment_format.muxer_args(output_dir, name));
+            args
+        };
+        // Every variant shares one audio group, encoded at the highest audio bitrate of the ladder
+        let audio_bitrate = profiles
+            .iter()
+            .map(|(rendition, _, _)| rendition.audio_bitrate.as_str())
+            .max_by_key(|bitrate| parse_bitrate(bitrate))
+            .unwrap_or("128k");
+        let audio_args = Self::audio_rendition_args(
+            &audio_tracks,
+            &media_info.audio,
+            audio_bitrate,
+            segment_format,
+            output_dir,
+        );
+        log::info!(
+            "Encoding {}x{} source ({}, {} segments) into {} with {} audio tracks at {}",
+            source_width,
+            source_height,
+            encoding_mode,
+            segment_format,
+            profiles
+                .iter()
+                .map(|(rendition, width, height)| format!(
+                    "{} ({}x{}{})",
+                    rendition.name,
+                    width,
+                    height,
+                    if is_passthrough(rendition) {
+                        ", stream copy"
+                    } else {
+                        ""
+                    }
+                ))
+                .collect::<Vec<_>>()
+                .join(", "),
+            audio_tracks.len(),
+            audio_bitrate
+        );
+
+        if encoding_mode == HlsEncodingMode::SingleDecode {
+            let (copied, transcoded): (Vec<_>, Vec<_>) = profiles
+                .iter()
+                .cloned()
+                .partition(|(rendition, _, _)| is_passthrough(rendition));
+            // Outputs mapped straight from the input rather than from the decoded video
+            let mut direct_outputs: Vec<(String, Vec<String>)> = copied
+                .iter()
+                .map(|(rendition, _, _)| {
+                    (
+                        rendition.name.clone(),
+                        Passthrough::output_args(segment_format, output_dir, &rendition.name),
+                    )
+                })
+                .collect();
+            if !audio_args.is_empty() {
+                direct_outputs.push((AUDIO_PROGRESS_KEY.to_string(), audio_args));
+            }
 
-        let (source_width, source_height) = Self::get_video_dimensions(input_path)
-            .await
-            .context("Failed to get source video dimensions")?;
+            Self::encode_single_decode(
+                input_path,
+                &transcoded,
+                output_args,
+                &direct_outputs,
+                duration_secs,
+                &progress,
+                usage,
+            )
+            .await?;
+
+            let generated_profiles: Vec<_> = profiles
+                .into_iter()
+                .map(|(rendition, width, height)| (rendition.name, width, height))
+                .collect();
+            Self::generate_manifests(output_dir, &generated_profiles, &audio_tracks).await?;
+            return Ok((audio_tracks, passthrough.is_some()));
+        }
 
         let mut generated_profiles = Vec::with_capacity(profiles.len());
 
         let input_path_owned = input_path.to_owned();
-        let output_dir_owned = output_dir.to_owned();
 
-        let mut tasks = Vec::with_capacity(profiles.len());
+        // Audio renditions need no decoded video, so they get a process of their own
+        let audio_task = (!audio_args.is_empty()).then(|| {
+            let mut args = vec!["-y".to_string(), "-i".to_string(), input_path_owned.clone()];
+            args.extend(audio_args);
+            let track_count = audio_tracks.len();
+            let progress = progress.clone();
+            let usage = usage.clone();
+            progress.report(AUDIO_PROGRESS_KEY, 0.0);
+
+            tokio::spawn(async move {
+                let args: Vec<&str> = args.iter().map(String::as_str).collect();
+                Self::run_ffmpeg_with_progress(&args, duration_secs, &usage, |percent| {
+                    progress.report(AUDIO_PROGRESS_KEY, percent)
+                })
+                .await
+                .context("FFmpeg error for audio renditions")?;
 
-        for &(quality, max_width, max_height, video_bitrate, audio_bitrate) in &profiles {
-            let (target_width, target_height) = Self::calculate_scaled_dimensions(
-                source_width,
-                source_height,
-                max_width,
-                max_height,
-            );
+                log::info!("Generated {} audio renditions", track_count);
+                Ok::<_, anyhow::Error>(())
+            })
+        });
 
+        let mut tasks = Vec::with_capacity(profiles.len());
+
+        for (rendition, target_width, target_height) in profiles {
             let input_path_clone = input_path_owned.clone();
-            let output_dir_clone = output_dir_owned.clone();
 
-            let quality_label = quality.to_string();
+            let rendition_args = if is_passthrough(&rendition) {
+                Passthrough::output_args(segment_format, output_dir, &rendition.name)
+            } else {
+                let mut args = rendition.encoder.args();
+                args.extend([
+                    "-b:v".to_string(),
+                    rendition.video_bitrate.clone(),
+                    "-an".to_string(),
+                    "-vf".to_string(),
+                    format!("scale={}:{}", target_width, target_height),
+                ]);
+                args.extend(output_args(&rendition.name));
+                args
+            };
+            let quality_label = rendition.name;
             let quality_label_for_context = quality_label.clone();
             let quality_label_for_log = quality_label.clone();
-            let video_bitrate_owned = video_bitrate.to_string();
+            let progress = progress.clone();
+            let usage = usage.clone();
+            progress.report(&quality_label, 0.0);
 
             tasks.push(tokio::spawn(async move {
-                let segment_filename = format!("{}/{}_%03d.ts", output_dir_clone, quality_label);
-                let playlist_filename = format!("{}/{}.m3u8", output_dir_clone, quality_label);
-
-                let scale_filter = format!("scale={}:{}", target_width, target_height);
-
-                let output = Command::new("ffmpeg")
-                    .args([
-                        "-i",
-                        &input_path_clone,
-                        "-c:v",
-                        "libx264",
-                        "-c:a",
-                        "aac",
-                        "-b:v",
-                        video_bitrate,
-                        "-b:a",
-                        audio_bitrate,
-                        "-vf",
-                        scale_filter.as_str(),
-                        "-hls_time",
-                        "10",
-                        "-hls_playlist_type",
-                        "vod",
-                        "-hls_segment_filename",
-                        &segment_filename,
-                        "-f",
-                        "hls",
-                        &playlist_filename,
-                        "-y",
-                    ])
-                    .output()
-                    .await
-                    .context(format!(
-                        "Failed to execute FFmpeg command for {} quality",
-                        quality_label_for_context
-                    ))?;
-
-                if !output.status.success() {
-                    let error_msg = String::from_utf8_lossy(&output.stderr);
-                    return Err(anyhow!(
-                        "FFmpeg error for {} quality: {}",
-                        quality_label_for_log,
-                        error_msg
-                    ));
-                }
+                let mut args = vec!["-y", "-i", &input_path_clone];
+                args.extend(rendition_args.iter().map(String::as_str));
+
+                Self::run_ffmpeg_with_progress(&args, duration_secs, &usage, |percent| {
+                    progress.report(&quality_label, percent)
+                })
+                .await
+                .with_context(|| {
+                    format!("FFmpeg error for {} quality", quality_label_for_context)
+                })?;
 
                 log::info!("Generated {} quality stream", quality_label_for_log);
-                Ok((
-                    quality_label,
-                    target_width,
-                    target_height,
-                    video_bitrate_owned,
-                ))
+                Ok::<_, anyhow::Error>((quality_label, target_width, target_height))
             }));
         }
 
@@ -381,71 +1080,720 @@ impl VideoProcessingService {
                 .context("FFmpeg encoding task failed to join")??;
             generated_profiles.push(profile);
         }
+        if let Some(audio_task) = audio_task {
+            audio_task
+                .await
+                .context("FFmpeg audio task failed to join")??;
+        }
 
-        Self::generate_master_playlist(output_dir, &generated_profiles).await?;
+        Self::generate_manifests(output_dir, &generated_profiles, &audio_tracks).await?;
 
-        Ok(generated_profiles.len())
+        Ok((audio_tracks, passthrough.is_some()))
     }
 
-    async fn generate_master_playlist(
-        output_dir: &str,
-        profiles: &[(String, i32, i32, String)],
-    ) -> Result<()> {
-        let mut master_playlist = String::from("#EXTM3U\n#EXT-X-VERSION:3\n\n");
+    /// Check whether the top H.264 rendition can be stream copied from the source, returning why
+    /// not otherwise. The source must already be H.264 in a profile and pixel format players
+    /// decode, at exactly that rung's resolution and not far above its bitrate, with keyframes
+    /// close enough together to cut segments of `HLS_SEGMENT_SECONDS`.
+    async fn plan_passthrough(
+        input_path: &str,
+        media_info: &MediaInfo,
+        profiles: &[(Rendition, i32, i32)],
+    ) -> Result<Passthrough, String> {
+        let (rendition, frame_rate) = Self::passthrough_rendition(media_info, profiles)?;
+
+        let (first_frame, keyframe_times) = probe_keyframe_times(input_path)
+            .await
+            .map_err(|err| format!("failed to probe source keyframes: {:#}", err))?;
+        if keyframe_times
+            .first()
+            .is_none_or(|first| first - first_frame > 0.001)
+        {
+            return Err("source does not start with a keyframe".to_string());
+        }
+        if keyframe_times.len() > MAX_PASSTHROUGH_KEYFRAMES {
+            return Err(format!(
+                "source has {} keyframes, more than {}",
+                keyframe_times.len(),
+                MAX_PASSTHROUGH_KEYFRAMES
+            ));
+        }
+        // The last segment runs from the last keyframe to the end
+        let max_gop_secs = keyframe_times
+            .windows(2)
+            .map(|pair| pair[1] - pair[0])
+            .chain(
+                keyframe_times
+                    .last()
+                    .map(|last| media_info.duration_secs() - last),
+            )
+            .fold(0.0, f64::max);
+        let segment_secs: f64 = HLS_SEGMENT_SECONDS.parse().unwrap_or(GOP_SECONDS);
+        if max_gop_secs > segment_secs {
+            return Err(format!(
+                "source keyframes are up to {:.1}s apart, segments are {}s",
+                max_gop_secs, segment_secs
+            ));
+        }
+
+        log::info!(
+            "Stream copying {} rendition from the H.264 source ({} keyframes, longest GOP {:.1}s)",
+            rendition.name,
+            keyframe_times.len(),
+            max_gop_secs
+        );
+        Ok(Passthrough {
+            rendition: rendition.name.clone(),
+            keyframe_times,
+            max_gop_frames: (max_gop_secs * frame_rate).ceil() as u32,
+        })
+    }
 
-        for (quality, width, height, video_bitrate) in profiles {
-            let bandwidth = video_bitrate.replace('k', "000");
-            master_playlist.push_str(&format!(
-                "#EXT-X-STREAM-INF:BANDWIDTH={},RESOLUTION={}x{}\n{}.m3u8\n\n",
-                bandwidth, width, height, quality
+    /// The top H.264 rendition, with the source frame rate, when the source stream itself
+    /// qualifies for stream copying it; the keyframe checks are left to `plan_passthrough`.
+    fn passthrough_rendition<'a>(
+        media_info: &MediaInfo,
+        profiles: &'a [(Rendition, i32, i32)],
+    ) -> Result<(&'a Rendition, f64), String> {
+        let source = media_info
+            .primary_video()
+            .ok_or("source has no video stream")?;
+        if source.codec != VideoCodec::H264.to_string() {
+            return Err(format!("source codec is {}", source.codec));
+        }
+        let profile = source.profile.as_deref().unwrap_or("unknown");
+        if !PASSTHROUGH_H264_PROFILES.contains(&profile) {
+            return Err(format!("source H.264 profile is {}", profile));
+        }
+        let pixel_format = source.pixel_format.as_deref().unwrap_or("unknown");
+        if !matches!(pixel_format, "yuv420p" | "yuvj420p") {
+            return Err(format!("source pixel format is {}", pixel_format));
+        }
+        if source.rotation != 0 {
+            return Err(format!("source is rotated by {} degrees", source.rotation));
+        }
+        let frame_rate = source.frame_rate.ok_or("source frame rate is unknown")?;
+
+        let (rendition, width, height) = profiles
+            .iter()
+            .find(|(rendition, _, _)| rendition.encoder.codec == VideoCodec::H264)
+            .ok_or("no H.264 rendition is planned")?;
+        if (*width, *height) != (source.width as i32, source.height as i32) {
+            return Err(format!(
+                "{} rendition is {}x{}, source is {}x{}",
+                rendition.name, width, height, source.width, source.height
+            ));
+        }
+        let source_bitrate = source
+            .bit_rate
+            .or(media_info.bit_rate)
+            .ok_or("source bitrate is unknown")?;
+        let rendition_bitrate = parse_bitrate(&rendition.video_bitrate).unwrap_or_default();
+        if source_bitrate as f64 > rendition_bitrate as f64 * PASSTHROUGH_BITRATE_TOLERANCE {
+            return Err(format!(
+                "source bitrate {} exceeds {} rendition bitrate {}",
+                source_bitrate, rendition.name, rendition.video_bitrate
             ));
         }
 
-        fs::write(format!("{}/playlist.m3u8", output_dir), master_playlist)
+        Ok((rendition, frame_rate))
+    }
+
+    /// Encoder options giving every rendition closed GOPs with keyframes at the same timestamps,
+    /// so players can switch renditions on any segment boundary.
+    fn gop_args(frame_rate: Option<f64>) -> Vec<String> {
+        let mut args = vec![
+            "-force_key_frames".to_string(),
+            format!("expr:gte(t,n_forced*{})", GOP_SECONDS),
+            "-sc_threshold".to_string(),
+            "0".to_string(),
+            "-flags".to_string(),
+            "+cgop".to_string(),
+        ];
+        if let Some(frame_rate) = frame_rate {
+            let gop_frames = ((frame_rate * GOP_SECONDS).round() as u32)
+                .max(1)
+                .to_string();
+            args.extend([
+                "-g".to_string(),
+                gop_frames.clone(),
+                "-keyint_min".to_string(),
+                gop_frames,
+            ]);
+        }
+        args
+    }
+
+    /// Decode the source once and encode every rendition from a `split` filter graph in a single
+    /// ffmpeg process. `direct_outputs` are progress keys with output options mapping streams
+    /// straight from the input, such as the audio renditions or a stream copied rendition.
+    async fn encode_single_decode(
+        input_path: &str,
+        profiles: &[(Rendition, i32, i32)],
+        output_args: impl Fn(&str) -> Vec<String>,
+        direct_outputs: &[(String, Vec<String>)],
+        duration_secs: f64,
+        progress: &ProgressReporter,
+        usage: &ProcessUsageTracker,
+    ) -> Result<()> {
+        let mut args: Vec<String> = ["-y", "-i", input_path]
+            .iter()
+            .map(|arg| arg.to_string())
+            .collect();
+        if !profiles.is_empty() {
+            let split_labels: String = (0..profiles.len())
+                .map(|index| format!("[v{}]", index))
+                .collect();
+            let mut filter_graph = format!("[0:v]split={}{}", profiles.len(), split_labels);
+            for (index, (_, width, height)) in profiles.iter().enumerate() {
+                filter_graph.push_str(&format!(
+                    ";[v{}]scale={}:{}[out{}]",
+                    index, width, height, index
+                ));
+            }
+            args.extend(["-filter_complex".to_string(), filter_graph]);
+        }
+        for (index, (rendition, _, _)) in profiles.iter().enumerate() {
+            progress.report(&rendition.name, 0.0);
+            args.extend(["-map".to_string(), format!("[out{}]", index)]);
+            args.extend(rendition.encoder.args());
+            args.extend(["-b:v".to_string(), rendition.video_bitrate.clone()]);
+            args.extend(output_args(&rendition.name));
+        }
+        for (key, output) in direct_outputs {
+            progress.report(key, 0.0);
+            args.extend(output.iter().cloned());
+        }
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+
+        Self::run_ffmpeg_with_progress(&args, duration_secs, usage, |percent| {
+            for (rendition, _, _) in profiles {
+                progress.report(&rendition.name, percent);
+            }
+            for (key, _) in direct_outputs {
+                progress.report(key, percent);
+            }
+        })
+        .await
+        .context("FFmpeg error for single-decode encode")?;
+
+        log::info!(
+            "Generated {} quality streams from a single decode",
+            profiles.len()
+        );
+        Ok(())
+    }
+
+    /// Run ffmpeg with `-progress` on stdout, passing every reported percentage to `on_progress`.
+    async fn run_ffmpeg_with_progress(
+        args: &[&str],
+        duration_secs: f64,
+        usage: &ProcessUsageTracker,
+        on_progress: impl Fn(f64),
+    ) -> Result<()> {
+        let mut child = Command::new("ffmpeg")
+            .args(["-progress", "pipe:1", "-nostats"])
+            .args(args)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            // A job cancelled by the worker drops this future; the encode must not outlive it
+            .kill_on_drop(true)
+            .spawn()
+            .context("Failed to execute FFmpeg command")?;
+        usage.track(child.id());
+
+        // Drain stderr alongside stdout so a chatty ffmpeg never blocks on a full pipe
+        let mut stderr = child.stderr.take().context("FFmpeg stderr not captured")?;
+        let stderr_task = tokio::spawn(async move {
+            let mut buffer = Vec::new();
+            let _ = stderr.read_to_end(&mut buffer).await;
+            buffer
+        });
+
+        let stdout = child.stdout.take().context("FFmpeg stdout not captured")?;
+        let mut lines = BufReader::new(stdout).lines();
+        while let Some(line) = lines
+            .next_line()
             .await
-            .context("Failed to write master playlist")?;
+            .context("Failed to read FFmpeg progress")?
+        {
+            if let Some(percent) = parse_ffmpeg_progress(&line, duration_secs) {
+                on_progress(percent);
+            }
+        }
 
+        let status = child.wait().await.context("Failed to wait for FFmpeg")?;
+        let stderr = stderr_task.await.unwrap_or_default();
+        if !status.success() {
+            return Err(FfmpegError::new("FFmpeg error", &stderr).into());
+        }
+
+        Ok(())
+    }
+
+    /// Write `playlist.m3u8` with bandwidths measured from the rendition segments on disk and
+    /// codecs and frame rate probed from the rendition playlists. Video variants reference the
+    /// audio renditions as one audio group. CMAF output additionally gets a DASH `manifest.mpd`
+    /// over the same segments.
+    async fn generate_manifests(
+        output_dir: &str,
+        profiles: &[(String, i32, i32)],
+        audio_tracks: &[AudioTrack],
+    ) -> Result<()> {
+        let mut audio_renditions = Vec::with_capacity(audio_tracks.len());
+        for track in audio_tracks {
+            let name = track.playlist.trim_end_matches(".m3u8");
+            audio_renditions.push(Self::measure_rendition(output_dir, name).await?);
+        }
+
+        // Variants must declare the codecs and bandwidth of the most demanding audio rendition
+        let audio_bandwidth = audio_renditions
+            .iter()
+            .map(|rendition| rendition.bandwidth)
+            .max()
+            .unwrap_or_default();
+        let audio_average_bandwidth = audio_renditions
+            .iter()
+            .map(|rendition| rendition.average_bandwidth)
+            .max()
+            .unwrap_or_default();
+        let mut audio_codecs: Vec<String> = Vec::new();
+        for codec in audio_renditions
+            .iter()
+            .flat_map(|rendition| &rendition.codecs)
+        {
+            if !audio_codecs.contains(codec) {
+                audio_codecs.push(codec.clone());
+            }
+        }
+        let audio_codecs_known = audio_renditions
+            .iter()
+            .all(|rendition| !rendition.codecs.is_empty());
+        let audio_group = (!audio_tracks.is_empty()).then(|| AUDIO_GROUP_ID.to_string());
+
+        let mut variants = Vec::with_capacity(profiles.len());
+        let mut video_renditions = Vec::with_capacity(profiles.len());
+        for (quality, width, height) in profiles {
+            let rendition = Self::measure_rendition(output_dir, quality).await?;
+
+            let mut codecs = rendition.codecs.clone();
+            if codecs.is_empty() || !audio_codecs_known {
+                codecs.clear();
+            } else {
+                codecs.extend(audio_codecs.iter().cloned());
+            }
+
+            variants.push(VariantStream {
+                uri: format!("{}.m3u8", quality),
+                bandwidth: rendition.bandwidth + audio_bandwidth,
+                average_bandwidth: rendition.average_bandwidth + audio_average_bandwidth,
+                codecs,
+                width: *width,
+                height: *height,
+                frame_rate: rendition.frame_rate,
+                audio_group: audio_group.clone(),
+            });
+            video_renditions.push(rendition);
+        }
+
+        let media_renditions: Vec<AudioRendition> = audio_tracks
+            .iter()
+            .map(|track| AudioRendition {
+                group_id: AUDIO_GROUP_ID.to_string(),
+                name: track.name.clone(),
+                language: track.language.clone(),
+                channels: track.channels,
+                default: track.default,
+                uri: track.playlist.clone(),
+            })
+            .collect();
+
+        fs::write(
+            format!("{}/playlist.m3u8", output_dir),
+            render_master_playlist(&media_renditions, &variants),
+        )
+        .await
+        .context("Failed to write master playlist")?;
         log::info!("Generated master playlist");
+
+        // DASH can only reference fMP4 segments, which are the ones with an init section
+        let all_renditions = || video_renditions.iter().chain(&audio_renditions);
+        if all_renditions().any(|rendition| rendition.playlist.init_uri.is_none()) {
+            return Ok(());
+        }
+
+        let min_buffer_secs = all_renditions()
+            .map(|rendition| rendition.playlist.target_duration)
+            .fold(0.0, f64::max);
+        let representations: Vec<DashRepresentation> = profiles
+            .iter()
+            .zip(&video_renditions)
+            .zip(&variants)
+            .map(
+                |(((quality, width, height), rendition), variant)| DashRepresentation {
+                    id: quality.clone(),
+                    bandwidth: rendition.bandwidth,
+                    codecs: rendition.codecs.clone(),
+                    width: *width,
+                    height: *height,
+                    frame_rate: variant.frame_rate,
+                    init_uri: rendition.playlist.init_uri.clone().unwrap_or_default(),
+                    segments: rendition.playlist.segments.clone(),
+                },
+            )
+            .collect();
+        let audio_representations: Vec<DashAudioRepresentation> = audio_tracks
+            .iter()
+            .zip(&audio_renditions)
+            .map(|(track, rendition)| DashAudioRepresentation {
+                id: track.playlist.trim_end_matches(".m3u8").to_string(),
+                bandwidth: rendition.bandwidth,
+                codecs: rendition.codecs.clone(),
+                language: track.language.clone(),
+                channels: track.channels,
+                default: track.default,
+                init_uri: rendition.playlist.init_uri.clone().unwrap_or_default(),
+                segments: rendition.playlist.segments.clone(),
+            })
+            .collect();
+
+        fs::write(
+            format!("{}/{}", output_dir, DASH_MANIFEST_FILENAME),
+            render_dash_manifest(&representations, &audio_representations, min_buffer_secs),
+        )
+        .await
+        .context("Failed to write DASH manifest")?;
+        log::info!("Generated DASH manifest");
+
         Ok(())
     }
 
-    async fn get_video_dimensions(input_path: &str) -> Result<(i32, i32)> {
+    /// Parse the media playlist of rendition `name`, measure its bitrates from the segment
+    /// sizes and probe its codecs.
+    async fn measure_rendition(output_dir: &str, name: &str) -> Result<MeasuredRendition> {
+        let playlist_path = format!("{}/{}.m3u8", output_dir, name);
+        let content = fs::read_to_string(&playlist_path)
+            .await
+            .with_context(|| format!("Failed to read {} playlist", name))?;
+        let playlist =
+            parse_media_playlist(&content).with_context(|| format!("Invalid {} playlist", name))?;
+        if playlist.segments.is_empty() {
+            return Err(anyhow!("{} playlist has no segments", name));
+        }
+
+        let mut segment_sizes = Vec::with_capacity(playlist.segments.len());
+        for segment in &playlist.segments {
+            let metadata = fs::metadata(format!("{}/{}", output_dir, segment.uri))
+                .await
+                .with_context(|| format!("Failed to read segment {}", segment.uri))?;
+            segment_sizes.push(metadata.len());
+        }
+        let (bandwidth, average_bandwidth) = measure_bitrates(&playlist, &segment_sizes);
+
+        // Probing through the playlist reads the init section of fMP4 segments as well
+        let (codecs, frame_rate) = Self::probe_rendition_codecs(&playlist_path)
+            .await
+            .with_context(|| format!("Failed to probe {} rendition", name))?;
+
+        Ok(MeasuredRendition {
+            playlist,
+            bandwidth,
+            average_bandwidth,
+            codecs,
+            frame_rate,
+        })
+    }
+
+    /// RFC 6381 codec strings and video frame rate of an encoded rendition playlist.
+    async fn probe_rendition_codecs(playlist_path: &str) -> Result<(Vec<String>, Option<f64>)> {
         let output = Command::new("ffprobe")
             .args([
                 "-v",
                 "error",
-                "-select_streams",
-                "v:0",
                 "-show_entries",
-                "stream=width,height",
+                "stream=codec_type,codec_name,profile,level,width,height,avg_frame_rate",
                 "-of",
-                "csv=s=x:p=0",
-                input_path,
+                "json",
+                playlist_path,
             ])
             .output()
             .await
-            .context("Failed to execute FFprobe command for dimensions")?;
+            .context("Failed to execute FFprobe command for rendition codecs")?;
 
         if !output.status.success() {
-            let error_msg = String::from_utf8_lossy(&output.stderr);
-            return Err(anyhow::anyhow!("FFprobe error: {}", error_msg));
+            return Err(FfmpegError::new("FFprobe error", &output.stderr).into());
         }
 
-        let dims = String::from_utf8_lossy(&output.stdout).trim().to_string();
-        let mut parts = dims.split('x');
+        let probe: serde_json::Value =
+            serde_json::from_slice(&output.stdout).context("Failed to parse FFprobe output")?;
+        let streams = probe["streams"].as_array().cloned().unwrap_or_default();
+
+        let mut codecs = Vec::new();
+        let mut frame_rate = None;
+        let mut codecs_complete = true;
+        for stream in &streams {
+            let codec_name = stream["codec_name"].as_str().unwrap_or_default();
+            let profile = stream["profile"].as_str();
+            match stream["codec_type"].as_str() {
+                Some("video") => {
+                    let level = stream["level"].as_i64().unwrap_or_default();
+                    frame_rate = stream["avg_frame_rate"].as_str().and_then(parse_frame_rate);
+                    match video_codec(
+                        codec_name,
+                        profile,
+                        level,
+                        stream["width"].as_i64().unwrap_or_default(),
+                        stream["height"].as_i64().unwrap_or_default(),
+                        frame_rate,
+                    ) {
+                        Some(codec) => codecs.insert(0, codec),
+                        None => {
+                            log::warn!(
+                                "No codec string for video stream {} {:?} level {}",
+                                codec_name,
+                                profile,
+                                level
+                            );
+                            codecs_complete = false;
+                        }
+                    }
+                }
+                Some("audio") => match audio_codec(codec_name, profile) {
+                    Some(codec) => codecs.push(codec),
+                    None => {
+                        log::warn!("No codec string for audio stream {}", codec_name);
+                        codecs_complete = false;
+                    }
+                },
+                _ => {}
+            }
+        }
 
-        let width = parts
-            .next()
-            .ok_or_else(|| anyhow::anyhow!("Missing width in FFprobe output"))?
-            .parse::<i32>()
-            .context("Failed to parse video width")?;
-        let height = parts
-            .next()
-            .ok_or_else(|| anyhow::anyhow!("Missing height in FFprobe output"))?
-            .parse::<i32>()
-            .context("Failed to parse video height")?;
+        // A partial CODECS list is worse than none, as players would reject the missing codec
+        if !codecs_complete {
+            codecs.clear();
+        }
 
-        Ok((width, height))
+        Ok((codecs, frame_rate))
+    }
+
+    /// Audio streams of the source as the audio renditions to encode, downmixed to at most stereo.
+    fn audio_tracks(streams: &[AudioStreamInfo]) -> Vec<AudioTrack> {
+        let default_index = streams
+            .iter()
+            .position(|stream| stream.default)
+            .unwrap_or(0);
+
+        let mut tracks: Vec<AudioTrack> = Vec::with_capacity(streams.len());
+        for (index, stream) in streams.iter().enumerate() {
+            let mut name = stream
+                .title
+                .clone()
+                .or_else(|| stream.language.clone())
+                .unwrap_or_else(|| format!("Audio {}", index + 1));
+            // Names must be unique within the audio group
+            if tracks.iter().any(|track| track.name == name) {
+                name = format!("{} {}", name, index + 1);
+            }
+
+            tracks.push(AudioTrack {
+                index,
+                language: stream.language.clone(),
+                name,
+                channels: if stream.channels == 0 {
+                    2
+                } else {
+                    stream.channels.min(2)
+                },
+                default: index == default_index,
+                playlist: format!("audio_{}.m3u8", index),
+            });
+        }
+
+        tracks
+    }
+
+    /// Store every text subtitle stream of the source as a subtitle track, replacing the tracks
+    /// an earlier run extracted. Returns the number of tracks stored.
+    async fn extract_subtitles(
+        video_id: Uuid,
+        input_path: &str,
+        streams: &[SubtitleStreamInfo],
+        temp_dir: &str,
+        duration_secs: f64,
+        subtitle_service: &dyn SubtitleServiceTrait,
+    ) -> Result<usize> {
+        subtitle_service
+            .delete_embedded_subtitle_tracks(&video_id)
+            .await
+            .context("Failed to remove previously extracted subtitle tracks")?;
+
+        let segment_format = HlsSegmentFormat::from_env();
+
+        let mut stored = 0;
+        for (index, stream) in streams.iter().enumerate() {
+            if !TEXT_SUBTITLE_CODECS.contains(&stream.codec.as_str()) {
+                log::info!(
+                    "Skipping subtitle stream {} with non-text codec {}",
+                    index,
+                    stream.codec
+                );
+                continue;
+            }
+
+            let webvtt_path = format!("{}/subtitles_{}.vtt", temp_dir, index);
+            let map = format!("0:s:{}", index);
+            let output = Command::new("ffmpeg")
+                .args([
+                    "-y",
+                    "-i",
+                    input_path,
+                    "-map",
+                    &map,
+                    "-c:s",
+                    "webvtt",
+                    "-f",
+                    "webvtt",
+                    &webvtt_path,
+                ])
+                .output()
+                .await
+                .context("Failed to execute FFmpeg command for subtitles")?;
+
+            if !output.status.success() {
+                return Err(FfmpegError::new("FFmpeg error", &output.stderr).into());
+            }
+
+            let webvtt = fs::read_to_string(&webvtt_path)
+                .await
+                .context("Failed to read extracted subtitles")?;
+            let cues = match parse_subtitles(&webvtt) {
+                Ok(cues) => cues,
+                Err(err) => {
+                    log::warn!("Skipping subtitle stream {}: {}", index, err);
+                    continue;
+                }
+            };
+
+            let language = stream.language.clone();
+            let name = stream
+                .title
+                .clone()
+                .or_else(|| language.clone())
+                .unwrap_or_else(|| format!("Subtitles {}", index + 1));
+
+            subtitle_service
+                .add_subtitle_track(
+                    &video_id,
+                    NewSubtitleTrack {
+                        language,
+                        name,
+                        source: SubtitleSource::Embedded,
+                        cues,
+                    },
+                    segment_format,
+                    duration_secs,
+                )
+                .await
+                .with_context(|| format!("Failed to store subtitle stream {}", index))?;
+            stored += 1;
+        }
+
+        Ok(stored)
+    }
+
+    /// Output options encoding every source audio stream into its own AAC rendition, normalized
+    /// to the target loudness of the streams that were measured.
+    fn audio_rendition_args(
+        tracks: &[AudioTrack],
+        streams: &[AudioStreamInfo],
+        bitrate: &str,
+        segment_format: HlsSegmentFormat,
+        output_dir: &str,
+    ) -> Vec<String> {
+        let mut args = Vec::new();
+        for track in tracks {
+            args.extend([
+                "-map".to_string(),
+                format!("0:a:{}", track.index),
+                "-c:a".to_string(),
+                "aac".to_string(),
+                "-b:a".to_string(),
+                bitrate.to_string(),
+                "-ac".to_string(),
+                track.channels.to_string(),
+            ]);
+            let stream = streams.get(track.index);
+            if let Some(loudness) = stream.and_then(|stream| stream.loudness.as_ref()) {
+                // loudnorm works at 192 kHz, so resample back to the source rate
+                let sample_rate = stream
+                    .and_then(|stream| stream.sample_rate)
+                    .unwrap_or(48_000);
+                args.extend([
+                    "-af".to_string(),
+                    loudnorm_filter(loudness),
+                    "-ar".to_string(),
+                    sample_rate.to_string(),
+                ]);
+            }
+            args.extend(
+                segment_format.muxer_args(output_dir, track.playlist.trim_end_matches(".m3u8")),
+            );
+        }
+        args
+    }
+
+    /// Renditions to encode with their scaled dimensions, grouped by codec and largest first.
+    /// Rungs that would only upscale are dropped, keeping the smallest of them per codec to
+    /// encode at the source resolution.
+    fn plan_renditions(
+        renditions: &[Rendition],
+        source_width: i32,
+        source_height: i32,
+    ) -> Vec<(Rendition, i32, i32)> {
+        let mut renditions = renditions.to_vec();
+        renditions.sort_by_key(|rendition| {
+            (
+                rendition.encoder.codec,
+                rendition.width as i64 * rendition.height as i64,
+            )
+        });
+
+        let mut planned: Vec<(Rendition, i32, i32)> = Vec::with_capacity(renditions.len());
+        for rendition in renditions {
+            let (width, height) = Self::calculate_scaled_dimensions(
+                source_width,
+                source_height,
+                rendition.width,
+                rendition.height,
+            );
+            let duplicate = planned
+                .last()
+                .is_some_and(|(last, last_width, last_height)| {
+                    last.encoder.codec == rendition.encoder.codec
+                        && width <= *last_width
+                        && height <= *last_height
+                });
+            if duplicate {
+                log::info!(
+                    "Skipping {} rendition, source is only {}x{}",
+                    rendition.name,
+                    source_width,
+                    source_height
+                );
+                continue;
+            }
+            planned.push((rendition, width, height));
+        }
+
+        // Master playlists list H.264 first, and the highest quality first within each codec
+        planned.sort_by_key(|(rendition, width, height)| {
+            (
+                rendition.encoder.codec,
+                std::cmp::Reverse(*width as i64 * *height as i64),
+            )
+        });
+        planned
     }
 
     fn calculate_scaled_dimensions(
@@ -515,67 +1863,99 @@ impl VideoProcessingService {
     //     Ok(())
     // }
 
-    async fn generate_thumbnail(input_path: &str, thumbnail_path: &str) -> Result<()> {
-        if let Some(parent) = Path::new(thumbnail_path).parent() {
-            fs::create_dir_all(parent)
-                .await
-                .context("Failed to create thumbnail directory")?;
-        }
+    /// Sprite sheets of scrubbing thumbnails plus the WebVTT storyboard referencing them,
+    /// written to `output_dir`.
+    async fn generate_trickplay(
+        input_path: &str,
+        output_dir: &str,
+        duration_secs: f64,
+        interval_secs: u32,
+    ) -> Result<()> {
+        fs::create_dir_all(output_dir)
+            .await
+            .context("Failed to create trick-play directory")?;
 
+        let sprite_pattern = format!("{}/sprite_%03d.jpg", output_dir);
         let output = Command::new("ffmpeg")
             .args([
                 "-i",
                 input_path,
-                "-ss",
-                "00:00:01",
-                "-vframes",
-                "1",
-                "-q:v",
-                "2",
+                "-an",
+                "-sn",
                 "-vf",
-                THUMBNAIL_FILTER,
+                &trickplay_filter(interval_secs),
+                "-q:v",
+                "5",
                 "-y",
-                thumbnail_path,
+                &sprite_pattern,
             ])
             .output()
             .await
-            .context("Failed to execute FFmpeg thumbnail command")?;
+            .context("Failed to execute FFmpeg trick-play command")?;
 
         if !output.status.success() {
-            let error_msg = String::from_utf8_lossy(&output.stderr);
-            return Err(anyhow::anyhow!("FFmpeg thumbnail error: {}", error_msg));
+            return Err(FfmpegError::new("FFmpeg trick-play error", &output.stderr).into());
         }
 
-        log::info!("Generated thumbnail: {}", thumbnail_path);
+        let mut sprites = Vec::new();
+        let mut entries = fs::read_dir(output_dir)
+            .await
+            .context("Failed to read trick-play directory")?;
+        while let Some(entry) = entries.next_entry().await? {
+            let filename = entry.file_name().to_string_lossy().to_string();
+            if filename.starts_with("sprite_") && filename.ends_with(".jpg") {
+                sprites.push(filename);
+            }
+        }
+        if sprites.is_empty() {
+            return Err(anyhow!("FFmpeg produced no trick-play sprite sheets"));
+        }
+        // The zero-padded sequence numbers sort in the order FFmpeg wrote the sheets
+        sprites.sort();
+
+        fs::write(
+            format!("{}/{}", output_dir, TRICKPLAY_STORYBOARD_FILENAME),
+            render_storyboard(duration_secs, interval_secs, &sprites),
+        )
+        .await
+        .context("Failed to write storyboard")?;
+
+        log::info!(
+            "Generated {} trick-play sprite sheets in {}",
+            sprites.len(),
+            output_dir
+        );
         Ok(())
     }
 
-    async fn get_video_duration(input_path: &str) -> Result<i32> {
-        let output = Command::new("ffprobe")
-            .args([
-                "-v",
-                "quiet",
-                "-show_entries",
-                "format=duration",
-                "-of",
-                "csv=p=0",
-                input_path,
-            ])
+    /// Silent MP4 and animated WebP preview stitched from short clips across the video,
+    /// written to `output_dir`.
+    async fn generate_preview(
+        input_path: &str,
+        output_dir: &str,
+        duration_secs: f64,
+    ) -> Result<()> {
+        fs::create_dir_all(output_dir)
+            .await
+            .context("Failed to create preview directory")?;
+
+        let clips = preview_clips(duration_secs);
+        let output = Command::new("ffmpeg")
+            .args(preview_args(input_path, &clips, output_dir))
             .output()
             .await
-            .context("Failed to execute FFprobe command")?;
+            .context("Failed to execute FFmpeg preview command")?;
 
         if !output.status.success() {
-            let error_msg = String::from_utf8_lossy(&output.stderr);
-            return Err(anyhow::anyhow!("FFprobe error: {}", error_msg));
+            return Err(FfmpegError::new("FFmpeg preview error", &output.stderr).into());
         }
 
-        let duration_str = String::from_utf8_lossy(&output.stdout).trim().to_string();
-        let duration: f64 = duration_str
-            .parse()
-            .context("Failed to parse video duration")?;
-
-        Ok(duration as i32)
+        log::info!(
+            "Generated animated preview from {} clips in {}",
+            clips.len(),
+            output_dir
+        );
+        Ok(())
     }
 
     async fn upload_hls_files_to_storage(
@@ -599,11 +1979,8 @@ impl VideoProcessingService {
 
                 let storage_path = format!("{}{}", storage_output_dir, filename);
 
-                let file_data = fs::read(&local_path)
-                    .await
-                    .context(format!("Failed to read {} file", filename))?;
                 storage_service
-                    .upload_file_data(file_data, &storage_path)
+                    .upload_file(&local_path.to_string_lossy(), &storage_path)
                     .await
                     .context(format!("Failed to upload {} to storage", filename))?;
 
@@ -615,24 +1992,23 @@ impl VideoProcessingService {
         Ok(uploaded_files)
     }
 
-    pub fn validate_video_file(filename: &str, file_size: u64) -> Result<()> {
-        let extension = filename.split('.').last().unwrap_or("").to_lowercase();
-        let supported_formats = ["mp4", "mov", "avi", "mkv", "webm", "flv", "wmv", "m4v"];
-
-        if !supported_formats.contains(&extension.as_str()) {
-            return Err(anyhow::anyhow!(
-                "Unsupported video format: {}. Supported formats: {}",
-                extension,
-                supported_formats.join(", ")
-            ));
-        }
+    /// Upload size limit, configurable through `MAX_FILE_SIZE` (bytes, defaults to 2 GB).
+    pub fn max_file_size() -> u64 {
+        std::env::var("MAX_FILE_SIZE")
+            .ok()
+            .and_then(|value| value.trim().parse().ok())
+            .unwrap_or(DEFAULT_MAX_FILE_SIZE)
+    }
 
-        const MAX_FILE_SIZE: u64 = 2 * 1024 * 1024 * 1024;
-        if file_size > MAX_FILE_SIZE {
+    /// Size checks that can run before any bytes arrive; the content is checked by
+    /// `validate_upload` once the file is complete.
+    pub fn validate_video_file_size(file_size: u64) -> Result<()> {
+        let max_file_size = Self::max_file_size();
+        if file_size > max_file_size {
             return Err(anyhow::anyhow!(
                 "File size too large: {} MB. Maximum allowed: {} MB",
                 file_size / 1024 / 1024,
-                MAX_FILE_SIZE / 1024 / 1024
+                max_file_size / 1024 / 1024
             ));
         }
 
@@ -669,7 +2045,6 @@ impl VideoProcessingServiceTrait for VideoProcessingService {
     async fn process_video(
         &self,
         video_id: Uuid,
-        video_data: Vec<u8>,
         filename: &str,
         benchmark_run_id: Option<Uuid>,
     ) -> Result<()> {
@@ -678,36 +2053,62 @@ impl VideoProcessingServiceTrait for VideoProcessingService {
             .await
             .context("Failed to update video status to processing")?;
 
-        let output_dir = self.storage_service.get_hls_path(&video_id);
-        let thumbnail_path = self.storage_service.get_thumbnail_path(&video_id);
-
-        let mut processing_run_id = benchmark_run_id;
-        if processing_run_id.is_none() {
-            let metadata = json!({
-                "video_id": video_id,
-                "filename": filename,
-                "payload_bytes": video_data.len(),
-            });
-            match self
-                .metrics_service
-                .create_benchmark_run("video_processing", Some(metadata))
-                .await
-            {
-                Ok(run_id) => processing_run_id = Some(run_id),
-                Err(err) => log::warn!("Unable to create benchmark run for {}: {}", video_id, err),
-            }
+        match self
+            .job_queue_service
+            .enqueue(video_id, filename, benchmark_run_id)
+            .await
+            .context("Failed to enqueue processing job")?
+        {
+            Some(job_id) => log::info!("Queued processing job {} for {}", job_id, video_id),
+            None => log::info!("Processing job for {} is already pending", video_id),
         }
 
-        log::info!("process_video: Creating temp directory for processing");
+        Ok(())
+    }
+
+    async fn run_job(&self, job: &ProcessingJob) -> Result<()> {
+        let video_id = job.video_id;
+        let output_dir = self.storage_service.get_hls_path(&video_id);
+        let thumbnails_dir = self.storage_service.get_thumbnails_path(&video_id);
+
+        let video = self
+            .video_service
+            .get_video_by_id(&video_id)
+            .await
+            .context("Failed to load video")?
+            .ok_or_else(|| anyhow!("Video {} no longer exists", video_id))?;
+        let (ladder_name, renditions) = self
+            .encoding_ladders
+            .resolve(video.encoding_ladder.as_deref());
+        log::info!("run_job: Using encoding ladder '{}'", ladder_name);
+        let loudness_target = self.encoding_ladders.loudness_target(ladder_name);
+        let renditions = self.video_encoders.expand_renditions(
+            renditions,
+            HlsSegmentFormat::from_env() == HlsSegmentFormat::Fmp4,
+        )?;
+
+        log::info!("run_job: Creating temp directory for processing");
         let temp_dir = format!("/tmp/video_processing/{}", video_id);
         let temp_dir_timer = Instant::now();
+        // Start from a clean directory in case an earlier attempt died halfway
+        if fs::metadata(&temp_dir).await.is_ok() {
+            fs::remove_dir_all(&temp_dir)
+                .await
+                .context(FailedStep::new(
+                    "create_temp_dir",
+                    "Failed to remove stale temp directory",
+                ))?;
+        }
         fs::create_dir_all(&temp_dir)
             .await
-            .context("Failed to create temp directory")?;
+            .context(FailedStep::new(
+                "create_temp_dir",
+                "Failed to create temp directory",
+            ))?;
         if let Err(err) = self
             .metrics_service
             .record_video_processing_step(
-                processing_run_id,
+                job.benchmark_run_id,
                 Some(video_id),
                 "create_temp_dir",
                 Some(temp_dir_timer.elapsed().as_millis() as i64),
@@ -723,90 +2124,259 @@ impl VideoProcessingServiceTrait for VideoProcessingService {
             );
         }
 
-        log::info!("process_video: Created temp directory: {}", temp_dir);
+        log::info!("run_job: Created temp directory: {}", temp_dir);
 
-        let local_input_path = format!("{}/{}", temp_dir, filename);
-        let write_timer = Instant::now();
-        fs::write(&local_input_path, &video_data)
-            .await
-            .context("Failed to write video data to temp file")?;
+        // Earlier attempts may have left progress behind
         if let Err(err) = self
-            .metrics_service
-            .record_video_processing_step(
-                processing_run_id,
-                Some(video_id),
-                "write_input_file",
-                Some(write_timer.elapsed().as_millis() as i64),
-                None,
-                None,
-            )
+            .video_service
+            .update_processing_progress(&video_id, 0.0, json!({}))
             .await
         {
             log::warn!(
-                "Failed to record write_input_file metric for {}: {}",
+                "Failed to reset processing progress for {}: {}",
                 video_id,
                 err
             );
         }
 
-        let video_service_clone = Arc::clone(&self.video_service);
-        let storage_service_clone = Arc::clone(&self.storage_service);
-        let metrics_service_clone = Arc::clone(&self.metrics_service);
-        let processing_run_id_clone = processing_run_id;
+        let result = async {
+            let local_input_path = format!("{}/{}", temp_dir, job.filename);
+            let download_timer = Instant::now();
+            self.storage_service
+                .download_file(
+                    &self
+                        .storage_service
+                        .get_video_path(&video_id, &job.filename),
+                    &local_input_path,
+                )
+                .await
+                .context(FailedStep::new(
+                    "download_original_video",
+                    "Failed to download original video",
+                ))?;
+            let download_ms = download_timer.elapsed().as_millis() as i64;
+
+            let mut processing_run_id = job.benchmark_run_id;
+            if processing_run_id.is_none() {
+                let payload_bytes = fs::metadata(&local_input_path)
+                    .await
+                    .map(|metadata| metadata.len())
+                    .ok();
+                let metadata = json!({
+                    "video_id": video_id,
+                    "filename": job.filename,
+                    "payload_bytes": payload_bytes,
+                    "job_id": job.id,
+                    "attempt": job.attempts,
+                });
+                match self
+                    .metrics_service
+                    .create_benchmark_run("video_processing", Some(metadata))
+                    .await
+                {
+                    Ok(run_id) => processing_run_id = Some(run_id),
+                    Err(err) => {
+                        log::warn!("Unable to create benchmark run for {}: {}", video_id, err)
+                    }
+                }
+            }
 
-        tokio::spawn(async move {
-            let processing_video_service = Arc::clone(&video_service_clone);
-            let processing_storage_service = Arc::clone(&storage_service_clone);
-            let processing_metrics_service = Arc::clone(&metrics_service_clone);
-            match VideoProcessingService::process_video_background(
+            if let Err(err) = self
+                .metrics_service
+                .record_video_processing_step(
+                    processing_run_id,
+                    Some(video_id),
+                    "download_original_video",
+                    Some(download_ms),
+                    None,
+                    None,
+                )
+                .await
+            {
+                log::warn!(
+                    "Failed to record download_original_video metric for {}: {}",
+                    video_id,
+                    err
+                );
+            }
+
+            VideoProcessingService::process_video_background(
                 video_id,
                 local_input_path,
-                temp_dir,
+                temp_dir.clone(),
                 output_dir,
-                thumbnail_path,
-                processing_video_service,
-                processing_storage_service,
-                processing_metrics_service.clone(),
-                processing_run_id_clone,
+                thumbnails_dir,
+                Arc::clone(&self.video_service),
+                Arc::clone(&self.storage_service),
+                Arc::clone(&self.metrics_service),
+                Arc::clone(&self.subtitle_service),
+                processing_run_id,
+                renditions,
+                loudness_target,
             )
             .await
-            {
-                Ok(_) => {
-                    log::info!(
-                        "✅ Video processing completed successfully for {}",
-                        video_id
-                    );
-                }
-                Err(e) => {
-                    log::error!("❌ Video processing failed for {}: {}", video_id, e);
-                    log::error!("🔍 Error details: {:?}", e);
-                    if let Err(err) = processing_metrics_service
-                        .record_video_processing_step(
-                            processing_run_id_clone,
-                            Some(video_id),
-                            "processing_failed",
-                            None,
-                            None,
-                            None,
-                        )
-                        .await
-                    {
-                        log::warn!(
-                            "Failed to record processing_failed metric for {}: {}",
-                            video_id,
-                            err
-                        );
-                    }
-                    if let Err(update_err) = video_service_clone
-                        .update_video_status(&video_id, VideoStatus::Failed)
-                        .await
-                    {
-                        log::error!("❌ Failed to update video status to failed: {}", update_err);
-                    }
-                }
+        }
+        .await;
+
+        if result.is_err() {
+            if let Err(e) = fs::remove_dir_all(&temp_dir).await {
+                log::warn!("Failed to clean up temp directory {}: {}", temp_dir, e);
             }
-        });
+        }
 
-        Ok(())
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{DynamicRange, VideoStreamInfo};
+
+    fn rung(name: &str, width: i32, height: i32, video_bitrate: &str) -> Rendition {
+        Rendition {
+            name: name.to_string(),
+            width,
+            height,
+            video_bitrate: video_bitrate.to_string(),
+            audio_bitrate: "128k".to_string(),
+            codecs: vec![VideoCodec::H264],
+            codec_bitrates: Default::default(),
+            encoder: Default::default(),
+        }
+    }
+
+    fn standard_ladder() -> Vec<Rendition> {
+        vec![
+            rung("1080p", 1920, 1080, "2000k"),
+            rung("720p", 1280, 720, "1000k"),
+            rung("480p", 854, 480, "500k"),
+            rung("360p", 640, 360, "250k"),
+        ]
+    }
+
+    fn planned(source_width: i32, source_height: i32) -> Vec<(String, i32, i32)> {
+        VideoProcessingService::plan_renditions(&standard_ladder(), source_width, source_height)
+            .into_iter()
+            .map(|(rendition, width, height)| (rendition.name, width, height))
+            .collect()
+    }
+
+    #[test]
+    fn plans_no_upscaled_renditions_for_480p_source() {
+        assert_eq!(
+            planned(854, 480),
+            [
+                ("480p".to_string(), 854, 480),
+                ("360p".to_string(), 640, 360)
+            ]
+        );
+    }
+
+    #[test]
+    fn plans_portrait_source_within_each_box() {
+        assert_eq!(
+            planned(1080, 1920),
+            [
+                ("1080p".to_string(), 608, 1080),
+                ("720p".to_string(), 404, 720),
+                ("480p".to_string(), 270, 480),
+                ("360p".to_string(), 202, 360)
+            ]
+        );
+    }
+
+    fn source(codec: &str, width: u32, height: u32, bit_rate: u64) -> MediaInfo {
+        MediaInfo {
+            container: "mov,mp4,m4a,3gp,3g2,mj2".to_string(),
+            duration_ms: 60_000,
+            bit_rate: Some(bit_rate + 128_000),
+            size: None,
+            video: vec![VideoStreamInfo {
+                codec: codec.to_string(),
+                profile: Some("High".to_string()),
+                width,
+                height,
+                frame_rate: Some(30.0),
+                bit_rate: Some(bit_rate),
+                pixel_format: Some("yuv420p".to_string()),
+                rotation: 0,
+                color_space: None,
+                color_transfer: None,
+                color_primaries: None,
+                color_range: None,
+                dynamic_range: DynamicRange::Sdr,
+            }],
+            audio: vec![AudioStreamInfo {
+                codec: "aac".to_string(),
+                profile: Some("LC".to_string()),
+                channels: 2,
+                channel_layout: Some("stereo".to_string()),
+                sample_rate: Some(48_000),
+                bit_rate: Some(128_000),
+                language: None,
+                title: None,
+                default: true,
+                loudness: None,
+            }],
+            subtitles: Vec::new(),
+        }
+    }
+
+    fn passthrough_rendition(media_info: &MediaInfo) -> Result<(String, f64), String> {
+        let video = media_info.primary_video().unwrap();
+        let profiles = VideoProcessingService::plan_renditions(
+            &standard_ladder(),
+            video.width as i32,
+            video.height as i32,
+        );
+        VideoProcessingService::passthrough_rendition(media_info, &profiles)
+            .map(|(rendition, frame_rate)| (rendition.name.clone(), frame_rate))
+    }
+
+    #[test]
+    fn keeps_smallest_rung_at_source_resolution() {
+        assert_eq!(planned(320, 180), [("360p".to_string(), 320, 180)]);
+        assert_eq!(planned(180, 320), [("360p".to_string(), 180, 320)]);
+    }
+
+    #[test]
+    fn stream_copies_top_rung_of_compatible_source() {
+        assert_eq!(
+            passthrough_rendition(&source("h264", 1920, 1080, 2_500_000)),
+            Ok(("1080p".to_string(), 30.0))
+        );
+        assert_eq!(
+            passthrough_rendition(&source("h264", 854, 480, 600_000)),
+            Ok(("480p".to_string(), 30.0))
+        );
+    }
+
+    #[test]
+    fn transcodes_incompatible_source() {
+        assert_eq!(
+            passthrough_rendition(&source("hevc", 1920, 1080, 2_500_000)),
+            Err("source codec is hevc".to_string())
+        );
+
+        let mut high_10 = source("h264", 1920, 1080, 2_500_000);
+        high_10.video[0].profile = Some("High 10".to_string());
+        assert_eq!(
+            passthrough_rendition(&high_10),
+            Err("source H.264 profile is High 10".to_string())
+        );
+
+        assert_eq!(
+            passthrough_rendition(&source("h264", 1920, 1080, 8_000_000)),
+            Err("source bitrate 8000000 exceeds 1080p rendition bitrate 2000k".to_string())
+        );
+    }
+
+    #[test]
+    fn transcodes_source_above_top_rung() {
+        assert_eq!(
+            passthrough_rendition(&source("h264", 3840, 2160, 2_500_000)),
+            Err("1080p rendition is 1920x1080, source is 3840x2160".to_string())
+        );
     }
 }
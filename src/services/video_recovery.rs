@@ -0,0 +1,167 @@
+use anyhow::{Context, Result};
+use std::env;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::models::{FailureCategory, ProcessingFailure, Video};
+use crate::services::{
+    CloudStorageService, MetricsServiceTrait, VideoProcessingServiceTrait, VideoServiceTrait,
+};
+
+const DEFAULT_STALE_AFTER_SECONDS: u64 = 60 * 60;
+const DEFAULT_INTERVAL_SECONDS: u64 = 5 * 60;
+
+#[derive(Debug, Default)]
+pub struct VideoRecoverySummary {
+    pub resumed: usize,
+    pub failed: usize,
+}
+
+/// Finds videos left in `uploading`/`processing` after a crash and either resumes processing
+/// from the stored original or marks them failed.
+pub struct VideoRecoveryService {
+    video_service: Arc<dyn VideoServiceTrait>,
+    storage_service: Arc<dyn CloudStorageService>,
+    video_processing_service: Arc<dyn VideoProcessingServiceTrait>,
+    metrics_service: Arc<dyn MetricsServiceTrait>,
+    stale_after: Duration,
+    interval: Duration,
+}
+
+impl VideoRecoveryService {
+    pub fn new(
+        video_service: Arc<dyn VideoServiceTrait>,
+        storage_service: Arc<dyn CloudStorageService>,
+        video_processing_service: Arc<dyn VideoProcessingServiceTrait>,
+        metrics_service: Arc<dyn MetricsServiceTrait>,
+    ) -> Self {
+        let env_secs = |name: &str, default: u64| {
+            env::var(name)
+                .ok()
+                .and_then(|value| value.trim().parse().ok())
+                .unwrap_or(default)
+        };
+
+        Self {
+            video_service,
+            storage_service,
+            video_processing_service,
+            metrics_service,
+            stale_after: Duration::from_secs(env_secs(
+                "VIDEO_RECOVERY_STALE_SECONDS",
+                DEFAULT_STALE_AFTER_SECONDS,
+            )),
+            interval: Duration::from_secs(env_secs(
+                "VIDEO_RECOVERY_INTERVAL_SECONDS",
+                DEFAULT_INTERVAL_SECONDS,
+            )),
+        }
+    }
+
+    /// Reconcile once at startup and then every `VIDEO_RECOVERY_INTERVAL_SECONDS`.
+    pub async fn run(self) {
+        let mut interval = tokio::time::interval(self.interval);
+        loop {
+            interval.tick().await;
+            match self.reconcile().await {
+                Ok(summary) if summary.resumed + summary.failed > 0 => log::info!(
+                    "Recovered stale videos: {} resumed, {} failed",
+                    summary.resumed,
+                    summary.failed
+                ),
+                Ok(_) => {}
+                Err(err) => log::warn!("Failed to reconcile stale videos: {}", err),
+            }
+        }
+    }
+
+    pub async fn reconcile(&self) -> Result<VideoRecoverySummary> {
+        let videos = self
+            .video_service
+            .list_stale_videos(self.stale_after.as_secs() as i64)
+            .await
+            .context("Failed to list stale videos")?;
+
+        let mut summary = VideoRecoverySummary::default();
+        for video in videos {
+            match self.recover_video(&video).await {
+                Ok(true) => summary.resumed += 1,
+                Ok(false) => summary.failed += 1,
+                Err(err) => log::warn!("Failed to recover video {}: {:#}", video.id, err),
+            }
+        }
+
+        Ok(summary)
+    }
+
+    /// Returns `true` when processing was resumed and `false` when the video was marked failed.
+    async fn recover_video(&self, video: &Video) -> Result<bool> {
+        let video_path = self
+            .storage_service
+            .get_video_path(&video.id, &video.filename);
+        let hls_path = self.storage_service.get_hls_path(&video.id);
+
+        let original_exists = self
+            .storage_service
+            .file_exists(&video_path)
+            .await
+            .context("Failed to check stored original")?;
+        let partial_output_exists = self
+            .storage_service
+            .file_exists(&format!("{}playlist.m3u8", hls_path))
+            .await
+            .context("Failed to check HLS output")?;
+
+        log::warn!(
+            "Video {} stuck in {} since {:?} (original stored: {}, HLS output: {})",
+            video.id,
+            video.get_status(),
+            video.updated_at,
+            original_exists,
+            partial_output_exists
+        );
+
+        // Whatever a crashed run left behind is regenerated or no longer needed
+        if let Err(err) = self.storage_service.delete_folder(&hls_path).await {
+            log::warn!(
+                "Failed to remove partial HLS output for {}: {}",
+                video.id,
+                err
+            );
+        }
+
+        if original_exists {
+            self.video_processing_service
+                .process_video(video.id, &video.filename, None)
+                .await
+                .context("Failed to resume processing")?;
+            self.record_step(video, "recovery_resumed").await;
+            return Ok(true);
+        }
+
+        let failure = ProcessingFailure::new(
+            FailureCategory::StorageError,
+            format!(
+                "Original upload is missing from storage after the video was stuck in {}",
+                video.get_status()
+            ),
+            0,
+        );
+        self.video_service
+            .mark_video_failed(&video.id, &failure)
+            .await
+            .context("Failed to mark video failed")?;
+        self.record_step(video, "recovery_failed").await;
+        Ok(false)
+    }
+
+    async fn record_step(&self, video: &Video, step: &str) {
+        if let Err(err) = self
+            .metrics_service
+            .record_video_processing_step(None, Some(video.id), step, None, None, None)
+            .await
+        {
+            log::warn!("Failed to record {} metric for {}: {}", step, video.id, err);
+        }
+    }
+}
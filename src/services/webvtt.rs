@@ -0,0 +1,397 @@
+use anyhow::{anyhow, Context, Result};
+
+/// One WebVTT cue, with times in seconds on the video timeline.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cue {
+    pub start: f64,
+    pub end: f64,
+    /// Cue settings after the end time, such as `line:0 align:start`
+    pub settings: String,
+    pub text: String,
+}
+
+/// Cues of a subtitle file, which is WebVTT when it starts with `WEBVTT` and SRT otherwise.
+/// Rendering the cues back with `render_webvtt` converts SRT to WebVTT.
+pub fn parse_subtitles(content: &str) -> Result<Vec<Cue>> {
+    let content = normalize(content);
+    if content.starts_with("WEBVTT") {
+        parse_webvtt(&content)
+    } else {
+        parse_srt(&content)
+    }
+}
+
+fn parse_srt(content: &str) -> Result<Vec<Cue>> {
+    let mut cues = Vec::new();
+
+    for block in content
+        .split("\n\n")
+        .filter(|block| !block.trim().is_empty())
+    {
+        let mut lines = block.lines().skip_while(|line| line.trim().is_empty());
+        let mut timing = lines.next().unwrap_or_default();
+        // The counter line is optional in practice
+        if !timing.contains("-->") {
+            timing = lines.next().unwrap_or_default();
+        }
+        let (start, end, _) = parse_timing(timing)
+            .with_context(|| format!("Invalid SRT cue timing: {}", timing.trim()))?;
+
+        let text = lines
+            .map(|line| strip_srt_formatting(line).replace("-->", "->"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        cues.push(Cue {
+            start,
+            end,
+            settings: String::new(),
+            text,
+        });
+    }
+
+    if cues.is_empty() {
+        return Err(anyhow!("Subtitle file contains no cues"));
+    }
+    Ok(cues)
+}
+
+fn parse_webvtt(content: &str) -> Result<Vec<Cue>> {
+    let mut blocks = content
+        .split("\n\n")
+        .filter(|block| !block.trim().is_empty());
+    // The first block is the `WEBVTT` header along with any header lines
+    blocks.next();
+
+    let mut cues = Vec::new();
+    for block in blocks {
+        let block = block.trim_start_matches('\n');
+        if ["NOTE", "STYLE", "REGION"]
+            .iter()
+            .any(|keyword| block.starts_with(keyword))
+        {
+            continue;
+        }
+
+        let mut lines = block.lines();
+        let mut timing = lines.next().unwrap_or_default();
+        // Cue identifiers are dropped; segments only need the timing and payload
+        if !timing.contains("-->") {
+            timing = lines.next().unwrap_or_default();
+        }
+        let (start, end, settings) = parse_timing(timing)
+            .with_context(|| format!("Invalid WebVTT cue timing: {}", timing.trim()))?;
+
+        cues.push(Cue {
+            start,
+            end,
+            settings,
+            text: lines.collect::<Vec<_>>().join("\n"),
+        });
+    }
+
+    if cues.is_empty() {
+        return Err(anyhow!("Subtitle file contains no cues"));
+    }
+    Ok(cues)
+}
+
+/// Strip a byte order mark and normalize line endings.
+fn normalize(content: &str) -> String {
+    content
+        .trim_start_matches('\u{feff}')
+        .replace("\r\n", "\n")
+        .replace('\r', "\n")
+}
+
+/// SRT styling WebVTT has no equivalent for: `<font>` tags and ASS overrides such as `{\an8}`.
+fn strip_srt_formatting(line: &str) -> String {
+    let mut stripped = String::with_capacity(line.len());
+    let mut rest = line;
+    while let Some(index) = rest.find(['<', '{']) {
+        stripped.push_str(&rest[..index]);
+        let tail = &rest[index..];
+        let skip = if tail.starts_with("{\\") {
+            tail.find('}').map(|end| end + 1)
+        } else if tail.to_lowercase().starts_with("<font")
+            || tail.to_lowercase().starts_with("</font")
+        {
+            tail.find('>').map(|end| end + 1)
+        } else {
+            None
+        };
+        match skip {
+            Some(skip) => rest = &tail[skip..],
+            None => {
+                stripped.push_str(&tail[..1]);
+                rest = &tail[1..];
+            }
+        }
+    }
+    stripped.push_str(rest);
+    stripped
+}
+
+/// Start, end and cue settings of a `00:00:01,000 --> 00:00:04,000` timing line.
+fn parse_timing(line: &str) -> Option<(f64, f64, String)> {
+    let (start, rest) = line.split_once("-->")?;
+    let mut rest = rest.split_whitespace();
+    let start = parse_timestamp(start.trim())?;
+    let end = parse_timestamp(rest.next()?)?;
+    if end < start {
+        return None;
+    }
+    Some((start, end, rest.collect::<Vec<_>>().join(" ")))
+}
+
+/// Seconds from `HH:MM:SS.mmm` or `MM:SS.mmm`, with `,` accepted as the SRT decimal separator.
+fn parse_timestamp(timestamp: &str) -> Option<f64> {
+    let timestamp = timestamp.replace(',', ".");
+    let parts: Vec<&str> = timestamp.split(':').collect();
+    let (hours, minutes, seconds) = match parts.as_slice() {
+        [hours, minutes, seconds] => (hours.parse::<u64>().ok()?, *minutes, *seconds),
+        [minutes, seconds] => (0, *minutes, *seconds),
+        _ => return None,
+    };
+    let minutes = minutes.parse::<u64>().ok()?;
+    let seconds = seconds.parse::<f64>().ok()?;
+    if minutes >= 60 || !(0.0..60.0).contains(&seconds) {
+        return None;
+    }
+    Some((hours * 3600 + minutes * 60) as f64 + seconds)
+}
+
+pub(crate) fn format_timestamp(seconds: f64) -> String {
+    let millis = (seconds * 1000.0).round() as u64;
+    format!(
+        "{:02}:{:02}:{:02}.{:03}",
+        millis / 3_600_000,
+        millis / 60_000 % 60,
+        millis / 1000 % 60,
+        millis % 1000
+    )
+}
+
+/// WebVTT file with the given cues. `mpegts_offset` adds the `X-TIMESTAMP-MAP` header HLS
+/// segments need to line cue times up with the media timestamps.
+pub fn render_webvtt(cues: &[Cue], mpegts_offset: Option<u64>) -> String {
+    let mut webvtt = String::from("WEBVTT\n");
+    if let Some(offset) = mpegts_offset {
+        webvtt.push_str(&format!(
+            "X-TIMESTAMP-MAP=MPEGTS:{},LOCAL:00:00:00.000\n",
+            offset
+        ));
+    }
+
+    for cue in cues {
+        let settings = if cue.settings.is_empty() {
+            String::new()
+        } else {
+            format!(" {}", cue.settings)
+        };
+        webvtt.push_str(&format!(
+            "\n{} --> {}{}\n{}\n",
+            format_timestamp(cue.start),
+            format_timestamp(cue.end),
+            settings,
+            cue.text
+        ));
+    }
+
+    webvtt
+}
+
+/// Split cues into HLS WebVTT segments of `segment_secs` covering `duration_secs`, returned as
+/// (segment duration, segment file). A cue spanning a boundary is repeated in every segment it
+/// overlaps, as players drop the duplicates.
+pub fn segment_webvtt(
+    cues: &[Cue],
+    duration_secs: f64,
+    segment_secs: f64,
+    mpegts_offset: u64,
+) -> Vec<(f64, String)> {
+    let last_cue_end = cues.iter().map(|cue| cue.end).fold(0.0, f64::max);
+    let duration_secs = duration_secs.max(last_cue_end);
+    let segment_count = ((duration_secs / segment_secs).ceil() as usize).max(1);
+
+    (0..segment_count)
+        .map(|index| {
+            let start = index as f64 * segment_secs;
+            let end = if index + 1 == segment_count {
+                duration_secs.max(start + 0.001)
+            } else {
+                start + segment_secs
+            };
+            let segment_cues: Vec<Cue> = cues
+                .iter()
+                .filter(|cue| cue.start < end && cue.end > start)
+                .cloned()
+                .collect();
+            (
+                end - start,
+                render_webvtt(&segment_cues, Some(mpegts_offset)),
+            )
+        })
+        .collect()
+}
+
+/// VOD media playlist listing WebVTT segments as (duration, uri).
+pub fn render_subtitle_playlist(segments: &[(f64, String)]) -> String {
+    let target_duration = segments
+        .iter()
+        .map(|(duration, _)| duration.ceil() as u64)
+        .max()
+        .unwrap_or(1);
+
+    let mut playlist = format!(
+        "#EXTM3U\n#EXT-X-VERSION:3\n#EXT-X-TARGETDURATION:{}\n#EXT-X-MEDIA-SEQUENCE:0\n#EXT-X-PLAYLIST-TYPE:VOD\n",
+        target_duration
+    );
+    for (duration, uri) in segments {
+        playlist.push_str(&format!("#EXTINF:{:.3},\n{}\n", duration, uri));
+    }
+    playlist.push_str("#EXT-X-ENDLIST\n");
+    playlist
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SRT: &str = "1
+00:00:01,000 --> 00:00:04,500
+Hello there
+
+2
+00:01:02,250 --> 00:01:05,000
+Second line
+spanning two rows
+";
+
+    #[test]
+    fn parses_srt_cues() {
+        let cues = parse_subtitles(SRT).unwrap();
+
+        assert_eq!(
+            cues,
+            vec![
+                Cue {
+                    start: 1.0,
+                    end: 4.5,
+                    settings: String::new(),
+                    text: "Hello there".to_string(),
+                },
+                Cue {
+                    start: 62.25,
+                    end: 65.0,
+                    settings: String::new(),
+                    text: "Second line\nspanning two rows".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_srt_without_counter_lines() {
+        let cues = parse_subtitles(
+            "00:00:01,000 --> 00:00:02,000\nFirst\n\n00:00:03,000 --> 00:00:04,000\nSecond\n",
+        )
+        .unwrap();
+
+        assert_eq!(cues.len(), 2);
+        assert_eq!(cues[0].text, "First");
+        assert_eq!((cues[1].start, cues[1].end), (3.0, 4.0));
+    }
+
+    #[test]
+    fn parses_srt_with_bom_and_crlf_line_endings() {
+        let content = format!("\u{feff}{}", SRT.replace('\n', "\r\n"));
+
+        assert_eq!(
+            parse_subtitles(&content).unwrap(),
+            parse_subtitles(SRT).unwrap()
+        );
+    }
+
+    #[test]
+    fn keeps_arrows_in_srt_text_out_of_the_timing() {
+        let cues = parse_subtitles("1\n00:00:01,000 --> 00:00:02,000\nLeft --> right\n").unwrap();
+
+        assert_eq!(cues[0].text, "Left -> right");
+        let webvtt = render_webvtt(&cues, None);
+        assert_eq!(webvtt.matches("-->").count(), 1);
+    }
+
+    #[test]
+    fn rejects_srt_with_invalid_timing() {
+        assert!(parse_subtitles("1\n00:00:05,000 --> 00:00:01,000\nBackwards\n").is_err());
+        assert!(parse_subtitles("1\nnot a timing\nText\n").is_err());
+        assert!(parse_subtitles("\n\n").is_err());
+    }
+
+    #[test]
+    fn strips_srt_formatting() {
+        assert_eq!(
+            strip_srt_formatting("{\\an8}Top of the screen"),
+            "Top of the screen"
+        );
+        assert_eq!(
+            strip_srt_formatting("<font color=\"#ffff00\">Yellow</font> text"),
+            "Yellow text"
+        );
+        assert_eq!(
+            strip_srt_formatting("<FONT face=\"Arial\">Loud</FONT>"),
+            "Loud"
+        );
+        // Tags WebVTT understands and braces that are not overrides stay
+        assert_eq!(
+            strip_srt_formatting("<i>Italic</i> {note}"),
+            "<i>Italic</i> {note}"
+        );
+        assert_eq!(strip_srt_formatting("a < b"), "a < b");
+    }
+
+    #[test]
+    fn strips_formatting_while_parsing_srt() {
+        let cues = parse_subtitles(
+            "1\n00:00:01,000 --> 00:00:02,000\n{\\an8}<font color=\"red\">Warning</font>\n",
+        )
+        .unwrap();
+
+        assert_eq!(cues[0].text, "Warning");
+    }
+
+    #[test]
+    fn renders_webvtt() {
+        let cues = vec![
+            Cue {
+                start: 1.0,
+                end: 4.5,
+                settings: String::new(),
+                text: "Hello".to_string(),
+            },
+            Cue {
+                start: 3661.0,
+                end: 3662.25,
+                settings: "line:0 align:start".to_string(),
+                text: "Later".to_string(),
+            },
+        ];
+
+        assert_eq!(
+            render_webvtt(&cues, None),
+            "WEBVTT\n\n00:00:01.000 --> 00:00:04.500\nHello\n\n01:01:01.000 --> 01:01:02.250 line:0 align:start\nLater\n"
+        );
+        assert!(render_webvtt(&cues, Some(900000))
+            .starts_with("WEBVTT\nX-TIMESTAMP-MAP=MPEGTS:900000,LOCAL:00:00:00.000\n\n"));
+    }
+
+    #[test]
+    fn converts_srt_to_webvtt() {
+        let webvtt = render_webvtt(&parse_subtitles(SRT).unwrap(), None);
+
+        assert_eq!(
+            parse_subtitles(&webvtt).unwrap(),
+            parse_subtitles(SRT).unwrap()
+        );
+    }
+}